@@ -0,0 +1,12 @@
+use std::path::PathBuf;
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    println!("cargo:rerun-if-changed=proto");
+    let mut config = prost_build::Config::new();
+    config.protoc_executable(protoc_bin_vendored::protoc_bin_path()?);
+    let descriptors = PathBuf::from(std::env::var("OUT_DIR")?).join("orders_descriptor.bin");
+    tonic_prost_build::configure()
+        .file_descriptor_set_path(descriptors)
+        .compile_with_config(config, &["proto/orders.proto", "proto/order_service.proto"], &["proto"])?;
+    Ok(())
+}
@@ -0,0 +1,14 @@
+CREATE OR REPLACE FUNCTION order_day(date_created TEXT) RETURNS DATE AS
+$$
+BEGIN
+    IF date_created !~ '^[0-9]{4}-[0-9]{2}-[0-9]{2}' THEN
+        RETURN NULL;
+    END IF;
+    RETURN (date_created::TIMESTAMPTZ AT TIME ZONE 'UTC')::DATE;
+EXCEPTION
+    WHEN others THEN RETURN NULL;
+END;
+$$ LANGUAGE plpgsql STABLE;
+
+REFRESH MATERIALIZED VIEW SalesDaily;
+REFRESH MATERIALIZED VIEW BrandSalesDaily;
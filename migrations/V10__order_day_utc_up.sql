@@ -0,0 +1,16 @@
+-- Timestamps written without an offset were read in the session's time zone, so the same order could
+-- land on another day depending on the connection. They are UTC now, like everywhere else in the service.
+CREATE OR REPLACE FUNCTION order_day(date_created TEXT) RETURNS DATE AS
+$$
+BEGIN
+    IF date_created !~ '^[0-9]{4}-[0-9]{2}-[0-9]{2}' THEN
+        RETURN NULL;
+    END IF;
+    RETURN (date_created::TIMESTAMPTZ AT TIME ZONE 'UTC')::DATE;
+EXCEPTION
+    WHEN others THEN RETURN NULL;
+END;
+$$ LANGUAGE plpgsql STABLE SET timezone = 'UTC';
+
+REFRESH MATERIALIZED VIEW SalesDaily;
+REFRESH MATERIALIZED VIEW BrandSalesDaily;
@@ -0,0 +1,16 @@
+CREATE TABLE Outbox
+(
+    id              BIGSERIAL PRIMARY KEY,
+    event_type      TEXT        NOT NULL,
+    aggregate_id    TEXT        NOT NULL,
+    payload         JSONB       NOT NULL,
+    created_at      TIMESTAMPTZ NOT NULL DEFAULT now(),
+    attempts        INTEGER     NOT NULL DEFAULT 0,
+    next_attempt_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    delivered_at    TIMESTAMPTZ,
+    failed_at       TIMESTAMPTZ,
+    last_error      TEXT
+);
+
+CREATE INDEX outbox_pending_idx ON Outbox (next_attempt_at, id)
+    WHERE delivered_at IS NULL AND failed_at IS NULL;
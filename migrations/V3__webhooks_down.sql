@@ -0,0 +1,2 @@
+DROP TABLE webhookdeliveries;
+DROP TABLE webhooksubscriptions;
\ No newline at end of file
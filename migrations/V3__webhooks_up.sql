@@ -0,0 +1,34 @@
+CREATE TABLE WebhookSubscriptions
+(
+    id                   BIGSERIAL PRIMARY KEY,
+    url                  TEXT        NOT NULL,
+    event_types          TEXT[]      NOT NULL,
+    delivery_service     TEXT,
+    customer_id          TEXT,
+    secret               TEXT        NOT NULL,
+    enabled              BOOLEAN     NOT NULL DEFAULT TRUE,
+    consecutive_failures INTEGER     NOT NULL DEFAULT 0,
+    created_at           TIMESTAMPTZ NOT NULL DEFAULT now(),
+    disabled_at          TIMESTAMPTZ
+);
+
+CREATE TABLE WebhookDeliveries
+(
+    id               BIGSERIAL PRIMARY KEY,
+    subscription_id  BIGINT      NOT NULL,
+    event_id         BIGINT      NOT NULL,
+    event_type       TEXT        NOT NULL,
+    payload          JSONB       NOT NULL,
+    status           TEXT        NOT NULL DEFAULT 'pending',
+    attempts         INTEGER     NOT NULL DEFAULT 0,
+    next_attempt_at  TIMESTAMPTZ NOT NULL DEFAULT now(),
+    last_status_code INTEGER,
+    last_error       TEXT,
+    created_at       TIMESTAMPTZ NOT NULL DEFAULT now(),
+    delivered_at     TIMESTAMPTZ,
+    UNIQUE (subscription_id, event_id),
+    FOREIGN KEY (subscription_id) REFERENCES WebhookSubscriptions (id) ON DELETE CASCADE
+);
+
+CREATE INDEX webhook_deliveries_pending_idx ON WebhookDeliveries (next_attempt_at, id)
+    WHERE status = 'pending';
@@ -0,0 +1,7 @@
+DROP AGGREGATE tsvector_agg(TSVECTOR);
+DROP INDEX items_search_idx;
+DROP INDEX deliveries_search_idx;
+DROP INDEX orders_search_idx;
+ALTER TABLE Items DROP COLUMN search_vector;
+ALTER TABLE Deliveries DROP COLUMN search_vector;
+ALTER TABLE Orders DROP COLUMN search_vector;
@@ -0,0 +1,31 @@
+-- Phone numbers are indexed as digits only and emails also by their parts,
+-- so that "+7 900", "7900..." and "ivan@mail" all find the order.
+ALTER TABLE Orders
+    ADD COLUMN search_vector TSVECTOR GENERATED ALWAYS AS (
+        setweight(to_tsvector('simple', coalesce(track_number, '')), 'A')
+    ) STORED;
+
+ALTER TABLE Deliveries
+    ADD COLUMN search_vector TSVECTOR GENERATED ALWAYS AS (
+        setweight(to_tsvector('simple', coalesce(name, '')), 'B') ||
+        setweight(to_tsvector('simple', regexp_replace(coalesce(phone, ''), '\D', '', 'g')), 'B') ||
+        setweight(to_tsvector('simple', coalesce(email, '')), 'B') ||
+        setweight(to_tsvector('simple', translate(coalesce(email, ''), '@.', '  ')), 'B')
+    ) STORED;
+
+ALTER TABLE Items
+    ADD COLUMN search_vector TSVECTOR GENERATED ALWAYS AS (
+        setweight(to_tsvector('simple', coalesce(name, '')), 'C') ||
+        setweight(to_tsvector('simple', coalesce(brand, '')), 'C')
+    ) STORED;
+
+CREATE INDEX orders_search_idx ON Orders USING GIN (search_vector);
+CREATE INDEX deliveries_search_idx ON Deliveries USING GIN (search_vector);
+CREATE INDEX items_search_idx ON Items USING GIN (search_vector);
+
+-- Joins the documents of an order's delivery and items into one
+CREATE AGGREGATE tsvector_agg(TSVECTOR) (
+    SFUNC = tsvector_concat,
+    STYPE = TSVECTOR,
+    INITCOND = ''
+);
@@ -0,0 +1,5 @@
+DROP INDEX items_nm_id_idx;
+DROP INDEX order_items_chrt_id_idx;
+DROP INDEX order_payments_payment_id_idx;
+DROP INDEX payments_request_id_idx;
+DROP INDEX orders_track_number_idx;
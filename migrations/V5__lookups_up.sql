@@ -0,0 +1,5 @@
+CREATE INDEX orders_track_number_idx ON Orders (track_number);
+CREATE INDEX payments_request_id_idx ON Payments (request_id);
+CREATE INDEX order_payments_payment_id_idx ON OrderPayments (payment_id);
+CREATE INDEX order_items_chrt_id_idx ON OrderItems (chrt_id);
+CREATE INDEX items_nm_id_idx ON Items (nm_id);
@@ -0,0 +1 @@
+CREATE INDEX orders_customer_idx ON Orders (customer_id, date_created DESC, order_uid);
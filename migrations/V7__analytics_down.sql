@@ -0,0 +1,3 @@
+DROP MATERIALIZED VIEW BrandSalesDaily;
+DROP MATERIALIZED VIEW SalesDaily;
+DROP FUNCTION order_day(TEXT);
@@ -0,0 +1,45 @@
+-- Orders keep date_created as client-provided text, unparsable dates are left out of the reports
+CREATE FUNCTION order_day(date_created TEXT) RETURNS DATE AS
+$$
+BEGIN
+    RETURN (date_created::TIMESTAMPTZ AT TIME ZONE 'UTC')::DATE;
+EXCEPTION
+    WHEN others THEN RETURN NULL;
+END;
+$$ LANGUAGE plpgsql STABLE;
+
+CREATE MATERIALIZED VIEW SalesDaily AS
+SELECT order_day(o.date_created)        AS day,
+       coalesce(o.delivery_service, '') AS delivery_service,
+       coalesce(d.region, '')           AS region,
+       coalesce(p.provider, '')         AS provider,
+       coalesce(p.bank, '')             AS bank,
+       coalesce(p.currency, '')         AS currency,
+       count(*)                         AS orders,
+       sum(p.amount)::BIGINT            AS revenue
+FROM Orders o
+         JOIN OrderDeliveries od ON od.order_uid = o.order_uid
+         JOIN Deliveries d ON d.id = od.delivery_id
+         JOIN OrderPayments op ON op.order_uid = o.order_uid
+         JOIN Payments p ON p.transaction = op.payment_id
+WHERE order_day(o.date_created) IS NOT NULL
+GROUP BY 1, 2, 3, 4, 5, 6;
+
+-- Unique indexes let the views be refreshed concurrently
+CREATE UNIQUE INDEX sales_daily_key ON SalesDaily (day, delivery_service, region, provider, bank, currency);
+
+CREATE MATERIALIZED VIEW BrandSalesDaily AS
+SELECT order_day(o.date_created)   AS day,
+       coalesce(i.brand, '')       AS brand,
+       coalesce(p.currency, '')    AS currency,
+       count(DISTINCT o.order_uid) AS orders,
+       sum(i.total_price)::BIGINT  AS revenue
+FROM Orders o
+         JOIN OrderItems oi ON oi.order_uid = o.order_uid
+         JOIN Items i ON i.chrt_id = oi.chrt_id
+         JOIN OrderPayments op ON op.order_uid = o.order_uid
+         JOIN Payments p ON p.transaction = op.payment_id
+WHERE order_day(o.date_created) IS NOT NULL
+GROUP BY 1, 2, 3;
+
+CREATE UNIQUE INDEX brand_sales_daily_key ON BrandSalesDaily (day, brand, currency);
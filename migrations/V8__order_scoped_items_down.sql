@@ -0,0 +1,65 @@
+-- Lossy: items with the same chrt_id collapse into the first order's row again
+DROP MATERIALIZED VIEW BrandSalesDaily;
+
+ALTER TABLE Items RENAME TO OwnedItems;
+ALTER INDEX items_search_idx RENAME TO owned_items_search_idx;
+ALTER INDEX items_nm_id_idx RENAME TO owned_items_nm_id_idx;
+
+CREATE TABLE Items
+(
+    chrt_id       INTEGER PRIMARY KEY,
+    track_number  TEXT,
+    price         INTEGER,
+    rid           TEXT,
+    name          TEXT,
+    sale          INTEGER,
+    size          TEXT,
+    total_price   INTEGER,
+    nm_id         INTEGER,
+    brand         TEXT,
+    status        INTEGER,
+    search_vector TSVECTOR GENERATED ALWAYS AS (
+        setweight(to_tsvector('simple', coalesce(name, '')), 'C') ||
+        setweight(to_tsvector('simple', coalesce(brand, '')), 'C')
+    ) STORED
+);
+
+CREATE TABLE OrderItems
+(
+    order_uid TEXT,
+    chrt_id   INTEGER,
+    PRIMARY KEY (order_uid, chrt_id),
+    FOREIGN KEY (order_uid) REFERENCES Orders (order_uid) ON DELETE CASCADE,
+    FOREIGN KEY (chrt_id) REFERENCES Items (chrt_id) ON DELETE CASCADE
+);
+
+INSERT INTO Items (chrt_id, track_number, price, rid, name, sale, size, total_price, nm_id, brand, status)
+SELECT DISTINCT ON (chrt_id) chrt_id, track_number, price, rid, name, sale, size, total_price, nm_id, brand, status
+FROM OwnedItems
+ORDER BY chrt_id, id;
+
+INSERT INTO OrderItems (order_uid, chrt_id)
+SELECT DISTINCT order_uid, chrt_id
+FROM OwnedItems;
+
+DROP TABLE OwnedItems;
+
+CREATE INDEX items_search_idx ON Items USING GIN (search_vector);
+CREATE INDEX items_nm_id_idx ON Items (nm_id);
+CREATE INDEX order_items_chrt_id_idx ON OrderItems (chrt_id);
+
+CREATE MATERIALIZED VIEW BrandSalesDaily AS
+SELECT order_day(o.date_created)   AS day,
+       coalesce(i.brand, '')       AS brand,
+       coalesce(p.currency, '')    AS currency,
+       count(DISTINCT o.order_uid) AS orders,
+       sum(i.total_price)::BIGINT  AS revenue
+FROM Orders o
+         JOIN OrderItems oi ON oi.order_uid = o.order_uid
+         JOIN Items i ON i.chrt_id = oi.chrt_id
+         JOIN OrderPayments op ON op.order_uid = o.order_uid
+         JOIN Payments p ON p.transaction = op.payment_id
+WHERE order_day(o.date_created) IS NOT NULL
+GROUP BY 1, 2, 3;
+
+CREATE UNIQUE INDEX brand_sales_daily_key ON BrandSalesDaily (day, brand, currency);
@@ -0,0 +1,63 @@
+-- Items used to be keyed by chrt_id and shared between orders through OrderItems, so an order
+-- referencing a known chrt_id got the first order's price, rid and track_number. Every order now
+-- owns its item rows, kept in the order they were sent.
+DROP MATERIALIZED VIEW BrandSalesDaily;
+
+ALTER TABLE Items RENAME TO SharedItems;
+ALTER INDEX items_search_idx RENAME TO shared_items_search_idx;
+ALTER INDEX items_nm_id_idx RENAME TO shared_items_nm_id_idx;
+
+CREATE TABLE Items
+(
+    id            BIGSERIAL PRIMARY KEY,
+    order_uid     TEXT    NOT NULL REFERENCES Orders (order_uid) ON DELETE CASCADE,
+    position      INTEGER NOT NULL,
+    chrt_id       INTEGER,
+    track_number  TEXT,
+    price         INTEGER,
+    rid           TEXT,
+    name          TEXT,
+    sale          INTEGER,
+    size          TEXT,
+    total_price   INTEGER,
+    nm_id         INTEGER,
+    brand         TEXT,
+    status        INTEGER,
+    search_vector TSVECTOR GENERATED ALWAYS AS (
+        setweight(to_tsvector('simple', coalesce(name, '')), 'C') ||
+        setweight(to_tsvector('simple', coalesce(brand, '')), 'C')
+    ) STORED,
+    UNIQUE (order_uid, position)
+);
+
+-- Each order gets its own copy of the shared rows. Whatever an order sent differently for
+-- an already known chrt_id was never stored and can't be recovered.
+INSERT INTO Items (order_uid, position, chrt_id, track_number, price, rid, name, sale, size, total_price, nm_id,
+                   brand, status)
+SELECT oi.order_uid,
+       row_number() OVER (PARTITION BY oi.order_uid ORDER BY oi.chrt_id) - 1,
+       s.chrt_id, s.track_number, s.price, s.rid, s.name, s.sale, s.size, s.total_price, s.nm_id, s.brand, s.status
+FROM OrderItems oi
+         JOIN SharedItems s ON s.chrt_id = oi.chrt_id;
+
+DROP TABLE OrderItems;
+DROP TABLE SharedItems;
+
+CREATE INDEX items_search_idx ON Items USING GIN (search_vector);
+CREATE INDEX items_chrt_id_idx ON Items (chrt_id);
+CREATE INDEX items_nm_id_idx ON Items (nm_id);
+
+CREATE MATERIALIZED VIEW BrandSalesDaily AS
+SELECT order_day(o.date_created)   AS day,
+       coalesce(i.brand, '')       AS brand,
+       coalesce(p.currency, '')    AS currency,
+       count(DISTINCT o.order_uid) AS orders,
+       sum(i.total_price)::BIGINT  AS revenue
+FROM Orders o
+         JOIN Items i ON i.order_uid = o.order_uid
+         JOIN OrderPayments op ON op.order_uid = o.order_uid
+         JOIN Payments p ON p.transaction = op.payment_id
+WHERE order_day(o.date_created) IS NOT NULL
+GROUP BY 1, 2, 3;
+
+CREATE UNIQUE INDEX brand_sales_daily_key ON BrandSalesDaily (day, brand, currency);
@@ -0,0 +1,11 @@
+CREATE OR REPLACE FUNCTION order_day(date_created TEXT) RETURNS DATE AS
+$$
+BEGIN
+    RETURN (date_created::TIMESTAMPTZ AT TIME ZONE 'UTC')::DATE;
+EXCEPTION
+    WHEN others THEN RETURN NULL;
+END;
+$$ LANGUAGE plpgsql STABLE;
+
+REFRESH MATERIALIZED VIEW SalesDaily;
+REFRESH MATERIALIZED VIEW BrandSalesDaily;
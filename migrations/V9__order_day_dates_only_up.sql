@@ -0,0 +1,16 @@
+-- Postgres also reads words such as 'yesterday', 'now' or 'epoch' as timestamps, which put such orders on
+-- whatever day the views were refreshed. Only dates written out as YYYY-MM-DD count now.
+CREATE OR REPLACE FUNCTION order_day(date_created TEXT) RETURNS DATE AS
+$$
+BEGIN
+    IF date_created !~ '^[0-9]{4}-[0-9]{2}-[0-9]{2}' THEN
+        RETURN NULL;
+    END IF;
+    RETURN (date_created::TIMESTAMPTZ AT TIME ZONE 'UTC')::DATE;
+EXCEPTION
+    WHEN others THEN RETURN NULL;
+END;
+$$ LANGUAGE plpgsql STABLE;
+
+REFRESH MATERIALIZED VIEW SalesDaily;
+REFRESH MATERIALIZED VIEW BrandSalesDaily;
@@ -1,15 +1,54 @@
 use {
     crate::{
         domain::interfaces::{OrderService, self},
+        infrastructure::{Authenticator, OrderBroadcaster, RateLimiter},
     },
-    std::{ops::Deref, error::Error}
+    std::{ops::Deref, error::Error, sync::Arc, time::Duration},
+    tokio::sync::Semaphore
 };
 
 type Repository = dyn interfaces::Repository<Error = Box<dyn Error>>;
+type WebhookStore = dyn interfaces::WebhookStore<Error = Box<dyn Error>>;
+type OrderSearch = dyn interfaces::OrderSearch<Error = Box<dyn Error>>;
+type SalesAnalytics = dyn interfaces::SalesAnalytics<Error = Box<dyn Error>>;
+type OrderExport = dyn interfaces::OrderExport<Error = Box<dyn Error>>;
+
+/// Limits and policies of the HTTP layer.
+#[derive(Clone, Debug)]
+pub struct HttpSettings {
+    pub max_order_body: usize,
+    pub read_timeout: Duration,
+    pub write_timeout: Duration,
+    /// Allowed browser origins, `*` allows any, CORS is off when empty
+    pub cors_origins: Vec<String>,
+    pub compression: bool,
+}
+
+impl Default for HttpSettings {
+    fn default() -> Self {
+        Self {
+            max_order_body: 1024 * 1024,
+            read_timeout: Duration::from_secs(10),
+            write_timeout: Duration::from_secs(30),
+            cors_origins: Vec::new(),
+            compression: true,
+        }
+    }
+}
 
 pub struct AppState {
     repository: Box<Repository>,
     order_service: Box<dyn OrderService>,
+    webhooks: Option<Box<WebhookStore>>,
+    search: Option<Box<OrderSearch>>,
+    analytics: Option<Box<SalesAnalytics>>,
+    export: Option<Box<OrderExport>>,
+    order_stream: Option<Arc<OrderBroadcaster>>,
+    authenticator: Box<dyn interfaces::Authenticator>,
+    read_limiter: Option<RateLimiter>,
+    write_limiter: Option<RateLimiter>,
+    db_permits: Option<Arc<Semaphore>>,
+    http: HttpSettings,
 }
 impl AppState {
     pub fn new(
@@ -19,14 +58,110 @@ impl AppState {
         Self {
             repository,
             order_service,
+            webhooks: None,
+            search: None,
+            analytics: None,
+            export: None,
+            order_stream: None,
+            authenticator: Box::new(Authenticator::disabled()),
+            read_limiter: None,
+            write_limiter: None,
+            db_permits: None,
+            http: HttpSettings::default(),
         }
     }
 
+    pub fn with_webhooks(mut self, webhooks: Box<WebhookStore>) -> Self {
+        self.webhooks = Some(webhooks);
+        self
+    }
+
+    pub fn with_search(mut self, search: Box<OrderSearch>) -> Self {
+        self.search = Some(search);
+        self
+    }
+
+    pub fn with_analytics(mut self, analytics: Box<SalesAnalytics>) -> Self {
+        self.analytics = Some(analytics);
+        self
+    }
+
+    pub fn with_export(mut self, export: Box<OrderExport>) -> Self {
+        self.export = Some(export);
+        self
+    }
+
+    pub fn with_order_stream(mut self, order_stream: Arc<OrderBroadcaster>) -> Self {
+        self.order_stream = Some(order_stream);
+        self
+    }
+
+    pub fn with_authenticator(mut self, authenticator: Box<dyn interfaces::Authenticator>) -> Self {
+        self.authenticator = authenticator;
+        self
+    }
+
+    pub fn with_rate_limits(mut self, read: Option<RateLimiter>, write: Option<RateLimiter>) -> Self {
+        self.read_limiter = read;
+        self.write_limiter = write;
+        self
+    }
+
+    pub fn with_db_concurrency(mut self, limit: usize) -> Self {
+        self.db_permits = Some(Arc::new(Semaphore::new(limit)));
+        self
+    }
+
+    pub fn with_http_settings(mut self, http: HttpSettings) -> Self {
+        self.http = http;
+        self
+    }
+
     pub fn repository(&self) -> &Repository {
         self.repository.deref()
     }
 
-    pub fn order_service(&self) -> &(dyn OrderService) { 
+    pub fn order_service(&self) -> &dyn OrderService {
         self.order_service.deref()
     }
+
+    pub fn webhooks(&self) -> Option<&WebhookStore> {
+        self.webhooks.as_deref()
+    }
+
+    pub fn search(&self) -> Option<&OrderSearch> {
+        self.search.as_deref()
+    }
+
+    pub fn analytics(&self) -> Option<&SalesAnalytics> {
+        self.analytics.as_deref()
+    }
+
+    pub fn export(&self) -> Option<&OrderExport> {
+        self.export.as_deref()
+    }
+
+    pub fn authenticator(&self) -> &dyn interfaces::Authenticator {
+        self.authenticator.deref()
+    }
+
+    pub fn order_stream(&self) -> Option<&OrderBroadcaster> {
+        self.order_stream.as_deref()
+    }
+
+    pub fn read_limiter(&self) -> Option<&RateLimiter> {
+        self.read_limiter.as_ref()
+    }
+
+    pub fn write_limiter(&self) -> Option<&RateLimiter> {
+        self.write_limiter.as_ref()
+    }
+
+    pub fn db_permits(&self) -> Option<&Arc<Semaphore>> {
+        self.db_permits.as_ref()
+    }
+
+    pub fn http_settings(&self) -> &HttpSettings {
+        &self.http
+    }
 }
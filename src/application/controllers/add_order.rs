@@ -1,23 +1,51 @@
 use {
     crate::{
-        application::{AppState, controllers::error_handler},
+        application::{AppState, controllers::{error_handler, ErrorBody}, wire::OrderBody},
         domain::{
-            models::Order,
+            models::{Order, Principal},
         },
     },
-    axum::{extract::State, http::StatusCode, Json},
+    axum::{extract::State, http::StatusCode, Extension, Json},
     serde_json::{Value, json},
     std::sync::Arc,
     log::{log, Level}
 };
 
+#[utoipa::path(
+    post,
+    path = "/add_order",
+    tag = "orders",
+    request_body(
+        description = "Order as JSON, MessagePack or protobuf `Order` from `proto/orders.proto`, told apart by `Content-Type`",
+        content(
+            (Order = "application/json"),
+            (Order = "application/msgpack"),
+            (Order = "application/x-protobuf"),
+        )
+    ),
+    security(("api_key" = ["orders:write"]), ("bearer" = ["orders:write"])),
+    responses(
+        (status = 201, description = "Order saved"),
+        (status = 400, description = "Order can't be saved", body = ErrorBody),
+        (status = 409, description = "Order or its unique part already exists", body = ErrorBody),
+        (status = 401, description = "Authentication required", body = ErrorBody),
+        (status = 403, description = "Missing orders:write scope", body = ErrorBody),
+        (status = 413, description = "Order body is larger than allowed"),
+        (status = 415, description = "Body is not JSON, MessagePack or protobuf"),
+        (status = 422, description = "Malformed order or it references missing data", body = ErrorBody),
+        (status = 429, description = "Rate limit exceeded, retry after `Retry-After` seconds", body = ErrorBody),
+        (status = 500, description = "Internal error"),
+        (status = 503, description = "Too many concurrent database requests or the request timed out", body = ErrorBody),
+    )
+)]
 pub async fn add_order(
     State(state): State<Arc<AppState>>,
-    Json(order): Json<Order>,
+    Extension(principal): Extension<Principal>,
+    OrderBody(order): OrderBody,
 ) -> (StatusCode, Json<Value>)
 {
     log!(target: "add_order_controller", Level::Info, "Got new order: {order:?}");
-    let result = state.order_service().add_order(state.repository(), order).await;
+    let result = state.order_service().add_order(state.repository(), order, &principal).await;
     if let Err(err) = result {
         return error_handler::handler(err);
     }
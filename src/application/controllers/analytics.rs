@@ -0,0 +1,61 @@
+use {
+    crate::{
+        application::{AppState, controllers::{error_handler, ErrorBody}},
+        domain::models::{DateRange, SalesDimension, SalesReport},
+    },
+    axum::{
+        extract::{Path, Query, State},
+        http::StatusCode,
+        Json,
+    },
+    serde::Deserialize,
+    serde_json::{Value, json},
+    std::sync::Arc,
+    utoipa::IntoParams,
+    log::{log, Level}
+};
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct RangeQuery {
+    /// First day of the report, `YYYY-MM-DD`
+    from: Option<String>,
+    /// Last day of the report, `YYYY-MM-DD`
+    to: Option<String>,
+}
+
+#[utoipa::path(
+    get,
+    path = "/analytics/{dimension}",
+    tag = "analytics",
+    params(("dimension" = SalesDimension, Path, description = "What sales are grouped by"), RangeQuery),
+    security(("api_key" = ["analytics:read"]), ("bearer" = ["analytics:read"])),
+    responses(
+        (status = 200, description = "Orders and revenue per group and currency", body = SalesReport),
+        (status = 400, description = "Unknown dimension or malformed date", body = ErrorBody),
+        (status = 401, description = "Authentication required", body = ErrorBody),
+        (status = 403, description = "Missing analytics:read scope", body = ErrorBody),
+        (status = 429, description = "Rate limit exceeded, retry after `Retry-After` seconds", body = ErrorBody),
+        (status = 500, description = "Internal error"),
+        (status = 501, description = "Analytics are not supported by this storage", body = ErrorBody),
+        (status = 503, description = "Too many concurrent database requests or the request timed out", body = ErrorBody),
+    )
+)]
+pub async fn sales_report(
+    State(state): State<Arc<AppState>>,
+    Path(dimension): Path<SalesDimension>,
+    Query(query): Query<RangeQuery>,
+) -> (StatusCode, Json<Value>) {
+    let Some(analytics) = state.analytics() else {
+        return (StatusCode::NOT_IMPLEMENTED, Json(json!({"error": "Analytics are not supported by this storage"})));
+    };
+    let range = DateRange { from: query.from, to: query.to };
+    if let Some(invalid) = range.malformed() {
+        return (StatusCode::BAD_REQUEST, Json(json!({"error": format!("Malformed date {invalid}, expected YYYY-MM-DD")})));
+    }
+    log!(target: "analytics_controller", Level::Info, "Got new sales report request by {dimension:?}");
+    match analytics.sales(dimension, &range).await {
+        Ok(rows) => (StatusCode::OK, Json(json!(SalesReport { dimension, range, rows }))),
+        Err(err) => error_handler::handler(err),
+    }
+}
@@ -0,0 +1,62 @@
+use {
+    crate::{
+        application::{AppState, controllers::{error_handler, paging, ErrorBody}},
+        domain::models::{CustomerOrders, Principal},
+    },
+    axum::{
+        extract::{Path, Query, State},
+        http::StatusCode,
+        Extension, Json,
+    },
+    serde::Deserialize,
+    serde_json::{Value, json},
+    std::sync::Arc,
+    utoipa::IntoParams,
+    log::{log, Level}
+};
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct PageQuery {
+    /// Page size, 20 by default, at most 100
+    limit: Option<i64>,
+    /// Number of orders to skip
+    offset: Option<i64>,
+}
+
+#[utoipa::path(
+    get,
+    path = "/customers/{customer_id}/orders",
+    tag = "orders",
+    params(("customer_id" = String, Path, description = "Customer identifier"), PageQuery),
+    security(("api_key" = ["orders:read"]), ("bearer" = ["orders:read"])),
+    responses(
+        (status = 200, description = "Customer's orders, newest first, with totals", body = CustomerOrders),
+        (status = 400, description = "Invalid paging", body = ErrorBody),
+        (status = 401, description = "Authentication required", body = ErrorBody),
+        (status = 403, description = "Missing orders:read scope", body = ErrorBody),
+        (status = 429, description = "Rate limit exceeded, retry after `Retry-After` seconds", body = ErrorBody),
+        (status = 500, description = "Internal error"),
+        (status = 503, description = "Too many concurrent database requests or the request timed out", body = ErrorBody),
+    )
+)]
+pub async fn customer_orders(
+    State(state): State<Arc<AppState>>,
+    Extension(principal): Extension<Principal>,
+    Path(customer_id): Path<String>,
+    Query(query): Query<PageQuery>,
+) -> (StatusCode, Json<Value>) {
+    let (limit, offset) = match paging::bounds(query.limit, query.offset) {
+        Ok(bounds) => bounds,
+        Err(response) => return response,
+    };
+    log!(target: "customer_orders_controller", Level::Info, "Got new request for orders of customer {customer_id}");
+    match state
+        .order_service()
+        .customer_orders(&customer_id, limit, offset, state.repository(), &principal)
+        .await
+    {
+        Ok(orders) => (StatusCode::OK, Json(json!(orders))),
+        Err(err) => error_handler::handler(err),
+    }
+}
@@ -3,15 +3,37 @@ use {
     axum::{http::StatusCode, Json},
     serde_json::{Value, json},
     tokio_postgres::error::SqlState,
-    crate::infrastructure::MultiError
+    crate::infrastructure::{is_conflict, CircuitOpen, DeadlineExceeded, MultiError},
+    serde::Serialize,
+    utoipa::ToSchema,
+    log::{log, Level}
 };
 
+/// Body of client error responses, server errors come with an empty object.
+#[derive(Serialize, ToSchema)]
+pub struct ErrorBody {
+    pub error: String,
+}
+
 pub fn handler(error: Box<dyn Error>) -> (StatusCode, Json<Value>) {
     let multi_error = error.downcast_ref::<MultiError>();
     let pool_error = error.downcast_ref::<deadpool_postgres::PoolError>();
-    if multi_error.is_some() || pool_error.is_some() {
+    // Our own data failing to (de)serialize is never the client's fault
+    let json_error = error.downcast_ref::<serde_json::Error>();
+    let encode_error = error.downcast_ref::<rmp_serde::encode::Error>();
+    if multi_error.is_some() || pool_error.is_some() || json_error.is_some() || encode_error.is_some() {
+        log!(target: "error_handler", Level::Error, "Internal error: {error}");
         return (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({})));
     }
+    if error.is::<DeadlineExceeded>() {
+        return (StatusCode::SERVICE_UNAVAILABLE, Json(json!({"error": "Request timed out"})));
+    }
+    if error.is::<CircuitOpen>() {
+        return (StatusCode::SERVICE_UNAVAILABLE, Json(json!({"error": "Database is unavailable"})));
+    }
+    if is_conflict(error.as_ref()) {
+        return (StatusCode::CONFLICT, Json(json!({"error" : "Order or its unique part already exists"})));
+    }
     let db_error = error.downcast_ref::<tokio_postgres::Error>();
     if db_error.is_none() {
         return (StatusCode::BAD_REQUEST, Json(json!({"error": error.to_string()})));
@@ -21,9 +43,18 @@ pub fn handler(error: Box<dyn Error>) -> (StatusCode, Json<Value>) {
         Some(&SqlState::FOREIGN_KEY_VIOLATION) => {
             (StatusCode::UNPROCESSABLE_ENTITY, Json(json!({"error" : "Can't save this order"})))
         },
-        Some(&SqlState::UNIQUE_VIOLATION) => {
-            (StatusCode::CONFLICT, Json(json!({"error" : "Order or its unique part already exists"})))
+        Some(&SqlState::DATETIME_FIELD_OVERFLOW | &SqlState::INVALID_DATETIME_FORMAT) => {
+            (StatusCode::BAD_REQUEST, Json(json!({"error": "Invalid date"})))
+        }
+        Some(&SqlState::QUERY_CANCELED) => {
+            (StatusCode::SERVICE_UNAVAILABLE, Json(json!({"error": "Request timed out"})))
+        }
+        Some(&SqlState::T_R_SERIALIZATION_FAILURE | &SqlState::T_R_DEADLOCK_DETECTED) => {
+            (StatusCode::SERVICE_UNAVAILABLE, Json(json!({"error": "Database is busy, try again"})))
+        }
+        _ => {
+            log!(target: "error_handler", Level::Error, "Database error: {db_error}");
+            (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({})))
         }
-        _ => (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({}))),
     }
 }
\ No newline at end of file
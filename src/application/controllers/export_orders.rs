@@ -0,0 +1,107 @@
+use {
+    crate::{
+        application::{AppState, controllers::{error_handler, ErrorBody}},
+        domain::models::{DateRange, OrderFilter, Principal},
+        infrastructure::{encode, ExportFormat},
+    },
+    axum::{
+        body::Body,
+        extract::{Query, State},
+        http::{header, StatusCode},
+        response::{IntoResponse, Response},
+        Extension, Json,
+    },
+    futures::TryStreamExt,
+    serde::Deserialize,
+    serde_json::json,
+    std::sync::Arc,
+    utoipa::{IntoParams, ToSchema},
+    log::{log, Level}
+};
+
+#[derive(Deserialize, ToSchema, Default, Clone, Copy)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportFileFormat {
+    /// A row per item with `delivery_*`, `payment_*` and `item_*` columns
+    #[default]
+    Csv,
+    /// The CSV rows in a columnar file
+    Parquet,
+    /// An order per line
+    Ndjson,
+}
+
+impl From<ExportFileFormat> for ExportFormat {
+    fn from(format: ExportFileFormat) -> Self {
+        match format {
+            ExportFileFormat::Csv => ExportFormat::Csv,
+            ExportFileFormat::Parquet => ExportFormat::Parquet,
+            ExportFileFormat::Ndjson => ExportFormat::Ndjson,
+        }
+    }
+}
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ExportQuery {
+    /// File format, `csv` by default
+    #[param(inline)]
+    format: Option<ExportFileFormat>,
+    /// First day of `date_created`, `YYYY-MM-DD`
+    from: Option<String>,
+    /// Last day of `date_created`, `YYYY-MM-DD`
+    to: Option<String>,
+}
+
+#[utoipa::path(
+    get,
+    path = "/orders/export",
+    tag = "orders",
+    params(ExportQuery),
+    security(("api_key" = ["orders:read"]), ("bearer" = ["orders:read"])),
+    responses(
+        (status = 200, description = "Orders oldest first, streamed as they are read. A failure midway cuts the transfer short",
+            content(
+                (String = "text/csv"),
+                (String = "application/vnd.apache.parquet"),
+                (String = "application/x-ndjson"),
+            )),
+        (status = 400, description = "Unknown format or malformed date", body = ErrorBody),
+        (status = 401, description = "Authentication required", body = ErrorBody),
+        (status = 403, description = "Missing orders:read scope", body = ErrorBody),
+        (status = 429, description = "Rate limit exceeded, retry after `Retry-After` seconds", body = ErrorBody),
+        (status = 500, description = "Internal error"),
+        (status = 501, description = "Export is not supported by this storage", body = ErrorBody),
+    )
+)]
+pub async fn export_orders(
+    State(state): State<Arc<AppState>>,
+    Extension(principal): Extension<Principal>,
+    Query(query): Query<ExportQuery>,
+) -> Response {
+    let Some(export) = state.export() else {
+        return (StatusCode::NOT_IMPLEMENTED, Json(json!({"error": "Export is not supported by this storage"}))).into_response();
+    };
+    let range = DateRange { from: query.from, to: query.to };
+    if let Some(invalid) = range.malformed() {
+        return (StatusCode::BAD_REQUEST, Json(json!({"error": format!("Malformed date {invalid}, expected YYYY-MM-DD")})))
+            .into_response();
+    }
+    let format = ExportFormat::from(query.format.unwrap_or_default());
+    log!(target: "audit", Level::Info, "{principal} exported orders as {format} from {:?} to {:?}", range.from, range.to);
+    let orders = match export.export(&OrderFilter { lookup: None, range }).await {
+        Ok(orders) => orders,
+        Err(err) => return error_handler::handler(err).into_response(),
+    };
+    let chunks = encode(orders, format.encoder()).inspect_err(|err| {
+        log!(target: "export_orders_controller", Level::Error, "Export failed midway, error: {err}");
+    });
+    (
+        [
+            (header::CONTENT_TYPE, format.content_type().to_string()),
+            (header::CONTENT_DISPOSITION, format!("attachment; filename=\"orders.{}\"", format.extension())),
+        ],
+        Body::from_stream(chunks),
+    )
+        .into_response()
+}
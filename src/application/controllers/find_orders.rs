@@ -0,0 +1,95 @@
+use {
+    crate::{
+        application::{AppState, controllers::{error_handler, paging, ErrorBody}},
+        domain::models::{OrderLookup, OrderPage, Principal},
+    },
+    axum::{
+        extract::{Query, State},
+        http::StatusCode,
+        Extension, Json,
+    },
+    serde::Deserialize,
+    serde_json::{Value, json},
+    std::sync::Arc,
+    utoipa::IntoParams,
+    log::{log, Level}
+};
+
+/// Exactly one of the keys has to be given.
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct LookupQuery {
+    /// Orders with this track number
+    track_number: Option<String>,
+    /// Order paid by this payment transaction
+    transaction: Option<String>,
+    /// Orders paid with this payment request id
+    request_id: Option<String>,
+    /// Orders containing an item with this `chrt_id`
+    chrt_id: Option<i32>,
+    /// Orders containing an item with this `nm_id`
+    nm_id: Option<i32>,
+    /// Page size, 20 by default, at most 100
+    limit: Option<i64>,
+    /// Number of orders to skip
+    offset: Option<i64>,
+}
+
+impl LookupQuery {
+    fn lookup(&self) -> Option<OrderLookup> {
+        let keys = [
+            self.track_number.clone().map(OrderLookup::TrackNumber),
+            self.transaction.clone().map(OrderLookup::Transaction),
+            self.request_id.clone().map(OrderLookup::RequestId),
+            self.chrt_id.map(OrderLookup::ChrtId),
+            self.nm_id.map(OrderLookup::NmId),
+        ];
+        let mut given = keys.into_iter().flatten();
+        match (given.next(), given.next()) {
+            (Some(lookup), None) => Some(lookup),
+            _ => None,
+        }
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/orders",
+    tag = "orders",
+    params(LookupQuery),
+    security(("api_key" = ["orders:read"]), ("bearer" = ["orders:read"])),
+    responses(
+        (status = 200, description = "Matching orders, newest first", body = OrderPage),
+        (status = 400, description = "Not exactly one lookup key or invalid paging", body = ErrorBody),
+        (status = 401, description = "Authentication required", body = ErrorBody),
+        (status = 403, description = "Missing orders:read scope", body = ErrorBody),
+        (status = 429, description = "Rate limit exceeded, retry after `Retry-After` seconds", body = ErrorBody),
+        (status = 500, description = "Internal error"),
+        (status = 503, description = "Too many concurrent database requests or the request timed out", body = ErrorBody),
+    )
+)]
+pub async fn find_orders(
+    State(state): State<Arc<AppState>>,
+    Extension(principal): Extension<Principal>,
+    Query(query): Query<LookupQuery>,
+) -> (StatusCode, Json<Value>) {
+    let Some(lookup) = query.lookup() else {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(json!({"error": "Exactly one of track_number, transaction, request_id, chrt_id or nm_id is required"})),
+        );
+    };
+    let (limit, offset) = match paging::bounds(query.limit, query.offset) {
+        Ok(bounds) => bounds,
+        Err(response) => return response,
+    };
+    log!(target: "find_orders_controller", Level::Info, "Got new lookup by {lookup}");
+    match state
+        .order_service()
+        .find_orders(&lookup, limit + 1, offset, state.repository(), &principal)
+        .await
+    {
+        Ok(orders) => (StatusCode::OK, Json(json!(paging::page(orders, limit, offset)))),
+        Err(err) => error_handler::handler(err),
+    }
+}
@@ -1,31 +1,57 @@
 use {
     crate::{
-        application::{AppState, controllers::error_handler},
+        application::{AppState, controllers::{error_handler, ErrorBody}, wire::WireFormat},
+        domain::models::{Order, Principal},
     },
     axum::{
         extract::{Path, State},
-        http::StatusCode,
-        Json,
+        http::{HeaderMap, StatusCode},
+        response::{IntoResponse, Response},
+        Extension, Json,
     },
     std::sync::Arc,
-    serde_json::{Value, json},
+    serde_json::json,
     log::{log, Level}
 };
 
+#[utoipa::path(
+    get,
+    path = "/order/{order_uid}",
+    tag = "orders",
+    params(
+        ("order_uid" = String, Path, description = "Order identifier"),
+        ("Accept" = Option<String>, Header, description = "`application/msgpack` or `application/x-protobuf` instead of JSON"),
+    ),
+    security(("api_key" = ["orders:read"]), ("bearer" = ["orders:read"])),
+    responses(
+        (status = 200, description = "Order found, in the format asked for by `Accept`",
+            content(
+                (Order = "application/json"),
+                (Order = "application/msgpack"),
+                (Order = "application/x-protobuf"),
+            )),
+        (status = 401, description = "Authentication required", body = ErrorBody),
+        (status = 403, description = "Missing orders:read scope", body = ErrorBody),
+        (status = 404, description = "Order with given uid not found", body = ErrorBody),
+        (status = 429, description = "Rate limit exceeded, retry after `Retry-After` seconds", body = ErrorBody),
+        (status = 500, description = "Internal error"),
+        (status = 503, description = "Too many concurrent database requests or the request timed out", body = ErrorBody),
+    )
+)]
 pub async fn get_order(
     State(state): State<Arc<AppState>>,
+    Extension(principal): Extension<Principal>,
     Path(order_uid): Path<String>,
-) -> (StatusCode, Json<Value>) {
+    headers: HeaderMap,
+) -> Response {
     log!(target: "get_order_controller", Level::Info, "Got new get-request by order_uid: {order_uid}");
-    match state.order_service().get_order(&order_uid, state.repository()).await {
-        Ok(Some(order)) => {
-            (StatusCode::OK, Json(serde_json::to_value(order).unwrap()))
-        }
+    match state.order_service().get_order(&order_uid, state.repository(), &principal).await {
+        Ok(Some(order)) => WireFormat::accepted(&headers).respond(StatusCode::OK, &order),
         Ok(None) => {
-            (StatusCode::NOT_FOUND, Json(json!({"error": "Order with given uid not found"})))
+            (StatusCode::NOT_FOUND, Json(json!({"error": "Order with given uid not found"}))).into_response()
         },
         Err(err) => {
-            error_handler::handler(err)
+            error_handler::handler(err).into_response()
         }
     }
     
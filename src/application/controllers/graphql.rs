@@ -0,0 +1,55 @@
+use {
+    crate::{
+        application::{AppState, controllers::ErrorBody, graphql::{schema, OrderLoader}},
+        domain::models::Principal,
+        infrastructure::deadline,
+    },
+    async_graphql::{dataloader::DataLoader, http::GraphiQLSource},
+    axum::{extract::State, response::Html, Extension, Json},
+    std::sync::Arc,
+    log::{log, Level}
+};
+
+#[utoipa::path(
+    post,
+    path = "/graphql",
+    tag = "orders",
+    request_body(
+        description = "GraphQL request with `query`, optional `operationName` and `variables`. The schema is served by introspection",
+        content((Object = "application/json"))
+    ),
+    security(("api_key" = ["orders:read"]), ("bearer" = ["orders:read"])),
+    responses(
+        (status = 200, description = "`data` with the selected fields and `errors` for the ones that failed, \
+            each with the status the HTTP route would answer with in `extensions.status`", body = Object),
+        (status = 400, description = "Body is not a GraphQL request", body = ErrorBody),
+        (status = 401, description = "Authentication required", body = ErrorBody),
+        (status = 403, description = "Missing orders:read scope", body = ErrorBody),
+        (status = 429, description = "Rate limit exceeded, retry after `Retry-After` seconds", body = ErrorBody),
+        (status = 503, description = "Too many concurrent database requests or the request timed out", body = ErrorBody),
+    )
+)]
+pub async fn graphql_query(
+    State(state): State<Arc<AppState>>,
+    Extension(principal): Extension<Principal>,
+    Json(request): Json<async_graphql::Request>,
+) -> Json<async_graphql::Response> {
+    log!(target: "graphql_controller", Level::Info, "Got new query: {}", request.query);
+    // Batches run in their own tasks, which have to keep the request's deadline
+    let deadline = deadline::current();
+    let loader = DataLoader::new(OrderLoader::new(state.clone(), principal.clone()), move |batch| {
+        tokio::spawn(async move {
+            match deadline {
+                Some(deadline) => deadline::with_deadline(deadline, batch).await,
+                None => batch.await,
+            }
+        })
+    });
+    let request = request.data(state).data(principal).data(loader);
+    Json(schema().execute(request).await)
+}
+
+/// GraphiQL page for trying queries out in a browser.
+pub async fn graphiql() -> Html<String> {
+    Html(GraphiQLSource::build().endpoint("/graphql").finish())
+}
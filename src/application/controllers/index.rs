@@ -0,0 +1,8 @@
+use axum::response::Html;
+
+const INDEX: &str = include_str!("../static/index.html");
+
+/// Order lookup page, it's embedded into the binary, so the service has no runtime assets.
+pub async fn index() -> Html<&'static str> {
+    Html(INDEX)
+}
@@ -1,6 +1,26 @@
 mod add_order;
 mod get_order;
-mod error_handler;
+pub(crate) mod error_handler;
+mod webhooks;
+mod stream_orders;
+mod index;
+mod search_orders;
+mod find_orders;
+mod customer_orders;
+mod analytics;
+mod export_orders;
+mod graphql;
+pub(crate) mod paging;
 
+pub use error_handler::ErrorBody;
 pub use add_order::*;
-pub use get_order::*;
\ No newline at end of file
+pub use get_order::*;
+pub use webhooks::*;
+pub use stream_orders::*;
+pub use index::*;
+pub use search_orders::*;
+pub use find_orders::*;
+pub use customer_orders::*;
+pub use analytics::*;
+pub use export_orders::*;
+pub use graphql::*;
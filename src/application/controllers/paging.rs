@@ -0,0 +1,26 @@
+use {
+    crate::domain::models::{OrderPage, OrderSummary},
+    axum::{http::StatusCode, Json},
+    serde_json::{Value, json},
+};
+
+pub(crate) const DEFAULT_LIMIT: i64 = 20;
+pub(crate) const MAX_LIMIT: i64 = 100;
+
+/// Validates paging parameters. Stores are asked for `limit + 1` rows, the extra one tells whether
+/// there is a next page.
+pub(crate) fn bounds(limit: Option<i64>, offset: Option<i64>) -> Result<(i64, i64), (StatusCode, Json<Value>)> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT);
+    let offset = offset.unwrap_or(0);
+    if !(1..=MAX_LIMIT).contains(&limit) || offset < 0 {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(json!({"error": format!("limit must be between 1 and {MAX_LIMIT}, offset must not be negative")})),
+        ));
+    }
+    Ok((limit, offset))
+}
+
+pub(super) fn page(orders: Vec<OrderSummary>, limit: i64, offset: i64) -> OrderPage {
+    OrderPage::from_overfetched(orders, limit, offset)
+}
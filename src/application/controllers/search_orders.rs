@@ -0,0 +1,66 @@
+use {
+    crate::{
+        application::{AppState, controllers::{error_handler, paging, ErrorBody}},
+        domain::models::OrderPage,
+    },
+    axum::{
+        extract::{Query, State},
+        http::StatusCode,
+        Json,
+    },
+    serde::Deserialize,
+    serde_json::{Value, json},
+    std::sync::Arc,
+    utoipa::IntoParams,
+    log::{log, Level}
+};
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct SearchQuery {
+    /// Words to look for in track number, recipient name, phone, email, item names and brands
+    q: String,
+    /// Page size, 20 by default, at most 100
+    limit: Option<i64>,
+    /// Number of results to skip
+    offset: Option<i64>,
+}
+
+#[utoipa::path(
+    get,
+    path = "/orders/search",
+    tag = "orders",
+    params(SearchQuery),
+    security(("api_key" = ["orders:read"]), ("bearer" = ["orders:read"])),
+    responses(
+        (status = 200, description = "Matching orders, best first", body = OrderPage),
+        (status = 400, description = "Empty query or invalid paging", body = ErrorBody),
+        (status = 401, description = "Authentication required", body = ErrorBody),
+        (status = 403, description = "Missing orders:read scope", body = ErrorBody),
+        (status = 429, description = "Rate limit exceeded, retry after `Retry-After` seconds", body = ErrorBody),
+        (status = 500, description = "Internal error"),
+        (status = 501, description = "Search is not supported by this storage", body = ErrorBody),
+        (status = 503, description = "Too many concurrent database requests or the request timed out", body = ErrorBody),
+    )
+)]
+pub async fn search_orders(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<SearchQuery>,
+) -> (StatusCode, Json<Value>) {
+    let Some(search) = state.search() else {
+        return (StatusCode::NOT_IMPLEMENTED, Json(json!({"error": "Search is not supported by this storage"})));
+    };
+    let text = query.q.trim();
+    if text.is_empty() {
+        return (StatusCode::BAD_REQUEST, Json(json!({"error": "q must not be empty"})));
+    }
+    let (limit, offset) = match paging::bounds(query.limit, query.offset) {
+        Ok(bounds) => bounds,
+        Err(response) => return response,
+    };
+    log!(target: "search_orders_controller", Level::Info, "Searching orders for \"{text}\"");
+    match search.search(text, limit + 1, offset).await {
+        Ok(orders) => (StatusCode::OK, Json(json!(paging::page(orders, limit, offset)))),
+        Err(err) => error_handler::handler(err),
+    }
+}
@@ -0,0 +1,110 @@
+use {
+    crate::{
+        application::{AppState, controllers::ErrorBody},
+        domain::models::Order,
+        infrastructure::StreamedOrder,
+    },
+    axum::{
+        extract::{Query, State},
+        http::{HeaderMap, StatusCode},
+        response::{sse::{Event, KeepAlive, Sse}, IntoResponse, Response},
+        Json,
+    },
+    futures::{stream, StreamExt},
+    serde::Deserialize,
+    serde_json::json,
+    std::{convert::Infallible, sync::Arc},
+    tokio_stream::wrappers::BroadcastStream,
+    utoipa::IntoParams,
+    log::{log, Level}
+};
+
+#[derive(Deserialize, IntoParams, Default)]
+#[into_params(parameter_in = Query)]
+pub struct StreamFilter {
+    /// Only orders with this `entry`
+    entry: Option<String>,
+    /// Only orders with this `delivery_service`
+    delivery_service: Option<String>,
+    /// Only orders with this `locale`
+    locale: Option<String>,
+}
+
+impl StreamFilter {
+    fn matches(&self, order: &Order) -> bool {
+        fn check(expected: &Option<String>, actual: &str) -> bool {
+            expected.as_deref().is_none_or(|expected| expected == actual)
+        }
+        check(&self.entry, &order.entry)
+            && check(&self.delivery_service, &order.delivery_service)
+            && check(&self.locale, &order.locale)
+    }
+}
+
+fn to_event(streamed: &StreamedOrder) -> Option<Result<Event, Infallible>> {
+    let event = Event::default()
+        .id(streamed.id.to_string())
+        .event("order")
+        .json_data(&streamed.order)
+        .ok()?;
+    Some(Ok(event))
+}
+
+#[utoipa::path(
+    get,
+    path = "/orders/stream",
+    tag = "orders",
+    params(
+        StreamFilter,
+        ("Last-Event-ID" = Option<u64>, Header, description = "Replay buffered orders newer than this event id"),
+    ),
+    security(("api_key" = ["orders:read"]), ("bearer" = ["orders:read"])),
+    responses(
+        (status = 200, description = "Stream of `order` events carrying accepted orders", body = Order, content_type = "text/event-stream"),
+        (status = 401, description = "Authentication required", body = ErrorBody),
+        (status = 403, description = "Missing orders:read scope", body = ErrorBody),
+        (status = 429, description = "Rate limit exceeded, retry after `Retry-After` seconds", body = ErrorBody),
+        (status = 501, description = "Order stream is disabled", body = ErrorBody),
+    )
+)]
+pub async fn stream_orders(
+    State(state): State<Arc<AppState>>,
+    Query(filter): Query<StreamFilter>,
+    headers: HeaderMap,
+) -> Response {
+    let Some(broadcaster) = state.order_stream() else {
+        return (StatusCode::NOT_IMPLEMENTED, Json(json!({"error": "Order stream is disabled"}))).into_response();
+    };
+    let last_event_id = headers
+        .get("Last-Event-ID")
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.trim().parse::<u64>().ok());
+    log!(target: "stream_orders_controller", Level::Info, "New order stream subscriber, last event id: {last_event_id:?}");
+    let (missed, receiver) = broadcaster.subscribe(last_event_id);
+    let filter = Arc::new(filter);
+    let replay_filter = filter.clone();
+    let replayed = stream::iter(missed)
+        .filter_map(move |streamed| {
+            let event = replay_filter.matches(&streamed.order).then(|| to_event(&streamed)).flatten();
+            async move { event }
+        });
+    // A lagging subscriber is disconnected, it resumes from the replay buffer using Last-Event-ID
+    let live = BroadcastStream::new(receiver)
+        .take_while(|received| {
+            let alive = received.is_ok();
+            if !alive {
+                log!(target: "stream_orders_controller", Level::Warn, "Order stream subscriber lagged behind, disconnecting");
+            }
+            async move { alive }
+        })
+        .filter_map(move |received| {
+            let event = received
+                .ok()
+                .filter(|streamed| filter.matches(&streamed.order))
+                .and_then(|streamed| to_event(&streamed));
+            async move { event }
+        });
+    Sse::new(replayed.chain(live))
+        .keep_alive(KeepAlive::default())
+        .into_response()
+}
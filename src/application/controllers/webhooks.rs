@@ -0,0 +1,241 @@
+use {
+    crate::{
+        application::{AppState, controllers::{error_handler, ErrorBody}},
+        domain::models::{OrderEvent, WebhookDelivery, WebhookSubscription, WebhookSubscriptionRequest},
+    },
+    axum::{
+        extract::{Path, Query, State},
+        http::StatusCode,
+        response::{IntoResponse, Response},
+        Json,
+    },
+    serde::Deserialize,
+    serde_json::{Value, json},
+    std::sync::Arc,
+    utoipa::IntoParams,
+    log::{log, Level}
+};
+
+const MIN_SECRET_LENGTH: usize = 16;
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct DeliveriesQuery {
+    /// Maximum number of deliveries to return, 100 by default
+    limit: Option<i64>,
+}
+
+fn validate(request: &WebhookSubscriptionRequest) -> Result<(), String> {
+    if !(request.url.starts_with("http://") || request.url.starts_with("https://")) {
+        return Err("url must be an http(s) URL".to_string());
+    }
+    if request.event_types.is_empty() {
+        return Err("event_types must not be empty".to_string());
+    }
+    if let Some(unknown) = request.event_types.iter().find(|t| !OrderEvent::TYPES.contains(&t.as_str())) {
+        return Err(format!("Unknown event type: {unknown}"));
+    }
+    if request.secret.len() < MIN_SECRET_LENGTH {
+        return Err(format!("secret must be at least {MIN_SECRET_LENGTH} characters long"));
+    }
+    Ok(())
+}
+
+fn unavailable() -> (StatusCode, Json<Value>) {
+    (StatusCode::NOT_IMPLEMENTED, Json(json!({"error": "Webhooks are not supported by this storage"})))
+}
+
+fn not_found() -> (StatusCode, Json<Value>) {
+    (StatusCode::NOT_FOUND, Json(json!({"error": "Webhook subscription with given id not found"})))
+}
+
+#[utoipa::path(
+    post,
+    path = "/webhooks",
+    tag = "webhooks",
+    security(("api_key" = ["orders:admin"]), ("bearer" = ["orders:admin"])),
+    request_body = WebhookSubscriptionRequest,
+    responses(
+        (status = 201, description = "Subscription created", body = WebhookSubscription),
+        (status = 422, description = "Invalid subscription", body = ErrorBody),
+        (status = 401, description = "Authentication required", body = ErrorBody),
+        (status = 403, description = "Missing orders:admin scope", body = ErrorBody),
+        (status = 429, description = "Rate limit exceeded, retry after `Retry-After` seconds", body = ErrorBody),
+        (status = 501, description = "Webhooks are not supported by this storage", body = ErrorBody),
+        (status = 503, description = "Too many concurrent database requests or the request timed out", body = ErrorBody),
+    )
+)]
+pub async fn create_webhook(
+    State(state): State<Arc<AppState>>,
+    Json(request): Json<WebhookSubscriptionRequest>,
+) -> (StatusCode, Json<Value>) {
+    let Some(webhooks) = state.webhooks() else {
+        return unavailable();
+    };
+    if let Err(error) = validate(&request) {
+        return (StatusCode::UNPROCESSABLE_ENTITY, Json(json!({"error": error})));
+    }
+    match webhooks.create(request).await {
+        Ok(subscription) => {
+            log!(target: "webhooks_controller", Level::Info, "Webhook subscription {} created", subscription.id);
+            (StatusCode::CREATED, Json(json!(subscription)))
+        }
+        Err(err) => error_handler::handler(err),
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/webhooks",
+    tag = "webhooks",
+    security(("api_key" = ["orders:admin"]), ("bearer" = ["orders:admin"])),
+    responses(
+        (status = 200, description = "All subscriptions", body = Vec<WebhookSubscription>),
+        (status = 401, description = "Authentication required", body = ErrorBody),
+        (status = 403, description = "Missing orders:admin scope", body = ErrorBody),
+        (status = 429, description = "Rate limit exceeded, retry after `Retry-After` seconds", body = ErrorBody),
+        (status = 501, description = "Webhooks are not supported by this storage", body = ErrorBody),
+        (status = 503, description = "Too many concurrent database requests or the request timed out", body = ErrorBody),
+    )
+)]
+pub async fn list_webhooks(State(state): State<Arc<AppState>>) -> (StatusCode, Json<Value>) {
+    let Some(webhooks) = state.webhooks() else {
+        return unavailable();
+    };
+    match webhooks.list().await {
+        Ok(subscriptions) => (StatusCode::OK, Json(json!(subscriptions))),
+        Err(err) => error_handler::handler(err),
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/webhooks/{id}",
+    tag = "webhooks",
+    security(("api_key" = ["orders:admin"]), ("bearer" = ["orders:admin"])),
+    params(("id" = i64, Path, description = "Subscription id")),
+    responses(
+        (status = 200, description = "Subscription found", body = WebhookSubscription),
+        (status = 404, description = "Subscription not found", body = ErrorBody),
+        (status = 401, description = "Authentication required", body = ErrorBody),
+        (status = 403, description = "Missing orders:admin scope", body = ErrorBody),
+        (status = 429, description = "Rate limit exceeded, retry after `Retry-After` seconds", body = ErrorBody),
+        (status = 501, description = "Webhooks are not supported by this storage", body = ErrorBody),
+        (status = 503, description = "Too many concurrent database requests or the request timed out", body = ErrorBody),
+    )
+)]
+pub async fn get_webhook(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+) -> (StatusCode, Json<Value>) {
+    let Some(webhooks) = state.webhooks() else {
+        return unavailable();
+    };
+    match webhooks.get(id).await {
+        Ok(Some(subscription)) => (StatusCode::OK, Json(json!(subscription))),
+        Ok(None) => not_found(),
+        Err(err) => error_handler::handler(err),
+    }
+}
+
+#[utoipa::path(
+    put,
+    path = "/webhooks/{id}",
+    tag = "webhooks",
+    security(("api_key" = ["orders:admin"]), ("bearer" = ["orders:admin"])),
+    params(("id" = i64, Path, description = "Subscription id")),
+    request_body = WebhookSubscriptionRequest,
+    responses(
+        (status = 200, description = "Subscription updated", body = WebhookSubscription),
+        (status = 404, description = "Subscription not found", body = ErrorBody),
+        (status = 422, description = "Invalid subscription", body = ErrorBody),
+        (status = 401, description = "Authentication required", body = ErrorBody),
+        (status = 403, description = "Missing orders:admin scope", body = ErrorBody),
+        (status = 429, description = "Rate limit exceeded, retry after `Retry-After` seconds", body = ErrorBody),
+        (status = 501, description = "Webhooks are not supported by this storage", body = ErrorBody),
+        (status = 503, description = "Too many concurrent database requests or the request timed out", body = ErrorBody),
+    )
+)]
+pub async fn update_webhook(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+    Json(request): Json<WebhookSubscriptionRequest>,
+) -> (StatusCode, Json<Value>) {
+    let Some(webhooks) = state.webhooks() else {
+        return unavailable();
+    };
+    if let Err(error) = validate(&request) {
+        return (StatusCode::UNPROCESSABLE_ENTITY, Json(json!({"error": error})));
+    }
+    match webhooks.update(id, request).await {
+        Ok(Some(subscription)) => {
+            log!(target: "webhooks_controller", Level::Info, "Webhook subscription {id} updated");
+            (StatusCode::OK, Json(json!(subscription)))
+        }
+        Ok(None) => not_found(),
+        Err(err) => error_handler::handler(err),
+    }
+}
+
+#[utoipa::path(
+    delete,
+    path = "/webhooks/{id}",
+    tag = "webhooks",
+    security(("api_key" = ["orders:admin"]), ("bearer" = ["orders:admin"])),
+    params(("id" = i64, Path, description = "Subscription id")),
+    responses(
+        (status = 204, description = "Subscription deleted"),
+        (status = 404, description = "Subscription not found", body = ErrorBody),
+        (status = 401, description = "Authentication required", body = ErrorBody),
+        (status = 403, description = "Missing orders:admin scope", body = ErrorBody),
+        (status = 429, description = "Rate limit exceeded, retry after `Retry-After` seconds", body = ErrorBody),
+        (status = 501, description = "Webhooks are not supported by this storage", body = ErrorBody),
+        (status = 503, description = "Too many concurrent database requests or the request timed out", body = ErrorBody),
+    )
+)]
+pub async fn delete_webhook(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+) -> Response {
+    let Some(webhooks) = state.webhooks() else {
+        return unavailable().into_response();
+    };
+    match webhooks.delete(id).await {
+        Ok(true) => {
+            log!(target: "webhooks_controller", Level::Info, "Webhook subscription {id} deleted");
+            StatusCode::NO_CONTENT.into_response()
+        }
+        Ok(false) => not_found().into_response(),
+        Err(err) => error_handler::handler(err).into_response(),
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/webhooks/{id}/deliveries",
+    tag = "webhooks",
+    security(("api_key" = ["orders:admin"]), ("bearer" = ["orders:admin"])),
+    params(("id" = i64, Path, description = "Subscription id"), DeliveriesQuery),
+    responses(
+        (status = 200, description = "Latest deliveries, newest first", body = Vec<WebhookDelivery>),
+        (status = 401, description = "Authentication required", body = ErrorBody),
+        (status = 403, description = "Missing orders:admin scope", body = ErrorBody),
+        (status = 429, description = "Rate limit exceeded, retry after `Retry-After` seconds", body = ErrorBody),
+        (status = 501, description = "Webhooks are not supported by this storage", body = ErrorBody),
+        (status = 503, description = "Too many concurrent database requests or the request timed out", body = ErrorBody),
+    )
+)]
+pub async fn list_webhook_deliveries(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+    Query(query): Query<DeliveriesQuery>,
+) -> (StatusCode, Json<Value>) {
+    let Some(webhooks) = state.webhooks() else {
+        return unavailable();
+    };
+    let limit = query.limit.unwrap_or(100).clamp(1, 1000);
+    match webhooks.deliveries(id, limit).await {
+        Ok(deliveries) => (StatusCode::OK, Json(json!(deliveries))),
+        Err(err) => error_handler::handler(err),
+    }
+}
@@ -0,0 +1,39 @@
+use {
+    crate::{
+        application::AppState,
+        domain::models::{Order, Principal},
+    },
+    async_graphql::dataloader::Loader,
+    std::{collections::HashMap, sync::Arc},
+    log::{log, Level}
+};
+
+/// Reads all orders requested while resolving one level of a query with a single `OrderService::get_orders`.
+pub struct OrderLoader {
+    state: Arc<AppState>,
+    principal: Principal,
+}
+
+impl OrderLoader {
+    pub fn new(state: Arc<AppState>, principal: Principal) -> Self {
+        Self { state, principal }
+    }
+}
+
+impl Loader<String> for OrderLoader {
+    type Value = Order;
+    type Error = async_graphql::Error;
+
+    async fn load(&self, keys: &[String]) -> Result<HashMap<String, Order>, Self::Error> {
+        log!(target: "graphql", Level::Info, "Loading a batch of {} orders", keys.len());
+        let orders = self
+            .state
+            .order_service()
+            .get_orders(keys, self.state.repository(), &self.principal)
+            .await;
+        match orders {
+            Ok(orders) => Ok(orders.into_iter().map(|order| (order.order_uid.clone(), order)).collect()),
+            Err(err) => Err(super::error(err)),
+        }
+    }
+}
@@ -0,0 +1,37 @@
+mod loader;
+mod query;
+
+pub use loader::OrderLoader;
+pub use query::{DeliveryNode, ItemFilter, ItemNode, LookupInput, OrderConnection, OrderNode, PaymentNode, QueryRoot};
+
+use {
+    crate::application::controllers::error_handler,
+    async_graphql::{EmptyMutation, EmptySubscription, ErrorExtensions, Schema},
+    std::{error::Error, sync::LazyLock},
+};
+
+pub type OrderSchema = Schema<QueryRoot, EmptyMutation, EmptySubscription>;
+
+/// Deep enough for an order with its payment, delivery and items inside a page.
+const MAX_DEPTH: usize = 8;
+/// Every field of a full page of orders, lists count their field selection once per requested element.
+const MAX_COMPLEXITY: usize = 10_000;
+
+static SCHEMA: LazyLock<OrderSchema> = LazyLock::new(|| {
+    Schema::build(QueryRoot, EmptyMutation, EmptySubscription)
+        .limit_depth(MAX_DEPTH)
+        .limit_complexity(MAX_COMPLEXITY)
+        .finish()
+});
+
+/// Read only schema over orders. Requests need `Arc<AppState>`, their `Principal` and a `DataLoader<OrderLoader>`.
+pub fn schema() -> &'static OrderSchema {
+    &SCHEMA
+}
+
+/// The error with the status the HTTP routes would answer with in its `status` extension.
+fn error(error: Box<dyn Error>) -> async_graphql::Error {
+    let (status, body) = error_handler::handler(error);
+    let message = body["error"].as_str().unwrap_or("Internal error").to_string();
+    async_graphql::Error::new(message).extend_with(|_, extensions| extensions.set("status", status.as_u16()))
+}
@@ -0,0 +1,370 @@
+use {
+    super::{error, OrderLoader},
+    crate::{
+        application::{AppState, controllers::paging},
+        domain::models::{Delivery, Item, Order, OrderLookup, OrderPage, OrderSummary, Payment, Principal},
+    },
+    async_graphql::{dataloader::DataLoader, Context, InputObject, Object, OneofObject, Result},
+    std::{borrow::Cow, sync::Arc},
+};
+
+/// Exactly one key to look orders up by.
+#[derive(OneofObject)]
+pub enum LookupInput {
+    TrackNumber(String),
+    CustomerId(String),
+    /// Payment transaction id
+    Transaction(String),
+    /// Payment request id
+    RequestId(String),
+    /// Orders containing an item with this `chrtId`
+    ChrtId(i32),
+    /// Orders containing an item with this `nmId`
+    NmId(i32),
+}
+
+impl From<LookupInput> for OrderLookup {
+    fn from(lookup: LookupInput) -> Self {
+        match lookup {
+            LookupInput::TrackNumber(track_number) => OrderLookup::TrackNumber(track_number),
+            LookupInput::CustomerId(customer_id) => OrderLookup::CustomerId(customer_id),
+            LookupInput::Transaction(transaction) => OrderLookup::Transaction(transaction),
+            LookupInput::RequestId(request_id) => OrderLookup::RequestId(request_id),
+            LookupInput::ChrtId(chrt_id) => OrderLookup::ChrtId(chrt_id),
+            LookupInput::NmId(nm_id) => OrderLookup::NmId(nm_id),
+        }
+    }
+}
+
+/// Items with all of the given fields.
+#[derive(InputObject, Default)]
+pub struct ItemFilter {
+    chrt_id: Option<i32>,
+    nm_id: Option<i32>,
+    brand: Option<String>,
+    status: Option<i32>,
+}
+
+impl ItemFilter {
+    fn matches(&self, item: &Item) -> bool {
+        self.chrt_id.is_none_or(|chrt_id| chrt_id == item.chrt_id)
+            && self.nm_id.is_none_or(|nm_id| nm_id == item.nm_id)
+            && self.brand.as_ref().is_none_or(|brand| *brand == item.brand)
+            && self.status.is_none_or(|status| status == item.status)
+    }
+}
+
+/// Delivery of an order as GraphQL sees it.
+pub struct DeliveryNode(Delivery);
+
+#[Object(name = "Delivery")]
+impl DeliveryNode {
+    async fn name(&self) -> &str {
+        &self.0.name
+    }
+
+    async fn phone(&self) -> &str {
+        &self.0.phone
+    }
+
+    async fn zip(&self) -> &str {
+        &self.0.zip
+    }
+
+    async fn address(&self) -> &str {
+        &self.0.address
+    }
+
+    async fn region(&self) -> &str {
+        &self.0.region
+    }
+
+    async fn email(&self) -> &str {
+        &self.0.email
+    }
+}
+
+/// Payment of an order as GraphQL sees it.
+pub struct PaymentNode(Payment);
+
+#[Object(name = "Payment")]
+impl PaymentNode {
+    async fn transaction(&self) -> &str {
+        &self.0.transaction
+    }
+
+    async fn request_id(&self) -> &str {
+        &self.0.request_id
+    }
+
+    async fn currency(&self) -> &str {
+        &self.0.currency
+    }
+
+    async fn provider(&self) -> &str {
+        &self.0.provider
+    }
+
+    async fn amount(&self) -> i32 {
+        self.0.amount
+    }
+
+    async fn payment_dt(&self) -> i32 {
+        self.0.payment_dt
+    }
+
+    async fn bank(&self) -> &str {
+        &self.0.bank
+    }
+
+    async fn delivery_cost(&self) -> i32 {
+        self.0.delivery_cost
+    }
+
+    async fn goods_total(&self) -> i32 {
+        self.0.goods_total
+    }
+
+    async fn custom_fee(&self) -> i32 {
+        self.0.custom_fee
+    }
+}
+
+/// An item of an order as GraphQL sees it.
+pub struct ItemNode(Item);
+
+#[Object(name = "Item")]
+impl ItemNode {
+    async fn chrt_id(&self) -> i32 {
+        self.0.chrt_id
+    }
+
+    async fn track_number(&self) -> &str {
+        &self.0.track_number
+    }
+
+    async fn price(&self) -> i32 {
+        self.0.price
+    }
+
+    async fn rid(&self) -> &str {
+        &self.0.rid
+    }
+
+    async fn name(&self) -> &str {
+        &self.0.name
+    }
+
+    async fn sale(&self) -> i32 {
+        self.0.sale
+    }
+
+    async fn size(&self) -> &str {
+        &self.0.size
+    }
+
+    async fn total_price(&self) -> i32 {
+        self.0.total_price
+    }
+
+    async fn nm_id(&self) -> i32 {
+        self.0.nm_id
+    }
+
+    async fn brand(&self) -> &str {
+        &self.0.brand
+    }
+
+    async fn status(&self) -> i32 {
+        self.0.status
+    }
+}
+
+/// An order found by a lookup is only read in full once a field the summary lacks is selected.
+pub enum OrderNode {
+    Full(Box<Order>),
+    Summary(OrderSummary),
+}
+
+impl OrderNode {
+    async fn full(&self, ctx: &Context<'_>) -> Result<Cow<'_, Order>> {
+        match self {
+            OrderNode::Full(order) => Ok(Cow::Borrowed(order)),
+            OrderNode::Summary(summary) => ctx
+                .data_unchecked::<DataLoader<OrderLoader>>()
+                .load_one(summary.order_uid.clone())
+                .await?
+                .map(Cow::Owned)
+                .ok_or_else(|| format!("Order {} was removed", summary.order_uid).into()),
+        }
+    }
+}
+
+#[Object(name = "Order")]
+impl OrderNode {
+    async fn order_uid(&self) -> &str {
+        match self {
+            OrderNode::Full(order) => &order.order_uid,
+            OrderNode::Summary(summary) => &summary.order_uid,
+        }
+    }
+
+    async fn track_number(&self, ctx: &Context<'_>) -> Result<String> {
+        match self {
+            OrderNode::Summary(summary) => Ok(summary.track_number.clone()),
+            _ => Ok(self.full(ctx).await?.track_number.clone()),
+        }
+    }
+
+    async fn customer_id(&self, ctx: &Context<'_>) -> Result<String> {
+        match self {
+            OrderNode::Summary(summary) => Ok(summary.customer_id.clone()),
+            _ => Ok(self.full(ctx).await?.customer_id.clone()),
+        }
+    }
+
+    async fn delivery_service(&self, ctx: &Context<'_>) -> Result<String> {
+        match self {
+            OrderNode::Summary(summary) => Ok(summary.delivery_service.clone()),
+            _ => Ok(self.full(ctx).await?.delivery_service.clone()),
+        }
+    }
+
+    async fn date_created(&self, ctx: &Context<'_>) -> Result<String> {
+        match self {
+            OrderNode::Summary(summary) => Ok(summary.date_created.clone()),
+            _ => Ok(self.full(ctx).await?.date_created.clone()),
+        }
+    }
+
+    async fn item_count(&self, ctx: &Context<'_>) -> Result<i64> {
+        match self {
+            OrderNode::Summary(summary) => Ok(summary.item_count),
+            _ => Ok(self.full(ctx).await?.items.len() as i64),
+        }
+    }
+
+    async fn entry(&self, ctx: &Context<'_>) -> Result<String> {
+        Ok(self.full(ctx).await?.entry.clone())
+    }
+
+    async fn locale(&self, ctx: &Context<'_>) -> Result<String> {
+        Ok(self.full(ctx).await?.locale.clone())
+    }
+
+    async fn internal_signature(&self, ctx: &Context<'_>) -> Result<String> {
+        Ok(self.full(ctx).await?.internal_signature.clone())
+    }
+
+    async fn shardkey(&self, ctx: &Context<'_>) -> Result<String> {
+        Ok(self.full(ctx).await?.shardkey.clone())
+    }
+
+    async fn sm_id(&self, ctx: &Context<'_>) -> Result<i32> {
+        Ok(self.full(ctx).await?.sm_id)
+    }
+
+    async fn oof_shard(&self, ctx: &Context<'_>) -> Result<String> {
+        Ok(self.full(ctx).await?.oof_shard.clone())
+    }
+
+    async fn delivery(&self, ctx: &Context<'_>) -> Result<DeliveryNode> {
+        Ok(DeliveryNode(self.full(ctx).await?.delivery.clone()))
+    }
+
+    async fn payment(&self, ctx: &Context<'_>) -> Result<PaymentNode> {
+        Ok(PaymentNode(self.full(ctx).await?.payment.clone()))
+    }
+
+    /// Items in the order they were placed, optionally only some of them
+    async fn items(
+        &self,
+        ctx: &Context<'_>,
+        filter: Option<ItemFilter>,
+        limit: Option<usize>,
+        #[graphql(default)] offset: usize,
+    ) -> Result<Vec<ItemNode>> {
+        let filter = filter.unwrap_or_default();
+        let order = self.full(ctx).await?;
+        Ok(order
+            .items
+            .iter()
+            .filter(|item| filter.matches(item))
+            .skip(offset)
+            .take(limit.unwrap_or(usize::MAX))
+            .cloned()
+            .map(ItemNode)
+            .collect())
+    }
+}
+
+pub struct OrderConnection(OrderPage);
+
+#[Object]
+impl OrderConnection {
+    async fn nodes(&self) -> Vec<OrderNode> {
+        self.0.orders.iter().cloned().map(OrderNode::Summary).collect()
+    }
+
+    async fn limit(&self) -> i64 {
+        self.0.limit
+    }
+
+    async fn offset(&self) -> i64 {
+        self.0.offset
+    }
+
+    /// Offset of the next page, absent on the last one
+    async fn next_offset(&self) -> Option<i64> {
+        self.0.next_offset
+    }
+}
+
+pub struct QueryRoot;
+
+#[Object]
+impl QueryRoot {
+    /// The order with this uid, `null` if there is none
+    async fn order(&self, ctx: &Context<'_>, order_uid: String) -> Result<Option<OrderNode>> {
+        let order = ctx.data_unchecked::<DataLoader<OrderLoader>>().load_one(order_uid).await?;
+        Ok(order.map(|order| OrderNode::Full(Box::new(order))))
+    }
+
+    /// Orders with these uids in the same order, `null` for unknown ones
+    #[graphql(complexity = "order_uids.len() * child_complexity")]
+    async fn orders_by_uid(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "At most 100 uids")] order_uids: Vec<String>,
+    ) -> Result<Vec<Option<OrderNode>>> {
+        if order_uids.len() > paging::MAX_LIMIT as usize {
+            return Err(format!("orderUids must hold at most {} uids", paging::MAX_LIMIT).into());
+        }
+        let mut found = ctx.data_unchecked::<DataLoader<OrderLoader>>().load_many(order_uids.clone()).await?;
+        Ok(order_uids
+            .iter()
+            .map(|order_uid| found.remove(order_uid).map(|order| OrderNode::Full(Box::new(order))))
+            .collect())
+    }
+
+    /// Orders found by one key, newest first
+    #[graphql(complexity = "limit.unwrap_or(paging::DEFAULT_LIMIT).clamp(1, paging::MAX_LIMIT) as usize * child_complexity")]
+    async fn orders(
+        &self,
+        ctx: &Context<'_>,
+        lookup: LookupInput,
+        #[graphql(desc = "Page size, 20 by default, at most 100")] limit: Option<i64>,
+        offset: Option<i64>,
+    ) -> Result<OrderConnection> {
+        let state = ctx.data_unchecked::<Arc<AppState>>();
+        let principal = ctx.data_unchecked::<Principal>();
+        let (limit, offset) = paging::bounds(limit, offset)
+            .map_err(|(_, body)| body["error"].as_str().unwrap_or("Invalid paging").to_string())?;
+        let orders = state
+            .order_service()
+            .find_orders(&lookup.into(), limit + 1, offset, state.repository(), principal)
+            .await
+            .map_err(error)?;
+        Ok(OrderConnection(OrderPage::from_overfetched(orders, limit, offset)))
+    }
+}
@@ -0,0 +1,239 @@
+use {
+    crate::{
+        application::{
+            AppState,
+            controllers::{error_handler, paging},
+            middleware::{client_key, credentials, retry_after_secs, Budget},
+            wire::orders::{self, order_service_server},
+        },
+        domain::models::{Order, OrderLookup, OrderPage, Principal, PrincipalKind},
+        infrastructure::{deadline, StreamedOrder},
+    },
+    axum::http::StatusCode,
+    futures::{stream, Stream, StreamExt},
+    std::{error::Error, future::Future, pin::Pin, sync::Arc, time::Duration},
+    tokio::{net::TcpListener, time::Instant},
+    tokio_stream::wrappers::{BroadcastStream, TcpListenerStream},
+    tonic::{Code, Request, Response, Status},
+    log::{log, Level}
+};
+
+pub use order_service_server::OrderServiceServer;
+
+/// Serialized descriptors of `proto/*.proto`, served by reflection.
+pub const FILE_DESCRIPTOR_SET: &[u8] = include_bytes!(concat!(env!("OUT_DIR"), "/orders_descriptor.bin"));
+
+/// `OrderService` of `proto/order_service.proto` over the same state as the HTTP router,
+/// with the same scopes, rate limits, timeouts and database concurrency cap.
+pub struct GrpcOrders {
+    state: Arc<AppState>,
+}
+
+impl GrpcOrders {
+    pub fn new(state: Arc<AppState>) -> Self {
+        Self { state }
+    }
+
+    /// Authenticates the caller and takes a token from the budget of the HTTP route before checking the scope,
+    /// like the HTTP layers do.
+    fn authorize<T>(&self, request: &Request<T>, scope: &'static str, budget: Budget) -> Result<Principal, Status> {
+        let headers = request.metadata().clone().into_headers();
+        let principal = self
+            .state
+            .authenticator()
+            .authenticate(&credentials(&headers))
+            .map_err(|err| {
+                log!(target: "grpc_orders", Level::Warn, "Rejected credentials: {err}");
+                Status::unauthenticated("Invalid credentials")
+            })?;
+        if let Some(limiter) = budget.limiter(&self.state) {
+            let key = client_key(Some(&principal), request.remote_addr());
+            if let Err(wait) = limiter.check(&key) {
+                log!(target: "grpc_orders", Level::Warn, "{key} exceeded {budget:?} rate limit");
+                let mut status = Status::resource_exhausted("Too many requests");
+                status.metadata_mut().insert("retry-after", retry_after_secs(wait).into());
+                return Err(status);
+            }
+        }
+        match principal {
+            principal if principal.has_scope(scope) => Ok(principal),
+            principal if principal.kind != PrincipalKind::Anonymous => {
+                log!(target: "grpc_orders", Level::Warn, "{principal} lacks {scope}");
+                Err(Status::permission_denied(format!("Missing scope {scope}")))
+            }
+            _ => Err(Status::unauthenticated("Authentication required")),
+        }
+    }
+
+    /// Runs a call working with the database under the concurrency cap and the HTTP timeout for its kind.
+    async fn bounded<T>(&self, limit: Duration, call: impl Future<Output = Result<T, Status>>) -> Result<T, Status> {
+        let _permit = match self.state.db_permits() {
+            Some(permits) => Some(permits.clone().try_acquire_owned().map_err(|_| {
+                log!(target: "grpc_orders", Level::Warn, "Database concurrency limit reached");
+                Status::unavailable("Server is busy")
+            })?),
+            None => None,
+        };
+        let deadline = Instant::now() + limit;
+        tokio::time::timeout_at(deadline, deadline::with_deadline(deadline, call))
+            .await
+            .map_err(|_| Status::deadline_exceeded("Request timed out"))?
+    }
+}
+
+/// The status the HTTP route would answer with, as a gRPC code.
+fn status(error: Box<dyn Error>) -> Status {
+    let (status, body) = error_handler::handler(error);
+    let message = body["error"].as_str().unwrap_or("Internal error").to_string();
+    let code = match status {
+        StatusCode::BAD_REQUEST => Code::InvalidArgument,
+        StatusCode::CONFLICT => Code::AlreadyExists,
+        StatusCode::UNPROCESSABLE_ENTITY => Code::FailedPrecondition,
+        StatusCode::SERVICE_UNAVAILABLE => Code::Unavailable,
+        _ => Code::Internal,
+    };
+    Status::new(code, message)
+}
+
+fn lookup(lookup: orders::list_orders_request::Lookup) -> OrderLookup {
+    use orders::list_orders_request::Lookup;
+    match lookup {
+        Lookup::TrackNumber(track_number) => OrderLookup::TrackNumber(track_number),
+        Lookup::CustomerId(customer_id) => OrderLookup::CustomerId(customer_id),
+        Lookup::Transaction(transaction) => OrderLookup::Transaction(transaction),
+        Lookup::RequestId(request_id) => OrderLookup::RequestId(request_id),
+        Lookup::ChrtId(chrt_id) => OrderLookup::ChrtId(chrt_id),
+        Lookup::NmId(nm_id) => OrderLookup::NmId(nm_id),
+    }
+}
+
+impl orders::StreamOrdersRequest {
+    fn matches(&self, order: &Order) -> bool {
+        fn check(expected: &str, actual: &str) -> bool {
+            expected.is_empty() || expected == actual
+        }
+        check(&self.entry, &order.entry)
+            && check(&self.delivery_service, &order.delivery_service)
+            && check(&self.locale, &order.locale)
+    }
+}
+
+fn to_event(streamed: &StreamedOrder) -> Result<orders::OrderEvent, Status> {
+    let order = Some(streamed.order.clone().into());
+    Ok(orders::OrderEvent { id: streamed.id, order })
+}
+
+type OrderEvents = Pin<Box<dyn Stream<Item = Result<orders::OrderEvent, Status>> + Send>>;
+
+#[tonic::async_trait]
+impl order_service_server::OrderService for GrpcOrders {
+    async fn add_order(&self, request: Request<orders::Order>) -> Result<Response<orders::AddOrderResponse>, Status> {
+        let principal = self.authorize(&request, "orders:write", Budget::Write)?;
+        let order = Order::from(request.into_inner());
+        log!(target: "grpc_orders", Level::Info, "Got new order: {order:?}");
+        let state = &self.state;
+        self.bounded(state.http_settings().write_timeout, async {
+            state.order_service().add_order(state.repository(), order, &principal).await.map_err(status)
+        })
+        .await?;
+        Ok(Response::new(orders::AddOrderResponse {}))
+    }
+
+    async fn get_order(&self, request: Request<orders::GetOrderRequest>) -> Result<Response<orders::Order>, Status> {
+        let principal = self.authorize(&request, "orders:read", Budget::Read)?;
+        let order_uid = request.into_inner().order_uid;
+        log!(target: "grpc_orders", Level::Info, "Got new get-request by order_uid: {order_uid}");
+        let state = &self.state;
+        let order = self
+            .bounded(state.http_settings().read_timeout, async {
+                state.order_service().get_order(&order_uid, state.repository(), &principal).await.map_err(status)
+            })
+            .await?;
+        match order {
+            Some(order) => Ok(Response::new(order.into())),
+            None => Err(Status::not_found("Order with given uid not found")),
+        }
+    }
+
+    async fn list_orders(
+        &self,
+        request: Request<orders::ListOrdersRequest>,
+    ) -> Result<Response<orders::ListOrdersResponse>, Status> {
+        let principal = self.authorize(&request, "orders:read", Budget::Read)?;
+        let request = request.into_inner();
+        let Some(lookup) = request.lookup.map(lookup) else {
+            return Err(Status::invalid_argument(
+                "One of track_number, customer_id, transaction, request_id, chrt_id or nm_id is required",
+            ));
+        };
+        let (limit, offset) = paging::bounds(request.limit, request.offset).map_err(|(_, body)| {
+            Status::invalid_argument(body["error"].as_str().unwrap_or("Invalid paging").to_string())
+        })?;
+        log!(target: "grpc_orders", Level::Info, "Got new lookup by {lookup}");
+        let state = &self.state;
+        let orders = self
+            .bounded(state.http_settings().read_timeout, async {
+                state
+                    .order_service()
+                    .find_orders(&lookup, limit + 1, offset, state.repository(), &principal)
+                    .await
+                    .map_err(status)
+            })
+            .await?;
+        let page = OrderPage::from_overfetched(orders, limit, offset);
+        Ok(Response::new(orders::ListOrdersResponse {
+            orders: page.orders.into_iter().map(Into::into).collect(),
+            next_offset: page.next_offset,
+        }))
+    }
+
+    type StreamOrdersStream = OrderEvents;
+
+    async fn stream_orders(
+        &self,
+        request: Request<orders::StreamOrdersRequest>,
+    ) -> Result<Response<Self::StreamOrdersStream>, Status> {
+        self.authorize(&request, "orders:read", Budget::Read)?;
+        let Some(broadcaster) = self.state.order_stream() else {
+            return Err(Status::unimplemented("Order stream is disabled"));
+        };
+        let filter = Arc::new(request.into_inner());
+        log!(target: "grpc_orders", Level::Info, "New order stream subscriber, last event id: {:?}", filter.after_id);
+        let (missed, receiver) = broadcaster.subscribe(filter.after_id);
+        let replay_filter = filter.clone();
+        let replayed = stream::iter(missed)
+            .filter(move |streamed| std::future::ready(replay_filter.matches(&streamed.order)))
+            .map(|streamed| to_event(&streamed));
+        // A lagging subscriber gets DATA_LOSS and resumes from the replay buffer with after_id
+        let live = BroadcastStream::new(receiver)
+            .filter_map(move |received| {
+                let event = match received {
+                    Ok(streamed) => filter.matches(&streamed.order).then(|| to_event(&streamed)),
+                    Err(_) => {
+                        log!(target: "grpc_orders", Level::Warn, "Order stream subscriber lagged behind, disconnecting");
+                        Some(Err(Status::data_loss("Subscriber lagged behind, resume with after_id")))
+                    }
+                };
+                std::future::ready(event)
+            })
+            .scan(false, |failed, event| {
+                let next = (!*failed).then_some(event);
+                *failed = next.as_ref().is_some_and(Result::is_err);
+                std::future::ready(next)
+            });
+        Ok(Response::new(Box::pin(replayed.chain(live))))
+    }
+}
+
+/// Serves `OrderService` and reflection on `listener` until the future is dropped.
+pub async fn serve_grpc(state: Arc<AppState>, listener: TcpListener) -> Result<(), Box<dyn Error>> {
+    let reflection = tonic_reflection::server::Builder::configure()
+        .register_encoded_file_descriptor_set(FILE_DESCRIPTOR_SET)
+        .build_v1()?;
+    tonic::transport::Server::builder()
+        .add_service(reflection)
+        .add_service(OrderServiceServer::new(GrpcOrders::new(state)))
+        .serve_with_incoming(TcpListenerStream::new(listener))
+        .await?;
+    Ok(())
+}
@@ -0,0 +1,67 @@
+use {
+    crate::{
+        application::AppState,
+        domain::{interfaces::Credentials, models::{Principal, PrincipalKind}},
+    },
+    axum::{
+        extract::{Request, State},
+        http::{header, HeaderMap, StatusCode},
+        middleware::Next,
+        response::{IntoResponse, Response},
+        Json,
+    },
+    serde_json::json,
+    std::sync::Arc,
+    log::{log, Level}
+};
+
+/// Credentials of a request, also used for gRPC metadata.
+pub fn credentials(headers: &HeaderMap) -> Credentials {
+    if let Some(key) = headers.get("X-API-Key").and_then(|value| value.to_str().ok()) {
+        return Credentials::ApiKey(key.trim().to_string());
+    }
+    let authorization = headers
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.split_once(' '));
+    match authorization {
+        Some((scheme, token)) if scheme.eq_ignore_ascii_case("bearer") => Credentials::Bearer(token.trim().to_string()),
+        Some((scheme, key)) if scheme.eq_ignore_ascii_case("apikey") => Credentials::ApiKey(key.trim().to_string()),
+        _ => Credentials::None,
+    }
+}
+
+fn unauthorized(message: &str) -> Response {
+    (
+        StatusCode::UNAUTHORIZED,
+        [(header::WWW_AUTHENTICATE, "Bearer, ApiKey")],
+        Json(json!({"error": message})),
+    )
+        .into_response()
+}
+
+/// Resolves request credentials into a `Principal` request extension.
+pub async fn authenticate(State(state): State<Arc<AppState>>, mut request: Request, next: Next) -> Response {
+    match state.authenticator().authenticate(&credentials(request.headers())) {
+        Ok(principal) => {
+            request.extensions_mut().insert(principal);
+            next.run(request).await
+        }
+        Err(err) => {
+            log!(target: "auth_middleware", Level::Warn, "Rejected credentials for {}: {err}", request.uri().path());
+            unauthorized("Invalid credentials")
+        }
+    }
+}
+
+/// Route layer letting through only principals granted `scope`, use with `from_fn_with_state`.
+pub async fn require_scope(State(scope): State<&'static str>, request: Request, next: Next) -> Response {
+    match request.extensions().get::<Principal>() {
+        Some(principal) if principal.has_scope(scope) => next.run(request).await,
+        Some(principal) if principal.kind != PrincipalKind::Anonymous => {
+            log!(target: "auth_middleware", Level::Warn, "{principal} lacks {scope} for {}", request.uri().path());
+            (StatusCode::FORBIDDEN, Json(json!({"error": format!("Missing scope {scope}")}))).into_response()
+        }
+        _ => unauthorized("Authentication required"),
+    }
+}
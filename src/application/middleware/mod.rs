@@ -0,0 +1,7 @@
+mod auth;
+mod rate_limit;
+mod timeout;
+
+pub use auth::*;
+pub use rate_limit::*;
+pub use timeout::*;
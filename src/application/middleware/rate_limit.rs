@@ -0,0 +1,90 @@
+use {
+    crate::{
+        application::AppState,
+        domain::models::{Principal, PrincipalKind},
+        infrastructure::RateLimiter,
+    },
+    axum::{
+        extract::{ConnectInfo, Request, State},
+        http::{header, StatusCode},
+        middleware::Next,
+        response::{IntoResponse, Response},
+        Json,
+    },
+    serde_json::json,
+    std::{net::SocketAddr, sync::Arc, time::Duration},
+    log::{log, Level}
+};
+
+#[derive(Clone, Copy, Debug)]
+pub enum Budget {
+    Read,
+    Write,
+}
+
+impl Budget {
+    /// Token bucket of the budget, `None` if it's unlimited.
+    pub fn limiter(self, state: &AppState) -> Option<&RateLimiter> {
+        match self {
+            Budget::Read => state.read_limiter(),
+            Budget::Write => state.write_limiter(),
+        }
+    }
+}
+
+/// Authenticated callers are limited by principal, anonymous ones by client IP.
+pub fn client_key(principal: Option<&Principal>, addr: Option<SocketAddr>) -> String {
+    match principal {
+        Some(principal) if principal.kind != PrincipalKind::Anonymous => principal.to_string(),
+        _ => addr.map(|addr| format!("ip:{}", addr.ip())).unwrap_or_else(|| "ip:unknown".to_string()),
+    }
+}
+
+/// Whole seconds to wait, at least one.
+pub fn retry_after_secs(wait: Duration) -> u64 {
+    wait.as_secs_f64().ceil().max(1.0) as u64
+}
+
+fn retry_after(status: StatusCode, wait: Duration, message: &str) -> Response {
+    (
+        status,
+        [(header::RETRY_AFTER, retry_after_secs(wait).to_string())],
+        Json(json!({"error": message})),
+    )
+        .into_response()
+}
+
+/// Route layer applying the read or write token bucket, use with `from_fn_with_state`.
+pub async fn rate_limit(
+    State((state, budget)): State<(Arc<AppState>, Budget)>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let Some(limiter) = budget.limiter(&state) else {
+        return next.run(request).await;
+    };
+    let addr = request.extensions().get::<ConnectInfo<SocketAddr>>().map(|ConnectInfo(addr)| *addr);
+    let key = client_key(request.extensions().get::<Principal>(), addr);
+    match limiter.check(&key) {
+        Ok(()) => next.run(request).await,
+        Err(wait) => {
+            log!(target: "rate_limit_middleware", Level::Warn, "{key} exceeded {budget:?} rate limit");
+            retry_after(StatusCode::TOO_MANY_REQUESTS, wait, "Too many requests")
+        }
+    }
+}
+
+/// Caps how many requests hit the database at once, the rest are rejected right away
+/// instead of queueing for a pool connection.
+pub async fn limit_concurrency(State(state): State<Arc<AppState>>, request: Request, next: Next) -> Response {
+    let Some(permits) = state.db_permits() else {
+        return next.run(request).await;
+    };
+    match permits.clone().try_acquire_owned() {
+        Ok(_permit) => next.run(request).await,
+        Err(_) => {
+            log!(target: "rate_limit_middleware", Level::Warn, "Database concurrency limit reached");
+            retry_after(StatusCode::SERVICE_UNAVAILABLE, Duration::from_secs(1), "Server is busy")
+        }
+    }
+}
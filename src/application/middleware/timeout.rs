@@ -0,0 +1,28 @@
+use {
+    crate::infrastructure::deadline,
+    axum::{
+        extract::{Request, State},
+        http::StatusCode,
+        middleware::Next,
+        response::{IntoResponse, Response},
+        Json,
+    },
+    serde_json::json,
+    std::time::Duration,
+    tokio::time::Instant,
+    log::{log, Level}
+};
+
+/// Route layer dropping the handler once `limit` passes. The deadline is also handed to the
+/// storage layer, which makes the database stop the request's statements at the same moment.
+pub async fn timeout(State(limit): State<Duration>, request: Request, next: Next) -> Response {
+    let path = request.uri().path().to_string();
+    let deadline = Instant::now() + limit;
+    match tokio::time::timeout_at(deadline, deadline::with_deadline(deadline, next.run(request))).await {
+        Ok(response) => response,
+        Err(_) => {
+            log!(target: "timeout_middleware", Level::Warn, "Request to {path} timed out after {limit:?}");
+            (StatusCode::SERVICE_UNAVAILABLE, Json(json!({"error": "Request timed out"}))).into_response()
+        }
+    }
+}
@@ -1,5 +1,11 @@
 mod app_state;
+mod router;
+pub mod middleware;
 pub mod controllers;
+pub mod wire;
+pub mod grpc;
+pub mod graphql;
 
-pub use app_state::AppState;
+pub use app_state::{AppState, HttpSettings};
 pub use controllers::{add_order, get_order};
+pub use router::{openapi, router};
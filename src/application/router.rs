@@ -0,0 +1,211 @@
+use {
+    crate::{
+        application::{
+            AppState,
+            controllers::*,
+            middleware::{authenticate, limit_concurrency, rate_limit, require_scope, timeout, Budget},
+            HttpSettings,
+        },
+        domain::models::Principal,
+    },
+    axum::{
+        extract::DefaultBodyLimit,
+        http::{header, HeaderName, HeaderValue, Method},
+        middleware::from_fn_with_state,
+        routing::get,
+        Router,
+    },
+    tower_http::{compression::CompressionLayer, cors::{AllowOrigin, CorsLayer}},
+    log::{log, Level},
+    std::sync::Arc,
+    utoipa::{
+        openapi::security::{ApiKey, ApiKeyValue, HttpAuthScheme, HttpBuilder, SecurityScheme},
+        Modify, OpenApi,
+    },
+    utoipa_axum::{router::OpenApiRouter, routes},
+    utoipa_swagger_ui::SwaggerUi,
+};
+
+#[derive(OpenApi)]
+#[openapi(
+    info(title = "WB Tech L0", description = "Orders storage service", license(name = "MIT")),
+    modifiers(&SecuritySchemes),
+    tags(
+        (name = "orders", description = "Saving and looking up orders"),
+        (name = "webhooks", description = "Push notifications about order events"),
+        (name = "analytics", description = "Sales reports, refreshed periodically"),
+    )
+)]
+struct ApiDoc;
+
+struct SecuritySchemes;
+
+impl Modify for SecuritySchemes {
+    fn modify(&self, openapi: &mut utoipa::openapi::OpenApi) {
+        let components = openapi.components.get_or_insert_with(Default::default);
+        components.add_security_scheme(
+            "api_key",
+            SecurityScheme::ApiKey(ApiKey::Header(ApiKeyValue::new("X-API-Key"))),
+        );
+        components.add_security_scheme(
+            "bearer",
+            SecurityScheme::Http(HttpBuilder::new().scheme(HttpAuthScheme::Bearer).bearer_format("JWT").build()),
+        );
+    }
+}
+
+struct RouteGroup {
+    scope: &'static str,
+    budget: Budget,
+    /// Works with the database, so it's capped by concurrency and bounded by the budget's timeout
+    db_bound: bool,
+    /// Accepts orders, so the body size is limited by `HttpSettings::max_order_body`
+    accepts_orders: bool,
+    compressed: bool,
+    routes: OpenApiRouter<Arc<AppState>>,
+}
+
+impl RouteGroup {
+    /// Layers run top to bottom: compression, rate limit, scope check, timeout, database concurrency cap.
+    fn guarded(self, state: &Arc<AppState>) -> OpenApiRouter<Arc<AppState>> {
+        let http = state.http_settings();
+        let mut routes = self.routes;
+        if self.db_bound {
+            let limit = match self.budget {
+                Budget::Read => http.read_timeout,
+                Budget::Write => http.write_timeout,
+            };
+            routes = routes
+                .layer(from_fn_with_state(state.clone(), limit_concurrency))
+                .layer(from_fn_with_state(limit, timeout));
+        }
+        if self.accepts_orders {
+            routes = routes.layer(DefaultBodyLimit::max(http.max_order_body));
+        }
+        routes = routes
+            .layer(from_fn_with_state(self.scope, require_scope))
+            .layer(from_fn_with_state((state.clone(), self.budget), rate_limit));
+        if self.compressed && http.compression {
+            routes = routes.layer(CompressionLayer::new());
+        }
+        routes
+    }
+}
+
+fn route_groups() -> Vec<RouteGroup> {
+    vec![
+        RouteGroup {
+            scope: Principal::ORDERS_READ,
+            budget: Budget::Read,
+            db_bound: true,
+            accepts_orders: false,
+            compressed: true,
+            routes: OpenApiRouter::new()
+                .routes(routes!(get_order))
+                .routes(routes!(search_orders))
+                .routes(routes!(find_orders))
+                .routes(routes!(customer_orders))
+                .routes(routes!(graphql_query)),
+        },
+        RouteGroup {
+            scope: Principal::ORDERS_READ,
+            budget: Budget::Read,
+            db_bound: false,
+            accepts_orders: false,
+            compressed: false,
+            routes: OpenApiRouter::new()
+                .routes(routes!(stream_orders))
+                .routes(routes!(export_orders)),
+        },
+        RouteGroup {
+            scope: Principal::ORDERS_WRITE,
+            budget: Budget::Write,
+            db_bound: true,
+            accepts_orders: true,
+            compressed: false,
+            routes: OpenApiRouter::new().routes(routes!(add_order)),
+        },
+        RouteGroup {
+            scope: Principal::ANALYTICS_READ,
+            budget: Budget::Read,
+            db_bound: true,
+            accepts_orders: false,
+            compressed: true,
+            routes: OpenApiRouter::new().routes(routes!(sales_report)),
+        },
+        RouteGroup {
+            scope: Principal::ORDERS_ADMIN,
+            budget: Budget::Write,
+            db_bound: true,
+            accepts_orders: false,
+            compressed: true,
+            routes: OpenApiRouter::new()
+                .routes(routes!(list_webhooks, create_webhook))
+                .routes(routes!(get_webhook, update_webhook, delete_webhook))
+                .routes(routes!(list_webhook_deliveries)),
+        },
+    ]
+}
+
+/// Without state the routes come without middleware, which is enough to describe them.
+fn documented_routes(state: Option<&Arc<AppState>>) -> OpenApiRouter<Arc<AppState>> {
+    route_groups()
+        .into_iter()
+        .fold(OpenApiRouter::with_openapi(ApiDoc::openapi()), |router, group| match state {
+            Some(state) => router.merge(group.guarded(state)),
+            None => router.merge(group.routes),
+        })
+}
+
+/// OpenAPI document built from the same route list the router is made of.
+pub fn openapi() -> utoipa::openapi::OpenApi {
+    documented_routes(None).split_for_parts().1
+}
+
+fn cors(http: &HttpSettings) -> Option<CorsLayer> {
+    if http.cors_origins.is_empty() {
+        return None;
+    }
+    let origins = if http.cors_origins.iter().any(|origin| origin == "*") {
+        AllowOrigin::any()
+    } else {
+        let origins = http
+            .cors_origins
+            .iter()
+            .filter_map(|origin| match HeaderValue::from_str(origin) {
+                Ok(origin) => Some(origin),
+                Err(_) => {
+                    log!(target: "router", Level::Warn, "Ignoring invalid CORS origin {origin}");
+                    None
+                }
+            })
+            .collect::<Vec<_>>();
+        AllowOrigin::list(origins)
+    };
+    let layer = CorsLayer::new()
+        .allow_origin(origins)
+        .allow_methods([Method::GET, Method::POST, Method::PUT, Method::DELETE])
+        .allow_headers([
+            header::CONTENT_TYPE,
+            header::AUTHORIZATION,
+            HeaderName::from_static("x-api-key"),
+            HeaderName::from_static("last-event-id"),
+        ])
+        .expose_headers([header::RETRY_AFTER]);
+    Some(layer)
+}
+
+pub fn router(state: Arc<AppState>) -> Router {
+    let (router, openapi) = documented_routes(Some(&state)).split_for_parts();
+    let router = router
+        .layer(from_fn_with_state(state.clone(), authenticate))
+        .route("/", get(index))
+        .route("/graphql", get(graphiql))
+        .merge(SwaggerUi::new("/swagger-ui").url("/openapi.json", openapi));
+    // CORS goes outside of authentication, preflight requests carry no credentials
+    let router = match cors(state.http_settings()) {
+        Some(cors) => router.layer(cors),
+        None => router,
+    };
+    router.with_state(state)
+}
@@ -0,0 +1,129 @@
+mod proto;
+
+pub use proto::orders;
+
+use {
+    crate::{application::controllers::error_handler, domain::models::Order},
+    axum::{
+        async_trait,
+        body::Bytes,
+        extract::{FromRequest, Request},
+        http::{header, HeaderMap, HeaderValue, StatusCode},
+        response::{IntoResponse, Response},
+        Json,
+    },
+    prost::Message,
+    serde_json::json,
+    std::error::Error,
+};
+
+/// Formats orders are exchanged in, picked by `Content-Type` and `Accept`. JSON is the default.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum WireFormat {
+    Json,
+    /// Fields by name, like in JSON
+    MessagePack,
+    /// `Order` from `proto/orders.proto`
+    Protobuf,
+}
+
+impl WireFormat {
+    pub const JSON: &'static str = "application/json";
+    pub const MESSAGE_PACK: &'static str = "application/msgpack";
+    pub const PROTOBUF: &'static str = "application/x-protobuf";
+
+    fn from_media_type(media_type: &str) -> Option<Self> {
+        let essence = media_type.split(';').next().unwrap_or_default().trim().to_ascii_lowercase();
+        match essence.as_str() {
+            Self::JSON | "*/*" | "application/*" => Some(Self::Json),
+            _ if essence.ends_with("+json") => Some(Self::Json),
+            Self::MESSAGE_PACK | "application/x-msgpack" => Some(Self::MessagePack),
+            Self::PROTOBUF | "application/protobuf" => Some(Self::Protobuf),
+            _ => None,
+        }
+    }
+
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            Self::Json => Self::JSON,
+            Self::MessagePack => Self::MESSAGE_PACK,
+            Self::Protobuf => Self::PROTOBUF,
+        }
+    }
+
+    /// Format of the request body, `None` when its `Content-Type` is missing or unknown.
+    pub fn of_body(headers: &HeaderMap) -> Option<Self> {
+        let content_type = headers.get(header::CONTENT_TYPE)?.to_str().ok()?;
+        Self::from_media_type(content_type)
+    }
+
+    /// Supported format the client prefers by `Accept` quality, JSON if it names none of them.
+    pub fn accepted(headers: &HeaderMap) -> Self {
+        let mut preferences: Vec<(Self, f32)> = headers
+            .get_all(header::ACCEPT)
+            .iter()
+            .filter_map(|value| value.to_str().ok())
+            .flat_map(|value| value.split(','))
+            .filter_map(|range| {
+                let quality = range
+                    .split(';')
+                    .skip(1)
+                    .find_map(|parameter| parameter.trim().strip_prefix("q="))
+                    .map_or(Some(1.0), |quality| quality.trim().parse::<f32>().ok())?;
+                Some((Self::from_media_type(range)?, quality))
+            })
+            .filter(|(_, quality)| *quality > 0.0)
+            .collect();
+        // Stable, so equally preferred types keep the client's order
+        preferences.sort_by(|a, b| b.1.total_cmp(&a.1));
+        preferences.first().map_or(Self::Json, |(format, _)| *format)
+    }
+
+    pub fn encode(&self, order: &Order) -> Result<Vec<u8>, Box<dyn Error>> {
+        match self {
+            Self::Json => Ok(serde_json::to_vec(order)?),
+            Self::MessagePack => Ok(rmp_serde::to_vec_named(order)?),
+            Self::Protobuf => Ok(orders::Order::from(order.clone()).encode_to_vec()),
+        }
+    }
+
+    pub fn decode(&self, bytes: &[u8]) -> Result<Order, Box<dyn Error>> {
+        match self {
+            Self::Json => Ok(serde_json::from_slice(bytes)?),
+            Self::MessagePack => Ok(rmp_serde::from_slice(bytes)?),
+            Self::Protobuf => Ok(orders::Order::decode(bytes)?.into()),
+        }
+    }
+
+    /// The order in this format, or the error response if it can't be encoded.
+    pub fn respond(&self, status: StatusCode, order: &Order) -> Response {
+        match self.encode(order) {
+            Ok(body) => (status, [(header::CONTENT_TYPE, HeaderValue::from_static(self.content_type()))], body).into_response(),
+            Err(err) => error_handler::handler(err).into_response(),
+        }
+    }
+}
+
+/// Order in the request body in any `WireFormat`. JSON bodies are read by `Json`, so they are rejected the same way.
+pub struct OrderBody(pub Order);
+
+#[async_trait]
+impl<S: Send + Sync> FromRequest<S> for OrderBody {
+    type Rejection = Response;
+
+    async fn from_request(request: Request, state: &S) -> Result<Self, Self::Rejection> {
+        match WireFormat::of_body(request.headers()) {
+            Some(format @ (WireFormat::MessagePack | WireFormat::Protobuf)) => {
+                let bytes = Bytes::from_request(request, state).await.map_err(IntoResponse::into_response)?;
+                format.decode(&bytes).map(OrderBody).map_err(|err| {
+                    let error = format!("Can't decode {} order: {err}", format.content_type());
+                    (StatusCode::BAD_REQUEST, Json(json!({ "error": error }))).into_response()
+                })
+            }
+            _ => Json::<Order>::from_request(request, state)
+                .await
+                .map(|Json(order)| OrderBody(order))
+                .map_err(IntoResponse::into_response),
+        }
+    }
+}
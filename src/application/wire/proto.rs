@@ -0,0 +1,161 @@
+use crate::domain::models::{Delivery, Item, Order, OrderSummary, Payment};
+
+/// Messages generated from `proto/orders.proto`.
+pub mod orders {
+    include!(concat!(env!("OUT_DIR"), "/wb_tech_l0.orders.rs"));
+}
+
+impl From<Delivery> for orders::Delivery {
+    fn from(delivery: Delivery) -> Self {
+        Self {
+            name: delivery.name,
+            phone: delivery.phone,
+            zip: delivery.zip,
+            address: delivery.address,
+            region: delivery.region,
+            email: delivery.email,
+        }
+    }
+}
+
+impl From<orders::Delivery> for Delivery {
+    fn from(delivery: orders::Delivery) -> Self {
+        Self {
+            name: delivery.name,
+            phone: delivery.phone,
+            zip: delivery.zip,
+            address: delivery.address,
+            region: delivery.region,
+            email: delivery.email,
+        }
+    }
+}
+
+impl From<Payment> for orders::Payment {
+    fn from(payment: Payment) -> Self {
+        Self {
+            transaction: payment.transaction,
+            request_id: payment.request_id,
+            currency: payment.currency,
+            provider: payment.provider,
+            amount: payment.amount,
+            payment_dt: payment.payment_dt,
+            bank: payment.bank,
+            delivery_cost: payment.delivery_cost,
+            goods_total: payment.goods_total,
+            custom_fee: payment.custom_fee,
+        }
+    }
+}
+
+impl From<orders::Payment> for Payment {
+    fn from(payment: orders::Payment) -> Self {
+        Self {
+            transaction: payment.transaction,
+            request_id: payment.request_id,
+            currency: payment.currency,
+            provider: payment.provider,
+            amount: payment.amount,
+            payment_dt: payment.payment_dt,
+            bank: payment.bank,
+            delivery_cost: payment.delivery_cost,
+            goods_total: payment.goods_total,
+            custom_fee: payment.custom_fee,
+        }
+    }
+}
+
+impl From<Item> for orders::Item {
+    fn from(item: Item) -> Self {
+        Self {
+            chrt_id: item.chrt_id,
+            track_number: item.track_number,
+            price: item.price,
+            rid: item.rid,
+            name: item.name,
+            sale: item.sale,
+            size: item.size,
+            total_price: item.total_price,
+            nm_id: item.nm_id,
+            brand: item.brand,
+            status: item.status,
+        }
+    }
+}
+
+impl From<orders::Item> for Item {
+    fn from(item: orders::Item) -> Self {
+        Self {
+            chrt_id: item.chrt_id,
+            track_number: item.track_number,
+            price: item.price,
+            rid: item.rid,
+            name: item.name,
+            sale: item.sale,
+            size: item.size,
+            total_price: item.total_price,
+            nm_id: item.nm_id,
+            brand: item.brand,
+            status: item.status,
+        }
+    }
+}
+
+impl From<Order> for orders::Order {
+    fn from(order: Order) -> Self {
+        Self {
+            order_uid: order.order_uid,
+            track_number: order.track_number,
+            entry: order.entry,
+            delivery: Some(order.delivery.into()),
+            payment: Some(order.payment.into()),
+            items: order.items.into_iter().map(Into::into).collect(),
+            locale: order.locale,
+            internal_signature: order.internal_signature,
+            customer_id: order.customer_id,
+            delivery_service: order.delivery_service,
+            shardkey: order.shardkey,
+            sm_id: order.sm_id,
+            date_created: order.date_created,
+            oof_shard: order.oof_shard,
+        }
+    }
+}
+
+/// Missing delivery and payment messages become empty ones, like absent scalar fields do in proto3.
+impl From<orders::Order> for Order {
+    fn from(order: orders::Order) -> Self {
+        Self {
+            order_uid: order.order_uid,
+            track_number: order.track_number,
+            entry: order.entry,
+            delivery: order.delivery.unwrap_or_default().into(),
+            payment: order.payment.unwrap_or_default().into(),
+            items: order.items.into_iter().map(Into::into).collect(),
+            locale: order.locale,
+            internal_signature: order.internal_signature,
+            customer_id: order.customer_id,
+            delivery_service: order.delivery_service,
+            shardkey: order.shardkey,
+            sm_id: order.sm_id,
+            date_created: order.date_created,
+            oof_shard: order.oof_shard,
+        }
+    }
+}
+
+impl From<OrderSummary> for orders::OrderSummary {
+    fn from(summary: OrderSummary) -> Self {
+        Self {
+            order_uid: summary.order_uid,
+            track_number: summary.track_number,
+            customer_id: summary.customer_id,
+            customer_name: summary.customer_name,
+            delivery_service: summary.delivery_service,
+            date_created: summary.date_created,
+            amount: summary.amount,
+            currency: summary.currency,
+            item_count: summary.item_count,
+        }
+    }
+}
@@ -0,0 +1,78 @@
+use {
+    super::Backend,
+    clap::Args,
+    std::{error::Error, path::PathBuf},
+    wb_tech_l0::{
+        infrastructure::{Authenticator, Database},
+        interfaces::Database as _,
+    },
+};
+
+#[derive(Args, Debug)]
+pub struct CheckArgs {
+    //Auth config the server is going to be started with
+    #[arg(long)]
+    auth_config: Option<PathBuf>,
+}
+
+/// Prints the outcome of each check, failing if any of them did.
+struct Report {
+    failed: usize,
+}
+
+impl Report {
+    fn ok(&mut self, message: impl AsRef<str>) {
+        println!("ok    {}", message.as_ref());
+    }
+
+    fn fail(&mut self, message: impl AsRef<str>) {
+        println!("FAIL  {}", message.as_ref());
+        self.failed += 1;
+    }
+}
+
+async fn check_postgres(report: &mut Report, database: &Database) {
+    match database.schema_version().await {
+        Ok(version) => {
+            report.ok("Postgres is reachable");
+            match version {
+                Some(version) if version == Database::SCHEMA_VERSION => report.ok(format!("Schema is at V{version}")),
+                Some(version) => report.fail(format!(
+                    "Schema is at V{version}, V{} is expected, apply the migrations",
+                    Database::SCHEMA_VERSION
+                )),
+                None => report.fail("No refinery_schema_history table, apply the migrations"),
+            }
+        }
+        Err(err) => report.fail(format!("Postgres is unreachable: {err}")),
+    }
+    match database.check_replica().await {
+        Ok(Some(lag)) => report.ok(format!("Replica is reachable, {lag:?} behind")),
+        Ok(None) => report.ok("No replica, reads go to the primary"),
+        Err(err) => report.fail(format!("Replica can't be used: {err}")),
+    }
+}
+
+pub async fn check(args: CheckArgs, backend: Result<Backend, Box<dyn Error>>) -> Result<(), Box<dyn Error>> {
+    let mut report = Report { failed: 0 };
+    match &args.auth_config {
+        Some(path) => match Authenticator::from_file(path) {
+            Ok(_) => report.ok(format!("Auth config {} is valid", path.display())),
+            Err(err) => report.fail(format!("Auth config {}: {err}", path.display())),
+        },
+        None => report.ok("No auth config, authentication is disabled"),
+    }
+    match backend {
+        Ok(Backend::Postgres(database)) => check_postgres(&mut report, &database).await,
+        Ok(Backend::Sqlite(database)) => match database.get("").await {
+            Ok(_) => report.ok("SQLite database is readable, its schema is created on open"),
+            Err(err) => report.fail(format!("SQLite database is unreadable: {err}")),
+        },
+        Ok(Backend::Memory) => report.ok("Memory storage needs no database"),
+        Err(err) => report.fail(format!("Storage: {err}")),
+    }
+    if report.failed > 0 {
+        return Err(format!("{} checks failed", report.failed).into());
+    }
+    Ok(())
+}
@@ -0,0 +1,89 @@
+use {
+    clap::{ArgGroup, Args},
+    futures::StreamExt,
+    std::{error::Error, path::PathBuf},
+    tokio::io::{AsyncWrite, AsyncWriteExt},
+    wb_tech_l0::{
+        infrastructure::{encode, ExportFormat},
+        interfaces,
+        models::{DateRange, OrderFilter, OrderLookup},
+    },
+};
+
+#[derive(Args, Debug)]
+#[command(group(ArgGroup::new("lookup").multiple(false)))]
+pub struct ExportArgs {
+    //ndjson with an order per line, csv or parquet with a row per item
+    #[arg(long, default_value_t = ExportFormat::Ndjson)]
+    format: ExportFormat,
+    //File to write, standard output if not set
+    #[arg(short, long)]
+    output: Option<PathBuf>,
+    //Only orders created on this day or later, YYYY-MM-DD
+    #[arg(long)]
+    from: Option<String>,
+    //Only orders created on this day or earlier, YYYY-MM-DD
+    #[arg(long)]
+    to: Option<String>,
+    //Only orders with this track number
+    #[arg(long, group = "lookup")]
+    track_number: Option<String>,
+    //Only orders of this customer
+    #[arg(long, group = "lookup")]
+    customer_id: Option<String>,
+    //Only the order paid by this payment transaction
+    #[arg(long, group = "lookup")]
+    transaction: Option<String>,
+    //Only orders paid with this payment request id
+    #[arg(long, group = "lookup")]
+    request_id: Option<String>,
+    //Only orders containing an item with this chrt_id
+    #[arg(long, group = "lookup")]
+    chrt_id: Option<i32>,
+    //Only orders containing an item with this nm_id
+    #[arg(long, group = "lookup")]
+    nm_id: Option<i32>,
+}
+
+impl ExportArgs {
+    fn filter(&self) -> Result<OrderFilter, Box<dyn Error>> {
+        let range = DateRange {
+            from: self.from.clone(),
+            to: self.to.clone(),
+        };
+        if let Some(value) = range.malformed() {
+            return Err(format!("{value} is not a YYYY-MM-DD date").into());
+        }
+        // clap lets through at most one of them
+        let lookup = [
+            self.track_number.clone().map(OrderLookup::TrackNumber),
+            self.customer_id.clone().map(OrderLookup::CustomerId),
+            self.transaction.clone().map(OrderLookup::Transaction),
+            self.request_id.clone().map(OrderLookup::RequestId),
+            self.chrt_id.map(OrderLookup::ChrtId),
+            self.nm_id.map(OrderLookup::NmId),
+        ]
+        .into_iter()
+        .flatten()
+        .next();
+        Ok(OrderFilter { lookup, range })
+    }
+}
+
+pub async fn export<D>(args: ExportArgs, database: D) -> Result<(), Box<dyn Error>>
+where
+    D: interfaces::OrderExport<Error = Box<dyn Error>>,
+{
+    let filter = args.filter()?;
+    let mut output: Box<dyn AsyncWrite + Unpin> = match &args.output {
+        Some(path) => Box::new(tokio::fs::File::create(path).await?),
+        None => Box::new(tokio::io::stdout()),
+    };
+    let mut chunks = encode(database.export(&filter).await?, args.format.encoder());
+    while let Some(chunk) = chunks.next().await {
+        let chunk = chunk.map_err(|err| -> Box<dyn Error> { err })?;
+        output.write_all(&chunk).await?;
+    }
+    output.flush().await?;
+    Ok(())
+}
@@ -0,0 +1,29 @@
+use {
+    clap::Args,
+    std::error::Error,
+    wb_tech_l0::{
+        infrastructure::{Cache, OrderService, Repository},
+        interfaces::{self, OrderService as _},
+        models::Principal,
+    },
+};
+
+#[derive(Args, Debug)]
+pub struct GetArgs {
+    //order_uid of the order
+    uid: String,
+}
+
+pub async fn get<D>(args: GetArgs, database: D) -> Result<(), Box<dyn Error>>
+where
+    D: interfaces::Database<Error = Box<dyn Error>> + 'static,
+{
+    let repository = Repository::new(Cache::new(), database);
+    match OrderService::new().get_order(&args.uid, &repository, &Principal::local()).await? {
+        Some(order) => {
+            println!("{}", serde_json::to_string_pretty(&order)?);
+            Ok(())
+        }
+        None => Err(format!("Order {} not found", args.uid).into()),
+    }
+}
@@ -0,0 +1,81 @@
+use {
+    clap::Args,
+    serde_json::Value,
+    std::{
+        error::Error,
+        fs::File,
+        io::{BufRead, BufReader},
+        path::{Path, PathBuf},
+    },
+    wb_tech_l0::{
+        infrastructure::{is_conflict, Cache, OrderService, Repository},
+        interfaces::{self, OrderService as _},
+        models::{Order, Principal},
+    },
+};
+
+#[derive(Args, Debug)]
+pub struct ImportArgs {
+    //JSON file with an order or an array of them, .ndjson and .jsonl files have an order per line
+    file: PathBuf,
+}
+
+/// An order read from the file, or why it couldn't be, labeled with where it came from.
+type Entry = (String, Result<Order, String>);
+
+fn is_ndjson(path: &Path) -> bool {
+    matches!(path.extension().and_then(|extension| extension.to_str()), Some("ndjson" | "jsonl"))
+}
+
+fn read_ndjson(path: &Path) -> Result<Box<dyn Iterator<Item = Entry>>, Box<dyn Error>> {
+    let lines = BufReader::new(File::open(path)?).lines();
+    Ok(Box::new(lines.enumerate().filter_map(|(index, line)| {
+        let label = format!("line {}", index + 1);
+        match line {
+            Ok(line) if line.trim().is_empty() => None,
+            Ok(line) => Some((label, serde_json::from_str(&line).map_err(|err| err.to_string()))),
+            Err(err) => Some((label, Err(err.to_string()))),
+        }
+    })))
+}
+
+fn read_json(path: &Path) -> Result<Box<dyn Iterator<Item = Entry>>, Box<dyn Error>> {
+    let orders = match serde_json::from_reader(BufReader::new(File::open(path)?))? {
+        Value::Array(orders) => orders,
+        order => vec![order],
+    };
+    Ok(Box::new(orders.into_iter().enumerate().map(|(index, order)| {
+        (format!("order {}", index + 1), serde_json::from_value(order).map_err(|err| err.to_string()))
+    })))
+}
+
+/// Adds the orders the way `POST /add_order` does, one by one, so a bad order doesn't stop the rest.
+pub async fn import<D>(args: ImportArgs, database: D) -> Result<(), Box<dyn Error>>
+where
+    D: interfaces::Database<Error = Box<dyn Error>> + 'static,
+{
+    let entries = if is_ndjson(&args.file) { read_ndjson(&args.file)? } else { read_json(&args.file)? };
+    let repository = Repository::new(Cache::new(), database);
+    let order_service = OrderService::new();
+    let principal = Principal::local();
+    let (mut imported, mut skipped, mut failed) = (0, 0, 0);
+    for (label, order) in entries {
+        let result = match order {
+            Ok(order) => order_service.add_order(&repository, order, &principal).await,
+            Err(err) => Err(err.into()),
+        };
+        match result {
+            Ok(()) => imported += 1,
+            Err(err) if is_conflict(err.as_ref()) => skipped += 1,
+            Err(err) => {
+                eprintln!("{label}: {err}");
+                failed += 1;
+            }
+        }
+    }
+    println!("Imported {imported}, skipped {skipped} existing, {failed} failed");
+    if failed > 0 {
+        return Err(format!("{failed} orders weren't imported").into());
+    }
+    Ok(())
+}
@@ -0,0 +1,148 @@
+mod check;
+mod export;
+mod get;
+mod import;
+mod serve;
+
+pub use {
+    check::{check, CheckArgs},
+    export::{export, ExportArgs},
+    get::{get, GetArgs},
+    import::{import, ImportArgs},
+    serve::{serve, ServeArgs},
+};
+
+use {
+    clap::{Args, Subcommand, ValueEnum},
+    std::{error::Error, time::Duration},
+    wb_tech_l0::infrastructure::{
+        BreakerConfig, Database, DatabaseSettings, ReplicaConfig, RetryPolicy, SqliteDatabase,
+    },
+};
+
+#[derive(ValueEnum, Clone, Copy, Debug)]
+pub enum Storage {
+    Postgres,
+    Sqlite,
+    Memory,
+}
+
+/// Where orders are kept, shared by every command.
+#[derive(Args, Debug)]
+pub struct StorageArgs {
+    //Where orders are kept, only Postgres supports search, analytics, webhooks and the outbox
+    #[arg(long, global = true, value_enum, default_value_t = Storage::Postgres)]
+    pub storage: Storage,
+    //Postgres connection URI or SQLite file path, not used by memory storage
+    #[arg(short, long, global = true)]
+    pub database: Option<String>,
+    //Postgres connection URI of a read replica, lookups, search, reports and exports are read from it
+    #[arg(long, global = true)]
+    pub replica_database: Option<String>,
+    //Reads of orders and customers written this recently go to the primary instead of the replica
+    #[arg(long, global = true, default_value_t = 5000)]
+    pub read_after_write_ms: u64,
+    //Replication lag beyond which reads go to the primary
+    #[arg(long, global = true, default_value_t = 10_000)]
+    pub replica_max_lag_ms: u64,
+    //Postgres connections kept open at most, four per CPU by default
+    #[arg(long, global = true)]
+    pub db_pool_size: Option<usize>,
+    //How long a query waits for a free Postgres connection
+    #[arg(long, global = true, default_value_t = 30_000)]
+    pub db_wait_timeout_ms: u64,
+    //How long opening a Postgres connection may take, unlimited by default
+    #[arg(long, global = true)]
+    pub db_create_timeout_ms: Option<u64>,
+    //How long checking an idle Postgres connection before reuse may take, unlimited by default
+    #[arg(long, global = true)]
+    pub db_recycle_timeout_ms: Option<u64>,
+    //Attempts at a Postgres read or insert failing with a serialization failure, deadlock or lost connection
+    #[arg(long, global = true, default_value_t = 3, value_parser = clap::value_parser!(u32).range(1..))]
+    pub db_max_attempts: u32,
+    //Lost Postgres connections in a row after which requests fail right away
+    #[arg(long, global = true, default_value_t = 5, value_parser = clap::value_parser!(u32).range(1..))]
+    pub db_breaker_threshold: u32,
+    //How long requests fail right away before Postgres is tried again
+    #[arg(long, global = true, default_value_t = 10_000)]
+    pub db_breaker_open_ms: u64,
+}
+
+pub enum Backend {
+    Postgres(Database),
+    Sqlite(SqliteDatabase),
+    Memory,
+}
+
+impl StorageArgs {
+    fn database_settings(&self) -> DatabaseSettings {
+        DatabaseSettings {
+            max_connections: self.db_pool_size,
+            wait_timeout: Duration::from_millis(self.db_wait_timeout_ms),
+            create_timeout: self.db_create_timeout_ms.map(Duration::from_millis),
+            recycle_timeout: self.db_recycle_timeout_ms.map(Duration::from_millis),
+            retry: RetryPolicy {
+                max_attempts: self.db_max_attempts,
+                ..RetryPolicy::default()
+            },
+            breaker: BreakerConfig {
+                failure_threshold: self.db_breaker_threshold,
+                open_for: Duration::from_millis(self.db_breaker_open_ms),
+            },
+        }
+    }
+
+    /// Connections are made lazily, opening doesn't check that the database is reachable.
+    pub async fn open(&self) -> Result<Backend, Box<dyn Error>> {
+        match (self.storage, self.database.clone()) {
+            (Storage::Postgres, Some(config)) => {
+                let mut database = Database::with_settings(config.parse()?, self.database_settings())?;
+                if let Some(replica) = &self.replica_database {
+                    let config = ReplicaConfig {
+                        read_after_write: Duration::from_millis(self.read_after_write_ms),
+                        max_lag: Duration::from_millis(self.replica_max_lag_ms),
+                    };
+                    database = database.with_replica(replica.parse()?, config)?;
+                }
+                Ok(Backend::Postgres(database))
+            }
+            (Storage::Sqlite | Storage::Memory, _) if self.replica_database.is_some() => {
+                Err("--replica-database is only supported by postgres storage".into())
+            }
+            (Storage::Sqlite, Some(path)) => Ok(Backend::Sqlite(SqliteDatabase::open(path)?)),
+            (Storage::Postgres | Storage::Sqlite, None) => {
+                Err("--database is required for postgres and sqlite storage".into())
+            }
+            (Storage::Memory, _) => Ok(Backend::Memory),
+        }
+    }
+}
+
+#[derive(Subcommand, Debug)]
+pub enum Command {
+    /// Run the HTTP server, the default when no command is given
+    Serve(ServeArgs),
+    /// Add orders from a JSON or NDJSON file, existing ones are skipped
+    Import(ImportArgs),
+    /// Write orders to standard output or a file, oldest first
+    Export(ExportArgs),
+    /// Print one order as JSON
+    Get(GetArgs),
+    /// Validate the configuration, database connectivity and schema version
+    Check(CheckArgs),
+}
+
+/// Runs a command that reads or writes stored orders, these make no sense without a database.
+macro_rules! with_database {
+    ($backend:expr, $database:ident => $body:expr) => {
+        match $backend {
+            $crate::commands::Backend::Postgres($database) => $body,
+            $crate::commands::Backend::Sqlite($database) => $body,
+            $crate::commands::Backend::Memory => {
+                Err("Memory storage keeps nothing between runs, use postgres or sqlite".into())
+            }
+        }
+    };
+}
+
+pub(crate) use with_database;
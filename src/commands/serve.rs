@@ -0,0 +1,226 @@
+use {
+    super::Backend,
+    clap::Args,
+    log::{info, warn},
+    std::{error::Error, io, net::SocketAddr, path::{Path, PathBuf}, sync::Arc, time::Duration},
+    wb_tech_l0::{
+        application::{grpc::serve_grpc, router, AppState, HttpSettings},
+        interfaces::EventSink,
+        infrastructure::{
+            sink_from_uri, AnalyticsRefresher, Authenticator, Cache, CacheSnapshotter, Database, FanoutSink, MemoryDatabase, OrderBroadcaster,
+            OrderService, OutboxRelay, Quota, RateLimiter, RelayConfig, ReplicaMonitor, Repository, WebhookConfig,
+            WebhookDispatcher, WebhookWorker,
+        },
+    },
+};
+
+#[derive(Args, Debug)]
+pub struct ServeArgs {
+    //Where to relay order events: http(s)://..., nats://host:port/subject or file:///path
+    #[arg(long)]
+    outbox_sink: Option<String>,
+    //How often the outbox is polled for pending events
+    #[arg(long, default_value_t = 1000)]
+    outbox_poll_interval_ms: u64,
+    //Delivery attempts before an event is given up on
+    #[arg(long, default_value_t = 10)]
+    outbox_max_attempts: i32,
+    //Delivery attempts of a single webhook call before it is given up on
+    #[arg(long, default_value_t = 8)]
+    webhook_max_attempts: i32,
+    //Consecutive failed webhook calls after which the subscription is disabled
+    #[arg(long, default_value_t = 20)]
+    webhook_disable_after: i32,
+    //How many recent orders /orders/stream keeps for clients resuming with Last-Event-ID
+    #[arg(long, default_value_t = 1000)]
+    stream_replay_buffer: usize,
+    //JSON file with hashed API keys and JWKS location, authentication is disabled without it
+    #[arg(long)]
+    auth_config: Option<PathBuf>,
+    //Read requests per second allowed for each client, unlimited if not set
+    #[arg(long, value_parser = parse_rate)]
+    read_rate_limit: Option<f64>,
+    //Read requests a client may burst above the rate, twice the rate by default
+    #[arg(long)]
+    read_burst: Option<u32>,
+    //Write requests per second allowed for each client, unlimited if not set
+    #[arg(long, value_parser = parse_rate)]
+    write_rate_limit: Option<f64>,
+    //Write requests a client may burst above the rate, twice the rate by default
+    #[arg(long)]
+    write_burst: Option<u32>,
+    //Maximum number of requests working with the database at once
+    #[arg(long)]
+    db_concurrency: Option<usize>,
+    //Largest accepted order body in bytes, bigger requests get 413
+    #[arg(long, default_value_t = 1024 * 1024)]
+    max_order_body_bytes: usize,
+    //Time a read request may take before it's answered with 503
+    #[arg(long, default_value_t = 10_000)]
+    read_timeout_ms: u64,
+    //Time a write request may take before it's answered with 503
+    #[arg(long, default_value_t = 30_000)]
+    write_timeout_ms: u64,
+    //Browser origin allowed to call the API, repeatable, * allows any, CORS is off if not set
+    #[arg(long = "cors-origin")]
+    cors_origins: Vec<String>,
+    //How often sales analytics are recomputed
+    #[arg(long, default_value_t = 300)]
+    analytics_refresh_secs: u64,
+    //Don't compress order, report and webhook responses
+    #[arg(long)]
+    disable_compression: bool,
+    //Port of the gRPC OrderService, served next to the HTTP API
+    #[arg(long, default_value_t = 50051)]
+    grpc_port: u16,
+    //File the order cache is saved to periodically and restored from on start, not used by memory storage
+    #[arg(long)]
+    cache_snapshot: Option<PathBuf>,
+    //How often the order cache is saved to --cache-snapshot
+    #[arg(long, default_value_t = 60)]
+    cache_snapshot_interval_secs: u64,
+}
+
+/// How often the read replica's lag is measured
+const REPLICA_CHECK_INTERVAL: Duration = Duration::from_secs(1);
+
+fn parse_rate(value: &str) -> Result<f64, String> {
+    match value.parse::<f64>() {
+        Ok(rate) if rate.is_finite() && rate > 0.0 => Ok(rate),
+        Ok(_) => Err("must be a positive number of requests per second".to_string()),
+        Err(err) => Err(err.to_string()),
+    }
+}
+
+fn rate_limiter(per_second: Option<f64>, burst: Option<u32>) -> Option<RateLimiter> {
+    let per_second = per_second.filter(|rate| rate.is_finite() && *rate > 0.0)?;
+    let burst = burst.unwrap_or((per_second * 2.0).ceil() as u32);
+    Some(RateLimiter::new(Quota { per_second, burst }))
+}
+
+/// Orders of the snapshot if there is a usable one, an empty cache otherwise.
+async fn restore_cache(path: &Path) -> Cache {
+    match Cache::load_snapshot(path).await {
+        Ok(cache) => {
+            info!("Restored {} orders from {}", cache.order_uids().await.len(), path.display());
+            cache
+        }
+        Err(err) if err.downcast_ref::<io::Error>().is_some_and(|err| err.kind() == io::ErrorKind::NotFound) => {
+            info!("No cache snapshot at {} yet", path.display());
+            Cache::new()
+        }
+        Err(err) => {
+            warn!("Ignoring cache snapshot {}: {err}", path.display());
+            Cache::new()
+        }
+    }
+}
+
+/// Cache for a database, restored from the snapshot and saved back periodically if one is configured.
+async fn cache<D>(args: &ServeArgs, database: &D) -> Cache
+where
+    D: wb_tech_l0::interfaces::Database<Error = Box<dyn Error>> + Clone + 'static,
+{
+    let Some(path) = &args.cache_snapshot else {
+        return Cache::new();
+    };
+    let cache = restore_cache(path).await;
+    let interval = Duration::from_secs(args.cache_snapshot_interval_secs);
+    tokio::spawn(CacheSnapshotter::new(cache.clone(), database.clone(), path.clone(), interval).run());
+    cache
+}
+
+/// State backed by Postgres, with the background workers only it supports.
+async fn postgres_state(
+    args: &ServeArgs,
+    database: Database,
+    order_service: Box<OrderService>,
+) -> Result<AppState, Box<dyn Error>> {
+    let mut sinks: Vec<Box<dyn EventSink>> = vec![Box::new(WebhookDispatcher::new(database.clone()))];
+    match &args.outbox_sink {
+        Some(uri) => {
+            sinks.push(sink_from_uri(uri).await?);
+            info!("Relaying order events to {uri}");
+        }
+        None => warn!("No outbox sink configured, order events are only delivered to webhooks"),
+    }
+    let relay_config = RelayConfig {
+        poll_interval: Duration::from_millis(args.outbox_poll_interval_ms),
+        max_attempts: args.outbox_max_attempts,
+        ..Default::default()
+    };
+    let sink = Box::new(FanoutSink::new(sinks));
+    tokio::spawn(OutboxRelay::new(database.clone(), sink, relay_config).run());
+    let webhook_config = WebhookConfig {
+        max_attempts: args.webhook_max_attempts,
+        disable_after: args.webhook_disable_after,
+        ..Default::default()
+    };
+    tokio::spawn(WebhookWorker::new(database.clone(), webhook_config).run());
+    tokio::spawn(AnalyticsRefresher::new(database.clone(), Duration::from_secs(args.analytics_refresh_secs)).run());
+    if database.has_replica() {
+        tokio::spawn(ReplicaMonitor::new(database.clone(), REPLICA_CHECK_INTERVAL).run());
+    }
+    let repository = Box::new(Repository::new(cache(args, &database).await, database.clone()));
+    Ok(AppState::new(repository, order_service)
+        .with_search(Box::new(database.clone()))
+        .with_analytics(Box::new(database.clone()))
+        .with_export(Box::new(database.clone()))
+        .with_webhooks(Box::new(database)))
+}
+
+pub async fn serve(args: ServeArgs, backend: Backend) -> Result<(), Box<dyn Error>> {
+    let addr = "0.0.0.0:7878";
+    let authenticator = match &args.auth_config {
+        Some(path) => Authenticator::from_file(path)?,
+        None => {
+            warn!("No auth config given, every request is allowed");
+            Authenticator::disabled()
+        }
+    };
+    let order_stream = Arc::new(OrderBroadcaster::new(args.stream_replay_buffer));
+    let order_service = Box::new(OrderService::new().with_notifier(order_stream.clone()));
+    let app_state = match backend {
+        Backend::Postgres(database) => postgres_state(&args, database, order_service).await?,
+        Backend::Sqlite(database) => {
+            warn!("SQLite storage has no search, analytics, webhooks or outbox");
+            let cache = cache(&args, &database).await;
+            AppState::new(Box::new(Repository::new(cache, database.clone())), order_service)
+                .with_export(Box::new(database))
+        }
+        Backend::Memory => {
+            warn!("Orders are kept in memory and lost on restart");
+            if args.cache_snapshot.is_some() {
+                warn!("Memory storage starts empty, --cache-snapshot is ignored");
+            }
+            AppState::new(Box::new(Repository::new(Cache::new(), MemoryDatabase::new())), order_service)
+        }
+    };
+    let http_settings = HttpSettings {
+        max_order_body: args.max_order_body_bytes,
+        read_timeout: Duration::from_millis(args.read_timeout_ms),
+        write_timeout: Duration::from_millis(args.write_timeout_ms),
+        cors_origins: args.cors_origins,
+        compression: !args.disable_compression,
+    };
+    let mut app_state = app_state
+        .with_http_settings(http_settings)
+        .with_order_stream(order_stream)
+        .with_authenticator(Box::new(authenticator))
+        .with_rate_limits(
+            rate_limiter(args.read_rate_limit, args.read_burst),
+            rate_limiter(args.write_rate_limit, args.write_burst),
+        );
+    if let Some(limit) = args.db_concurrency {
+        app_state = app_state.with_db_concurrency(limit);
+    }
+    let app_state = Arc::new(app_state);
+    let router = router(app_state.clone());
+    let listener = tokio::net::TcpListener::bind(addr).await?;
+    let grpc_addr = SocketAddr::from(([0, 0, 0, 0], args.grpc_port));
+    let grpc_listener = tokio::net::TcpListener::bind(grpc_addr).await?;
+    info!("Listening on {addr}, gRPC on {grpc_addr}");
+    let http = async { Ok(axum::serve(listener, router.into_make_service_with_connect_info::<SocketAddr>()).await?) };
+    tokio::try_join!(http, serve_grpc(app_state, grpc_listener))?;
+    Ok(())
+}
@@ -0,0 +1,13 @@
+use crate::domain::models::{DateRange, SalesDimension, SalesRow};
+use axum::async_trait;
+
+#[async_trait]
+pub trait SalesAnalytics: Sync + Send {
+    type Error;
+
+    /// Orders and revenue per group and currency, as of the last refresh.
+    async fn sales(&self, dimension: SalesDimension, range: &DateRange) -> Result<Vec<SalesRow>, Self::Error>;
+
+    /// Recomputes the aggregates from the orders.
+    async fn refresh(&self) -> Result<(), Self::Error>;
+}
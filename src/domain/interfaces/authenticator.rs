@@ -0,0 +1,13 @@
+use crate::domain::models::Principal;
+use std::error::Error;
+
+pub enum Credentials {
+    None,
+    ApiKey(String),
+    Bearer(String),
+}
+
+pub trait Authenticator: Sync + Send {
+    /// Resolves credentials into a principal, fails when they are present but invalid.
+    fn authenticate(&self, credentials: &Credentials) -> Result<Principal, Box<dyn Error + Send + Sync>>;
+}
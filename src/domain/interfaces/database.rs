@@ -1,5 +1,5 @@
 use axum::async_trait;
-use crate::domain::models::Order;
+use crate::domain::models::{CustomerTotals, Order, OrderLookup, OrderSummary};
 
 #[async_trait]
 pub trait Database: Sync + Send {
@@ -10,4 +10,39 @@ pub trait Database: Sync + Send {
     async fn remove(&self, id: &str) -> Result<(), Self::Error>;
     
     async fn get(&self, id: &str) -> Result<Option<Order>, Self::Error>;
-}
\ No newline at end of file
+
+    /// Orders with these uids in no particular order, unknown uids are left out.
+    async fn get_many(&self, ids: &[String]) -> Result<Vec<Order>, Self::Error> {
+        let mut orders = Vec::with_capacity(ids.len());
+        for id in ids {
+            if let Some(order) = self.get(id).await? {
+                orders.push(order);
+            }
+        }
+        Ok(orders)
+    }
+
+    /// Those of the uids the database has orders for, without reading the orders where the store allows it.
+    async fn existing(&self, ids: &[String]) -> Result<Vec<String>, Self::Error> {
+        Ok(self.get_many(ids).await?.into_iter().map(|order| order.order_uid).collect())
+    }
+
+    /// Summaries of the orders matching the lookup, newest first.
+    async fn find(&self, lookup: &OrderLookup, limit: i64, offset: i64) -> Result<Vec<OrderSummary>, Self::Error>;
+
+    async fn customer_totals(&self, customer_id: &str) -> Result<CustomerTotals, Self::Error>;
+
+    /// Summaries of customer's orders, newest first, with totals over all of them. Stores read both from
+    /// one snapshot so that the page and the totals agree, the default reads them one after another.
+    async fn customer_orders(
+        &self,
+        customer_id: &str,
+        limit: i64,
+        offset: i64,
+    ) -> Result<(Vec<OrderSummary>, CustomerTotals), Self::Error> {
+        let lookup = OrderLookup::CustomerId(customer_id.to_string());
+        let orders = self.find(&lookup, limit, offset).await?;
+        let totals = self.customer_totals(customer_id).await?;
+        Ok((orders, totals))
+    }
+}
@@ -2,8 +2,22 @@ mod cache;
 mod database;
 mod repository;
 mod order_service;
+mod outbox;
+mod webhooks;
+mod order_notifier;
+mod authenticator;
+mod order_search;
+mod analytics;
+mod order_export;
 
 pub use cache::*;
 pub use database::*;
 pub use repository::*;
 pub use order_service::*;
+pub use outbox::*;
+pub use webhooks::*;
+pub use order_notifier::*;
+pub use authenticator::*;
+pub use order_search::*;
+pub use analytics::*;
+pub use order_export::*;
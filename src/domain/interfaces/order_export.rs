@@ -0,0 +1,16 @@
+use crate::domain::models::{Order, OrderFilter};
+use axum::async_trait;
+use futures::stream::BoxStream;
+use std::error::Error;
+
+/// Orders coming one by one, errors may show up midway.
+pub type OrderStream = BoxStream<'static, Result<Order, Box<dyn Error + Send + Sync>>>;
+
+#[async_trait]
+pub trait OrderExport: Sync + Send {
+    type Error;
+
+    /// Every order matching the filter, oldest first. Orders are read as the stream is polled,
+    /// so exports don't have to fit in memory.
+    async fn export(&self, filter: &OrderFilter) -> Result<OrderStream, Self::Error>;
+}
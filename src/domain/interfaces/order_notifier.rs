@@ -0,0 +1,6 @@
+use crate::domain::models::Order;
+
+/// Gets notified about every order accepted by the order service.
+pub trait OrderNotifier: Sync + Send {
+    fn order_added(&self, order: &Order);
+}
@@ -0,0 +1,11 @@
+use crate::domain::models::OrderSummary;
+use axum::async_trait;
+
+#[async_trait]
+pub trait OrderSearch: Sync + Send {
+    type Error;
+
+    /// Finds orders containing every word of the query as a prefix of a word in their track number,
+    /// recipient name, phone or email, or in an item's name or brand. Best matches come first.
+    async fn search(&self, query: &str, limit: i64, offset: i64) -> Result<Vec<OrderSummary>, Self::Error>;
+}
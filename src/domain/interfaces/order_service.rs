@@ -1,4 +1,4 @@
-use crate::domain::models::Order;
+use crate::domain::models::{CustomerOrders, Order, OrderLookup, OrderSummary, Principal};
 use axum::async_trait;
 use std::error::Error;
 use crate::domain::interfaces;
@@ -12,11 +12,39 @@ pub trait OrderService: Sync + Send {
         &self,
         repository: &Repository,
         order: Order,
+        principal: &Principal,
     ) -> Result<(), Box<dyn Error>>;
 
     async fn get_order(
         &self,
         order_uid: &str,
         repository: &Repository,
+        principal: &Principal,
     ) -> Result<Option<Order>, Box<dyn Error>>;
+
+    /// Orders with these uids in no particular order, unknown uids are left out.
+    async fn get_orders(
+        &self,
+        order_uids: &[String],
+        repository: &Repository,
+        principal: &Principal,
+    ) -> Result<Vec<Order>, Box<dyn Error>>;
+
+    async fn find_orders(
+        &self,
+        lookup: &OrderLookup,
+        limit: i64,
+        offset: i64,
+        repository: &Repository,
+        principal: &Principal,
+    ) -> Result<Vec<OrderSummary>, Box<dyn Error>>;
+
+    async fn customer_orders(
+        &self,
+        customer_id: &str,
+        limit: i64,
+        offset: i64,
+        repository: &Repository,
+        principal: &Principal,
+    ) -> Result<CustomerOrders, Box<dyn Error>>;
 }
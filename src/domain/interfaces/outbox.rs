@@ -0,0 +1,23 @@
+use crate::domain::models::OrderEvent;
+use axum::async_trait;
+use std::error::Error;
+use std::time::Duration;
+
+#[async_trait]
+pub trait Outbox: Sync + Send {
+    type Error;
+
+    /// Leases up to `limit` pending events so that concurrent relays don't pick them up
+    /// until `lease` expires.
+    async fn claim_pending(&self, limit: i64, lease: Duration) -> Result<Vec<OrderEvent>, Self::Error>;
+
+    async fn mark_delivered(&self, id: i64) -> Result<(), Self::Error>;
+
+    /// Records a failed attempt. `retry_in: None` means the event is given up on.
+    async fn mark_failed(&self, id: i64, error: &str, retry_in: Option<Duration>) -> Result<(), Self::Error>;
+}
+
+#[async_trait]
+pub trait EventSink: Sync + Send {
+    async fn publish(&self, event: &OrderEvent) -> Result<(), Box<dyn Error + Send + Sync>>;
+}
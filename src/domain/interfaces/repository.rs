@@ -1,4 +1,4 @@
-use crate::domain::models::Order;
+use crate::domain::models::{CustomerOrders, Order, OrderLookup, OrderSummary};
 use axum::async_trait;
 
 #[async_trait]
@@ -12,4 +12,32 @@ pub trait Repository: Send + Sync {
     async fn get(&self, id: &str) -> Result<Option<Order>, Self::Error>;
     
     async fn get_and_cache(&self, id: &str) -> Result<Option<Order>, Self::Error>;
+
+    /// Orders with these uids in no particular order, unknown uids are left out.
+    async fn get_many(&self, ids: &[String]) -> Result<Vec<Order>, Self::Error> {
+        let mut orders = Vec::with_capacity(ids.len());
+        for id in ids {
+            if let Some(order) = self.get(id).await? {
+                orders.push(order);
+            }
+        }
+        Ok(orders)
+    }
+
+    /// Like `get_many`, but orders read from the database are cached.
+    async fn get_many_and_cache(&self, ids: &[String]) -> Result<Vec<Order>, Self::Error> {
+        let mut orders = Vec::with_capacity(ids.len());
+        for id in ids {
+            if let Some(order) = self.get_and_cache(id).await? {
+                orders.push(order);
+            }
+        }
+        Ok(orders)
+    }
+
+    /// Summaries of the orders matching the lookup, newest first.
+    async fn find(&self, lookup: &OrderLookup, limit: i64, offset: i64) -> Result<Vec<OrderSummary>, Self::Error>;
+
+    /// A page of customer's orders with totals, answers are cached until the customer places a new order.
+    async fn customer_orders(&self, customer_id: &str, limit: i64, offset: i64) -> Result<CustomerOrders, Self::Error>;
 }
@@ -0,0 +1,40 @@
+use crate::domain::models::{OrderEvent, WebhookDelivery, WebhookSubscription, WebhookSubscriptionRequest};
+use axum::async_trait;
+use std::time::Duration;
+
+#[async_trait]
+pub trait WebhookStore: Sync + Send {
+    type Error;
+
+    async fn create(&self, request: WebhookSubscriptionRequest) -> Result<WebhookSubscription, Self::Error>;
+
+    async fn list(&self) -> Result<Vec<WebhookSubscription>, Self::Error>;
+
+    async fn get(&self, id: i64) -> Result<Option<WebhookSubscription>, Self::Error>;
+
+    /// Replaces the subscription, re-enabling it also resets its failure counter.
+    async fn update(&self, id: i64, request: WebhookSubscriptionRequest) -> Result<Option<WebhookSubscription>, Self::Error>;
+
+    async fn delete(&self, id: i64) -> Result<bool, Self::Error>;
+
+    async fn deliveries(&self, subscription_id: i64, limit: i64) -> Result<Vec<WebhookDelivery>, Self::Error>;
+
+    /// Creates a pending delivery for every enabled subscription matching the event.
+    /// Enqueuing the same event twice is a no-op.
+    async fn enqueue(&self, event: &OrderEvent) -> Result<u64, Self::Error>;
+
+    async fn claim_due(&self, limit: i64, lease: Duration) -> Result<Vec<(WebhookDelivery, WebhookSubscription)>, Self::Error>;
+
+    async fn record_success(&self, delivery: &WebhookDelivery, status_code: i32) -> Result<(), Self::Error>;
+
+    /// Records a failed attempt. `retry_in: None` gives up on the delivery, the subscription is
+    /// disabled once it reaches `disable_after` consecutive failures.
+    async fn record_failure(
+        &self,
+        delivery: &WebhookDelivery,
+        status_code: Option<i32>,
+        error: &str,
+        retry_in: Option<Duration>,
+        disable_after: i32,
+    ) -> Result<(), Self::Error>;
+}
@@ -0,0 +1,154 @@
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+/// What sales are grouped by.
+#[derive(Serialize, Deserialize, ToSchema, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum SalesDimension {
+    Day,
+    Brand,
+    DeliveryService,
+    Region,
+    Provider,
+    Bank,
+    Currency,
+}
+
+/// Inclusive range of order days in `YYYY-MM-DD` form, unbounded where absent.
+#[derive(Serialize, Deserialize, ToSchema, Debug, Default, Clone)]
+pub struct DateRange {
+    pub from: Option<String>,
+    pub to: Option<String>,
+}
+
+impl DateRange {
+    pub fn is_date(value: &str) -> bool {
+        let parts: Vec<&str> = value.split('-').collect();
+        let numeric = |part: &str, len: usize| part.len() == len && part.bytes().all(|b| b.is_ascii_digit());
+        matches!(parts[..], [year, month, day] if numeric(year, 4) && numeric(month, 2) && numeric(day, 2))
+            && (1..=12).contains(&parts[1].parse::<u32>().unwrap_or(0))
+            && (1..=31).contains(&parts[2].parse::<u32>().unwrap_or(0))
+    }
+
+    /// The first bound that isn't a `YYYY-MM-DD` date.
+    pub fn malformed(&self) -> Option<&str> {
+        [&self.from, &self.to].into_iter().flatten().map(String::as_str).find(|date| !Self::is_date(date))
+    }
+
+    /// Whether the day of an order's `date_created` falls into the range. The day is the UTC day of the timestamp,
+    /// as Postgres `order_day()` takes it, orders without a leading `YYYY-MM-DD` are only in unbounded ranges.
+    pub fn contains(&self, date_created: &str) -> bool {
+        if self.from.is_none() && self.to.is_none() {
+            return true;
+        }
+        let Some(day) = Self::utc_day(date_created) else {
+            return false;
+        };
+        let day = day.as_str();
+        self.from.as_deref().is_none_or(|from| from <= day) && self.to.as_deref().is_none_or(|to| day <= to)
+    }
+
+    /// The UTC day of a `YYYY-MM-DD[(T| )HH:MM[:SS[.fff]]][Z|±HH[[:]MM]]` timestamp, without an offset it's
+    /// taken as UTC. Anything else has no day.
+    pub fn utc_day(date_created: &str) -> Option<String> {
+        let day = date_created.get(..10).filter(|day| Self::is_date(day))?;
+        let (year, month, date) = (day[..4].parse().ok()?, day[5..7].parse().ok()?, day[8..].parse().ok()?);
+        let days = days_from_civil(year, month, date);
+        if civil_from_days(days) != (year, month, date) {
+            return None;
+        }
+        let minutes = match &date_created[10..] {
+            "" => 0,
+            time => utc_minutes(time.strip_prefix(['T', ' '])?)?,
+        };
+        let (year, month, date) = civil_from_days(days + minutes.div_euclid(24 * 60));
+        Some(format!("{year:04}-{month:02}-{date:02}"))
+    }
+}
+
+/// Minutes past midnight in UTC of `HH:MM[:SS[.fff]][Z|±HH[[:]MM]]`, out of `0..1440` when the offset moves it
+/// to another day.
+fn utc_minutes(time: &str) -> Option<i64> {
+    let two_digits =
+        |value: &str| value.bytes().all(|b| b.is_ascii_digit()).then(|| value.parse::<i64>().ok()).flatten();
+    let (hours, minutes) = (two_digits(time.get(..2)?)?, two_digits(time.get(3..5)?)?);
+    if time.as_bytes()[2] != b':' || hours > 23 || minutes > 59 {
+        return None;
+    }
+    let mut rest = &time[5..];
+    if let Some(seconds) = rest.strip_prefix(':') {
+        two_digits(seconds.get(..2)?).filter(|seconds| *seconds <= 59)?;
+        rest = &seconds[2..];
+        if let Some(fraction) = rest.strip_prefix('.') {
+            let digits = fraction.bytes().take_while(u8::is_ascii_digit).count();
+            if digits == 0 {
+                return None;
+            }
+            rest = &fraction[digits..];
+        }
+    }
+    let offset = match rest {
+        "" | "Z" => 0,
+        _ => {
+            let sign = match rest.as_bytes()[0] {
+                b'+' => 1,
+                b'-' => -1,
+                _ => return None,
+            };
+            let offset = &rest[1..];
+            let offset_minutes = match offset.len() {
+                2 => "00",
+                4 => offset.get(2..)?,
+                5 if offset.as_bytes()[2] == b':' => offset.get(3..)?,
+                _ => return None,
+            };
+            let (offset_hours, offset_minutes) = (two_digits(offset.get(..2)?)?, two_digits(offset_minutes)?);
+            if offset_hours > 15 || offset_minutes > 59 {
+                return None;
+            }
+            sign * (offset_hours * 60 + offset_minutes)
+        }
+    };
+    Some(hours * 60 + minutes - offset)
+}
+
+/// Days since 1970-01-01 of a proleptic Gregorian date.
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let day_of_year = (153 * (month + if month > 2 { -3 } else { 9 }) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146097 + day_of_era - 719468
+}
+
+/// The proleptic Gregorian date `days` after 1970-01-01.
+fn civil_from_days(days: i64) -> (i64, i64, i64) {
+    let days = days + 719468;
+    let era = days.div_euclid(146097);
+    let day_of_era = days - era * 146097;
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * shifted_month + 2) / 5 + 1;
+    let month = if shifted_month < 10 { shifted_month + 3 } else { shifted_month - 9 };
+    (year_of_era + era * 400 + i64::from(month <= 2), month, day)
+}
+
+/// Sales of one group in one currency. For brands the revenue is the sum of their items' prices,
+/// otherwise the sum of paid amounts.
+#[derive(Serialize, Deserialize, ToSchema, Debug, Default, Clone)]
+pub struct SalesRow {
+    pub key: String,
+    pub currency: String,
+    pub orders: i64,
+    pub revenue: i64,
+}
+
+#[derive(Serialize, Deserialize, ToSchema, Debug, Clone)]
+pub struct SalesReport {
+    pub dimension: SalesDimension,
+    pub range: DateRange,
+    /// By day in date order, otherwise by revenue, largest first
+    pub rows: Vec<SalesRow>,
+}
@@ -0,0 +1,26 @@
+use crate::domain::models::OrderPage;
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+#[derive(Serialize, Deserialize, ToSchema, Debug, Default, Clone)]
+pub struct CurrencyTotal {
+    pub currency: String,
+    pub amount: i64,
+}
+
+/// Aggregates over all orders of a customer.
+#[derive(Serialize, Deserialize, ToSchema, Debug, Default, Clone)]
+pub struct CustomerTotals {
+    pub order_count: i64,
+    /// Sum of payment amounts for each currency
+    pub spent: Vec<CurrencyTotal>,
+    pub last_order_date: Option<String>,
+}
+
+/// A page of customer's orders, newest first, with totals over all of them.
+#[derive(Serialize, Deserialize, ToSchema, Debug, Default, Clone)]
+pub struct CustomerOrders {
+    pub customer_id: String,
+    pub totals: CustomerTotals,
+    pub page: OrderPage,
+}
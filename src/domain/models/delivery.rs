@@ -1,5 +1,6 @@
 use serde::{Deserialize, Serialize};
-#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+use utoipa::ToSchema;
+#[derive(Serialize, Deserialize, ToSchema, Debug, Default, Clone, PartialEq)]
 pub struct Delivery {
     pub name: String,
     pub phone: String,
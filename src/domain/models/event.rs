@@ -0,0 +1,17 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct OrderEvent {
+    pub id: i64,
+    pub event_type: String,
+    pub order_uid: String,
+    pub created_at: String,
+    pub attempts: i32,
+    pub payload: Value,
+}
+
+impl OrderEvent {
+    pub const ORDER_CREATED: &'static str = "order.created";
+    pub const TYPES: [&'static str; 1] = [Self::ORDER_CREATED];
+}
@@ -0,0 +1,15 @@
+use crate::domain::models::{DateRange, Order, OrderLookup};
+
+/// Which orders an export covers, all of them by default.
+#[derive(Debug, Default, Clone)]
+pub struct OrderFilter {
+    pub lookup: Option<OrderLookup>,
+    /// Days of `date_created`
+    pub range: DateRange,
+}
+
+impl OrderFilter {
+    pub fn matches(&self, order: &Order) -> bool {
+        self.lookup.as_ref().is_none_or(|lookup| lookup.matches(order)) && self.range.contains(&order.date_created)
+    }
+}
@@ -0,0 +1,43 @@
+use crate::domain::models::Order;
+use std::fmt::{Display, Formatter};
+
+/// Secondary keys an order can be found by.
+#[derive(Debug, Clone, PartialEq)]
+pub enum OrderLookup {
+    TrackNumber(String),
+    CustomerId(String),
+    /// Payment transaction id
+    Transaction(String),
+    /// Payment request id
+    RequestId(String),
+    /// Orders containing an item with this `chrt_id`
+    ChrtId(i32),
+    /// Orders containing an item with this `nm_id`
+    NmId(i32),
+}
+
+impl OrderLookup {
+    pub fn matches(&self, order: &Order) -> bool {
+        match self {
+            Self::TrackNumber(track_number) => &order.track_number == track_number,
+            Self::CustomerId(customer_id) => &order.customer_id == customer_id,
+            Self::Transaction(transaction) => &order.payment.transaction == transaction,
+            Self::RequestId(request_id) => &order.payment.request_id == request_id,
+            Self::ChrtId(chrt_id) => order.items.iter().any(|item| item.chrt_id == *chrt_id),
+            Self::NmId(nm_id) => order.items.iter().any(|item| item.nm_id == *nm_id),
+        }
+    }
+}
+
+impl Display for OrderLookup {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::TrackNumber(track_number) => write!(f, "track_number {track_number}"),
+            Self::CustomerId(customer_id) => write!(f, "customer_id {customer_id}"),
+            Self::Transaction(transaction) => write!(f, "transaction {transaction}"),
+            Self::RequestId(request_id) => write!(f, "request_id {request_id}"),
+            Self::ChrtId(chrt_id) => write!(f, "chrt_id {chrt_id}"),
+            Self::NmId(nm_id) => write!(f, "nm_id {nm_id}"),
+        }
+    }
+}
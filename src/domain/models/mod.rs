@@ -2,8 +2,24 @@ mod delivery;
 mod payment;
 mod item;
 mod order;
+mod event;
+mod webhook;
+mod principal;
+mod summary;
+mod lookup;
+mod customer;
+mod analytics;
+mod export;
 
 pub use delivery::Delivery;
 pub use payment::Payment;
 pub use item::Item;
 pub use order::Order;
+pub use event::OrderEvent;
+pub use principal::{Principal, PrincipalKind};
+pub use webhook::{WebhookDelivery, WebhookSubscription, WebhookSubscriptionRequest};
+pub use summary::{OrderSummary, OrderPage};
+pub use lookup::OrderLookup;
+pub use customer::{CurrencyTotal, CustomerOrders, CustomerTotals};
+pub use analytics::{DateRange, SalesDimension, SalesReport, SalesRow};
+pub use export::OrderFilter;
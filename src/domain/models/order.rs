@@ -1,7 +1,8 @@
 use crate::domain::models::{Delivery, Item, Payment};
 use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
 
-#[derive(Deserialize, Serialize, Debug, Default, Clone)]
+#[derive(Deserialize, Serialize, ToSchema, Debug, Default, Clone, PartialEq)]
 pub struct Order {
     pub order_uid: String,
     pub track_number: String,
@@ -1,6 +1,7 @@
 use serde::{Serialize, Deserialize};
+use utoipa::ToSchema;
 
-#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+#[derive(Serialize, Deserialize, ToSchema, Debug, Default, Clone, PartialEq)]
 pub struct Payment {
     pub transaction: String,
     pub request_id: String,
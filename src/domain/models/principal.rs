@@ -0,0 +1,69 @@
+use serde::{Deserialize, Serialize};
+use std::fmt::{Display, Formatter};
+
+/// Who performs a request, passed down to services for auditing.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Principal {
+    pub id: String,
+    pub kind: PrincipalKind,
+    pub scopes: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum PrincipalKind {
+    ApiKey,
+    Jwt,
+    Anonymous,
+    /// Operator running a command on the server itself
+    Local,
+}
+
+impl Principal {
+    pub const ORDERS_READ: &'static str = "orders:read";
+    pub const ORDERS_WRITE: &'static str = "orders:write";
+    pub const ORDERS_ADMIN: &'static str = "orders:admin";
+    pub const ANALYTICS_READ: &'static str = "analytics:read";
+
+    /// Unauthenticated caller, it has no scopes.
+    pub fn anonymous() -> Self {
+        Self {
+            id: "anonymous".to_string(),
+            kind: PrincipalKind::Anonymous,
+            scopes: Vec::new(),
+        }
+    }
+
+    /// Operator of a CLI command, allowed everything.
+    pub fn local() -> Self {
+        Self {
+            id: whoami(),
+            kind: PrincipalKind::Local,
+            scopes: vec![Self::ORDERS_ADMIN.to_string()],
+        }
+    }
+
+    /// `orders:admin` implies every other scope.
+    pub fn has_scope(&self, scope: &str) -> bool {
+        self.scopes
+            .iter()
+            .any(|granted| granted == scope || granted == Self::ORDERS_ADMIN)
+    }
+}
+
+impl Display for Principal {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let kind = match self.kind {
+            PrincipalKind::ApiKey => "api_key",
+            PrincipalKind::Jwt => "jwt",
+            PrincipalKind::Anonymous => "anonymous",
+            PrincipalKind::Local => "local",
+        };
+        write!(f, "{kind}:{}", self.id)
+    }
+}
+
+/// OS user running the process, for the audit log.
+fn whoami() -> String {
+    std::env::var("USER").unwrap_or_else(|_| "cli".to_string())
+}
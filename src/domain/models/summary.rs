@@ -0,0 +1,56 @@
+use crate::domain::models::Order;
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+/// Short description of an order for lists, without its items.
+#[derive(Serialize, Deserialize, ToSchema, Debug, Default, Clone)]
+pub struct OrderSummary {
+    pub order_uid: String,
+    pub track_number: String,
+    pub customer_id: String,
+    /// Recipient name from the delivery
+    pub customer_name: String,
+    pub delivery_service: String,
+    pub date_created: String,
+    pub amount: i32,
+    pub currency: String,
+    pub item_count: i64,
+}
+
+impl From<&Order> for OrderSummary {
+    fn from(order: &Order) -> Self {
+        Self {
+            order_uid: order.order_uid.clone(),
+            track_number: order.track_number.clone(),
+            customer_id: order.customer_id.clone(),
+            customer_name: order.delivery.name.clone(),
+            delivery_service: order.delivery_service.clone(),
+            date_created: order.date_created.clone(),
+            amount: order.payment.amount,
+            currency: order.payment.currency.clone(),
+            item_count: order.items.len() as i64,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, ToSchema, Debug, Default, Clone)]
+pub struct OrderPage {
+    pub orders: Vec<OrderSummary>,
+    pub limit: i64,
+    pub offset: i64,
+    /// Offset of the next page, absent on the last one
+    pub next_offset: Option<i64>,
+}
+
+impl OrderPage {
+    /// Builds a page out of up to `limit + 1` orders, the extra one only tells that there is a next page.
+    pub fn from_overfetched(mut orders: Vec<OrderSummary>, limit: i64, offset: i64) -> Self {
+        let next_offset = if orders.len() as i64 > limit {
+            orders.truncate(limit as usize);
+            Some(offset + limit)
+        } else {
+            None
+        };
+        Self { orders, limit, offset, next_offset }
+    }
+}
@@ -0,0 +1,62 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use utoipa::ToSchema;
+
+#[derive(Serialize, Deserialize, ToSchema, Debug, Clone)]
+pub struct WebhookSubscription {
+    pub id: i64,
+    pub url: String,
+    pub event_types: Vec<String>,
+    pub delivery_service: Option<String>,
+    pub customer_id: Option<String>,
+    #[serde(skip_serializing)]
+    #[schema(ignore)]
+    pub secret: String,
+    pub enabled: bool,
+    pub consecutive_failures: i32,
+    pub created_at: String,
+    pub disabled_at: Option<String>,
+}
+
+/// Body of create and update requests.
+#[derive(Serialize, Deserialize, ToSchema, Debug, Clone)]
+pub struct WebhookSubscriptionRequest {
+    pub url: String,
+    /// Events to deliver, `order.created` is the only one so far
+    pub event_types: Vec<String>,
+    #[serde(default)]
+    pub delivery_service: Option<String>,
+    #[serde(default)]
+    pub customer_id: Option<String>,
+    #[schema(min_length = 16)]
+    pub secret: String,
+    #[serde(default = "enabled_by_default")]
+    pub enabled: bool,
+}
+
+fn enabled_by_default() -> bool {
+    true
+}
+
+#[derive(Serialize, Deserialize, ToSchema, Debug, Clone)]
+pub struct WebhookDelivery {
+    pub id: i64,
+    pub subscription_id: i64,
+    pub event_id: i64,
+    pub event_type: String,
+    pub status: String,
+    pub attempts: i32,
+    pub last_status_code: Option<i32>,
+    pub last_error: Option<String>,
+    pub created_at: String,
+    pub delivered_at: Option<String>,
+    #[serde(skip_serializing)]
+    #[schema(ignore)]
+    pub payload: Value,
+}
+
+impl WebhookDelivery {
+    pub const PENDING: &'static str = "pending";
+    pub const DELIVERED: &'static str = "delivered";
+    pub const FAILED: &'static str = "failed";
+}
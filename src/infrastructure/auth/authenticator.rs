@@ -0,0 +1,135 @@
+use super::jwt::JwtVerifier;
+use crate::domain::{
+    interfaces::{self, Credentials},
+    models::{Principal, PrincipalKind},
+};
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::error::Error;
+use std::path::{Path, PathBuf};
+
+#[derive(Deserialize)]
+pub struct AuthConfig {
+    #[serde(default)]
+    pub api_keys: Vec<ApiKeyConfig>,
+    #[serde(default)]
+    pub jwt: Option<JwtConfig>,
+}
+
+/// Keys are stored as hex-encoded SHA-256 digests, so the config file never contains them.
+#[derive(Deserialize)]
+pub struct ApiKeyConfig {
+    pub name: String,
+    pub sha256: String,
+    pub scopes: Vec<String>,
+}
+
+#[derive(Deserialize)]
+pub struct JwtConfig {
+    pub jwks_path: PathBuf,
+    #[serde(default)]
+    pub issuer: Option<String>,
+    #[serde(default)]
+    pub audience: Option<String>,
+    #[serde(default = "default_leeway")]
+    pub leeway_secs: u64,
+}
+
+fn default_leeway() -> u64 {
+    30
+}
+
+struct ApiKey {
+    name: String,
+    scopes: Vec<String>,
+}
+
+pub struct Authenticator {
+    enabled: bool,
+    api_keys: HashMap<String, ApiKey>,
+    jwt: Option<JwtVerifier>,
+}
+
+impl Authenticator {
+    /// Lets everyone in with every scope, kept for setups without an auth config.
+    pub fn disabled() -> Self {
+        Self {
+            enabled: false,
+            api_keys: HashMap::new(),
+            jwt: None,
+        }
+    }
+
+    pub fn new(config: AuthConfig) -> Result<Self, Box<dyn Error>> {
+        let mut api_keys = HashMap::new();
+        for key in config.api_keys {
+            let digest = key.sha256.to_ascii_lowercase();
+            if digest.len() != 64 || !digest.chars().all(|c| c.is_ascii_hexdigit()) {
+                return Err(format!("API key {} must be a hex-encoded SHA-256 digest", key.name).into());
+            }
+            api_keys.insert(
+                digest,
+                ApiKey {
+                    name: key.name,
+                    scopes: key.scopes,
+                },
+            );
+        }
+        let jwt = match config.jwt {
+            Some(jwt) => Some(JwtVerifier::from_jwks_file(
+                &jwt.jwks_path,
+                jwt.issuer,
+                jwt.audience,
+                jwt.leeway_secs,
+            )?),
+            None => None,
+        };
+        Ok(Self {
+            enabled: true,
+            api_keys,
+            jwt,
+        })
+    }
+
+    /// Reads a JSON config file, see `AuthConfig`.
+    pub fn from_file(path: &Path) -> Result<Self, Box<dyn Error>> {
+        let config: AuthConfig = serde_json::from_str(&std::fs::read_to_string(path)?)?;
+        Self::new(config)
+    }
+
+    pub fn hash_key(key: &str) -> String {
+        hex::encode(Sha256::digest(key.as_bytes()))
+    }
+}
+
+impl interfaces::Authenticator for Authenticator {
+    fn authenticate(&self, credentials: &Credentials) -> Result<Principal, Box<dyn Error + Send + Sync>> {
+        if !self.enabled {
+            return Ok(Principal {
+                scopes: vec![Principal::ORDERS_ADMIN.to_string()],
+                ..Principal::anonymous()
+            });
+        }
+        match credentials {
+            Credentials::None => Ok(Principal::anonymous()),
+            Credentials::ApiKey(key) => {
+                let key = self.api_keys.get(&Self::hash_key(key)).ok_or("Unknown API key")?;
+                Ok(Principal {
+                    id: key.name.clone(),
+                    kind: PrincipalKind::ApiKey,
+                    scopes: key.scopes.clone(),
+                })
+            }
+            Credentials::Bearer(token) => {
+                let verifier = self.jwt.as_ref().ok_or("Bearer tokens are not accepted")?;
+                let (subject, scopes) = verifier.verify(token)?;
+                Ok(Principal {
+                    id: subject,
+                    kind: PrincipalKind::Jwt,
+                    scopes,
+                })
+            }
+        }
+    }
+}
@@ -0,0 +1,114 @@
+use jsonwebtoken::jwk::{AlgorithmParameters, EllipticCurve, Jwk, JwkSet};
+use jsonwebtoken::{decode, decode_header, Algorithm, DecodingKey, Validation};
+use serde::Deserialize;
+use std::error::Error;
+use std::path::Path;
+use std::str::FromStr;
+
+#[derive(Deserialize)]
+struct Claims {
+    sub: String,
+    /// Space separated scopes as in RFC 8693
+    #[serde(default)]
+    scope: Option<String>,
+    #[serde(default)]
+    scp: Option<Vec<String>>,
+}
+
+struct Key {
+    id: Option<String>,
+    key: DecodingKey,
+    /// The only algorithm accepted for tokens signed with the key
+    algorithm: Algorithm,
+}
+
+/// Algorithm of a key: its `alg`, or the usual one for its type when the JWKS doesn't name one.
+fn algorithm(jwk: &Jwk) -> Result<Algorithm, Box<dyn Error>> {
+    if let Some(algorithm) = jwk.common.key_algorithm {
+        return Algorithm::from_str(&algorithm.to_string())
+            .map_err(|_| format!("Key algorithm {algorithm} can't verify signatures").into());
+    }
+    match &jwk.algorithm {
+        AlgorithmParameters::RSA(_) => Ok(Algorithm::RS256),
+        AlgorithmParameters::EllipticCurve(key) => match key.curve {
+            EllipticCurve::P256 => Ok(Algorithm::ES256),
+            EllipticCurve::P384 => Ok(Algorithm::ES384),
+            ref curve => Err(format!("Unsupported curve {curve:?}").into()),
+        },
+        AlgorithmParameters::OctetKeyPair(_) => Ok(Algorithm::EdDSA),
+        AlgorithmParameters::OctetKey(_) => Ok(Algorithm::HS256),
+    }
+}
+
+/// Verifies bearer tokens against keys from a local JWKS file.
+pub(super) struct JwtVerifier {
+    keys: Vec<Key>,
+    issuer: Option<String>,
+    audience: Option<String>,
+    leeway: u64,
+}
+
+impl JwtVerifier {
+    pub(super) fn from_jwks_file(
+        path: &Path,
+        issuer: Option<String>,
+        audience: Option<String>,
+        leeway: u64,
+    ) -> Result<Self, Box<dyn Error>> {
+        let jwks: JwkSet = serde_json::from_str(&std::fs::read_to_string(path)?)?;
+        let keys = jwks
+            .keys
+            .iter()
+            .map(|jwk| {
+                Ok(Key {
+                    id: jwk.common.key_id.clone(),
+                    key: DecodingKey::from_jwk(jwk)?,
+                    algorithm: algorithm(jwk)?,
+                })
+            })
+            .collect::<Result<Vec<_>, Box<dyn Error>>>()?;
+        if keys.is_empty() {
+            return Err(format!("JWKS file {} contains no keys", path.display()).into());
+        }
+        Ok(Self {
+            keys,
+            issuer,
+            audience,
+            leeway,
+        })
+    }
+
+    /// Returns the subject and scopes of a valid token.
+    pub(super) fn verify(&self, token: &str) -> Result<(String, Vec<String>), Box<dyn Error + Send + Sync>> {
+        let header = decode_header(token)?;
+        let key = match &header.kid {
+            Some(kid) => self.keys.iter().find(|key| key.id.as_deref() == Some(kid.as_str())),
+            None if self.keys.len() == 1 => self.keys.first(),
+            None => None,
+        };
+        let key = key.ok_or("No matching key for the token")?;
+        // The header is the token's own claim, the key decides which algorithm is acceptable
+        if header.alg != key.algorithm {
+            return Err(format!("Token algorithm {:?} doesn't match the key's {:?}", header.alg, key.algorithm).into());
+        }
+        let mut validation = Validation::new(key.algorithm);
+        validation.leeway = self.leeway;
+        match &self.issuer {
+            Some(issuer) => validation.set_issuer(&[issuer]),
+            None => validation.iss = None,
+        }
+        match &self.audience {
+            Some(audience) => validation.set_audience(&[audience]),
+            None => validation.validate_aud = false,
+        }
+        let claims = decode::<Claims>(token, &key.key, &validation)?.claims;
+        let mut scopes: Vec<String> = claims
+            .scope
+            .unwrap_or_default()
+            .split_whitespace()
+            .map(str::to_string)
+            .collect();
+        scopes.extend(claims.scp.unwrap_or_default());
+        Ok((claims.sub, scopes))
+    }
+}
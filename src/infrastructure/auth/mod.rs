@@ -0,0 +1,4 @@
+mod authenticator;
+mod jwt;
+
+pub use authenticator::{ApiKeyConfig, AuthConfig, Authenticator, JwtConfig};
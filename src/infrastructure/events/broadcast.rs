@@ -0,0 +1,77 @@
+use crate::domain::{interfaces::OrderNotifier, models::Order};
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use tokio::sync::broadcast;
+
+#[derive(Debug)]
+pub struct StreamedOrder {
+    pub id: u64,
+    pub order: Order,
+}
+
+struct Replay {
+    next_id: u64,
+    buffer: VecDeque<Arc<StreamedOrder>>,
+}
+
+/// Broadcasts accepted orders to live subscribers and keeps the last `capacity` of them,
+/// so that reconnecting clients can resume from the id they saw last.
+pub struct OrderBroadcaster {
+    sender: broadcast::Sender<Arc<StreamedOrder>>,
+    replay: Mutex<Replay>,
+    capacity: usize,
+}
+
+impl OrderBroadcaster {
+    pub fn new(capacity: usize) -> Self {
+        let capacity = capacity.max(1);
+        let (sender, _) = broadcast::channel(capacity);
+        Self {
+            sender,
+            replay: Mutex::new(Replay {
+                next_id: 1,
+                buffer: VecDeque::with_capacity(capacity),
+            }),
+            capacity,
+        }
+    }
+
+    pub fn publish(&self, order: Order) -> u64 {
+        let mut replay = self.replay.lock().unwrap();
+        let streamed = Arc::new(StreamedOrder {
+            id: replay.next_id,
+            order,
+        });
+        replay.next_id += 1;
+        if replay.buffer.len() == self.capacity {
+            replay.buffer.pop_front();
+        }
+        replay.buffer.push_back(streamed.clone());
+        // No receivers is not an error, the order is still kept for replay
+        let _ = self.sender.send(streamed.clone());
+        streamed.id
+    }
+
+    /// Returns buffered orders newer than `last_event_id` together with a receiver for the
+    /// following ones. Without `last_event_id` nothing is replayed.
+    pub fn subscribe(&self, last_event_id: Option<u64>) -> (Vec<Arc<StreamedOrder>>, broadcast::Receiver<Arc<StreamedOrder>>) {
+        let replay = self.replay.lock().unwrap();
+        let receiver = self.sender.subscribe();
+        let missed = match last_event_id {
+            Some(last_id) => replay
+                .buffer
+                .iter()
+                .filter(|streamed| streamed.id > last_id)
+                .cloned()
+                .collect(),
+            None => Vec::new(),
+        };
+        (missed, receiver)
+    }
+}
+
+impl OrderNotifier for OrderBroadcaster {
+    fn order_added(&self, order: &Order) {
+        self.publish(order.clone());
+    }
+}
@@ -0,0 +1,9 @@
+mod broadcast;
+mod relay;
+mod sinks;
+mod webhooks;
+
+pub use broadcast::{OrderBroadcaster, StreamedOrder};
+pub use relay::{OutboxRelay, RelayConfig};
+pub use sinks::{sink_from_uri, FanoutSink, FileSink, HttpSink, NatsSink};
+pub use webhooks::{sign, WebhookConfig, WebhookDispatcher, WebhookWorker};
@@ -0,0 +1,100 @@
+use crate::domain::interfaces::{EventSink, Outbox};
+use log::{log, Level};
+use std::error::Error;
+use std::time::Duration;
+
+pub struct RelayConfig {
+    pub poll_interval: Duration,
+    pub batch_size: i64,
+    pub max_attempts: i32,
+    pub base_backoff: Duration,
+    pub max_backoff: Duration,
+    pub lease: Duration,
+}
+
+impl Default for RelayConfig {
+    fn default() -> Self {
+        Self {
+            poll_interval: Duration::from_secs(1),
+            batch_size: 100,
+            max_attempts: 10,
+            base_backoff: Duration::from_secs(1),
+            max_backoff: Duration::from_secs(300),
+            lease: Duration::from_secs(60),
+        }
+    }
+}
+
+/// Delivers events written to the outbox to a sink. An event is marked as delivered only after
+/// the sink accepted it, so a crash between the two steps results in a redelivery (at-least-once).
+pub struct OutboxRelay<O> {
+    outbox: O,
+    sink: Box<dyn EventSink>,
+    config: RelayConfig,
+}
+
+impl<O> OutboxRelay<O>
+where
+    O: Outbox<Error = Box<dyn Error>>,
+{
+    pub fn new(outbox: O, sink: Box<dyn EventSink>, config: RelayConfig) -> Self {
+        Self {
+            outbox,
+            sink,
+            config,
+        }
+    }
+
+    pub async fn run(self) {
+        loop {
+            match self.run_once().await {
+                Ok(delivered) if delivered as i64 == self.config.batch_size => continue,
+                Ok(_) => {}
+                Err(err) => {
+                    log!(target: "outbox_relay", Level::Error, "Failed to relay outbox events: {err}");
+                }
+            }
+            tokio::time::sleep(self.config.poll_interval).await;
+        }
+    }
+
+    /// Processes one batch of pending events, returns how many of them were claimed.
+    pub async fn run_once(&self) -> Result<usize, String> {
+        let events = self
+            .outbox
+            .claim_pending(self.config.batch_size, self.config.lease)
+            .await
+            .map_err(|err| err.to_string())?;
+        let claimed = events.len();
+        for event in events {
+            let failure = self.sink.publish(&event).await.err().map(|err| err.to_string());
+            let result = match failure {
+                None => {
+                    log!(target: "outbox_relay", Level::Info, "Event {} ({}) delivered", event.id, event.event_type);
+                    self.outbox.mark_delivered(event.id).await
+                }
+                Some(error) => {
+                    let attempt = event.attempts + 1;
+                    let retry_in = (attempt < self.config.max_attempts).then(|| self.backoff(attempt));
+                    match retry_in {
+                        Some(delay) => log!(target: "outbox_relay", Level::Warn,
+                            "Event {} delivery attempt {attempt} failed: {error}, retrying in {delay:?}", event.id),
+                        None => log!(target: "outbox_relay", Level::Error,
+                            "Event {} delivery failed after {attempt} attempts: {error}, giving up", event.id),
+                    }
+                    self.outbox.mark_failed(event.id, &error, retry_in).await
+                }
+            };
+            result.map_err(|err| err.to_string())?;
+        }
+        Ok(claimed)
+    }
+
+    fn backoff(&self, attempt: i32) -> Duration {
+        let factor = 2u32.saturating_pow(attempt.saturating_sub(1) as u32);
+        self.config
+            .base_backoff
+            .saturating_mul(factor)
+            .min(self.config.max_backoff)
+    }
+}
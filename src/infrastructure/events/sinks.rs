@@ -0,0 +1,150 @@
+use crate::domain::{interfaces::EventSink, models::OrderEvent};
+use axum::async_trait;
+use serde_json::json;
+use std::error::Error;
+use std::path::PathBuf;
+use std::time::Duration;
+use tokio::{fs::OpenOptions, io::AsyncWriteExt, sync::Mutex};
+
+fn envelope(event: &OrderEvent) -> serde_json::Value {
+    json!({
+        "id": event.id,
+        "type": event.event_type,
+        "order_uid": event.order_uid,
+        "created_at": event.created_at,
+        "data": event.payload,
+    })
+}
+
+pub struct HttpSink {
+    client: reqwest::Client,
+    url: String,
+}
+
+impl HttpSink {
+    /// Well below the default relay lease, so a hanging receiver can't outlive the claim of its event.
+    pub const TIMEOUT: Duration = Duration::from_secs(10);
+
+    pub fn new(url: String) -> Self {
+        Self::with_timeout(url, Self::TIMEOUT)
+    }
+
+    pub fn with_timeout(url: String, timeout: Duration) -> Self {
+        let client = reqwest::Client::builder()
+            .timeout(timeout)
+            .build()
+            .expect("HTTP client with default TLS settings");
+        Self { client, url }
+    }
+}
+
+#[async_trait]
+impl EventSink for HttpSink {
+    async fn publish(&self, event: &OrderEvent) -> Result<(), Box<dyn Error + Send + Sync>> {
+        self.client
+            .post(&self.url)
+            .header("Idempotency-Key", event.id.to_string())
+            .json(&envelope(event))
+            .send()
+            .await?
+            .error_for_status()?;
+        Ok(())
+    }
+}
+
+pub struct NatsSink {
+    client: async_nats::Client,
+    subject: String,
+}
+
+impl NatsSink {
+    pub async fn connect(server: &str, subject: String) -> Result<Self, Box<dyn Error>> {
+        let client = async_nats::connect(server).await?;
+        Ok(Self { client, subject })
+    }
+}
+
+#[async_trait]
+impl EventSink for NatsSink {
+    async fn publish(&self, event: &OrderEvent) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let payload = serde_json::to_vec(&envelope(event))?;
+        self.client.publish(self.subject.clone(), payload.into()).await?;
+        self.client.flush().await?;
+        Ok(())
+    }
+}
+
+/// Appends events as NDJSON lines, mostly useful for debugging and local development.
+pub struct FileSink {
+    path: PathBuf,
+    lock: Mutex<()>,
+}
+
+impl FileSink {
+    pub fn new(path: PathBuf) -> Self {
+        Self {
+            path,
+            lock: Mutex::new(()),
+        }
+    }
+}
+
+#[async_trait]
+impl EventSink for FileSink {
+    async fn publish(&self, event: &OrderEvent) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let mut line = serde_json::to_vec(&envelope(event))?;
+        line.push(b'\n');
+        let _guard = self.lock.lock().await;
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .await?;
+        file.write_all(&line).await?;
+        file.sync_data().await?;
+        Ok(())
+    }
+}
+
+/// Publishes every event to all of the sinks, failing if any of them fails.
+/// The whole event is retried then, so sinks must tolerate redeliveries.
+pub struct FanoutSink {
+    sinks: Vec<Box<dyn EventSink>>,
+}
+
+impl FanoutSink {
+    pub fn new(sinks: Vec<Box<dyn EventSink>>) -> Self {
+        Self { sinks }
+    }
+}
+
+#[async_trait]
+impl EventSink for FanoutSink {
+    async fn publish(&self, event: &OrderEvent) -> Result<(), Box<dyn Error + Send + Sync>> {
+        for sink in &self.sinks {
+            sink.publish(event).await?;
+        }
+        Ok(())
+    }
+}
+
+/// Builds a sink from URI: `http(s)://host/path` for a webhook, `nats://host:port/subject`
+/// for a NATS subject or `file:///path/to/events.ndjson` for a local file.
+pub async fn sink_from_uri(uri: &str) -> Result<Box<dyn EventSink>, Box<dyn Error>> {
+    let (scheme, rest) = uri
+        .split_once("://")
+        .ok_or_else(|| format!("Invalid sink URI: {uri}"))?;
+    match scheme {
+        "http" | "https" => Ok(Box::new(HttpSink::new(uri.to_string()))),
+        "nats" => {
+            let (server, subject) = rest
+                .split_once('/')
+                .filter(|(_, subject)| !subject.is_empty())
+                .ok_or_else(|| format!("NATS sink URI must contain a subject: {uri}"))?;
+            let sink = NatsSink::connect(&format!("nats://{server}"), subject.replace('/', ".")).await?;
+            Ok(Box::new(sink))
+        }
+        "file" => Ok(Box::new(FileSink::new(PathBuf::from(rest)))),
+        _ => Err(format!("Unsupported sink scheme: {scheme}").into()),
+    }
+}
@@ -0,0 +1,181 @@
+use crate::domain::{
+    interfaces::{EventSink, WebhookStore},
+    models::{OrderEvent, WebhookDelivery, WebhookSubscription},
+};
+use axum::async_trait;
+use hmac::{Hmac, KeyInit, Mac};
+use log::{log, Level};
+use serde_json::json;
+use sha2::Sha256;
+use std::error::Error;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Signature sent in `X-Webhook-Signature`: `sha256=` followed by hex-encoded
+/// HMAC-SHA256 of `{timestamp}.{body}` keyed with the subscription secret.
+pub fn sign(secret: &str, timestamp: u64, body: &[u8]) -> String {
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC accepts keys of any length");
+    mac.update(timestamp.to_string().as_bytes());
+    mac.update(b".");
+    mac.update(body);
+    format!("sha256={}", hex::encode(mac.finalize().into_bytes()))
+}
+
+/// Fans relayed outbox events out into per-subscription deliveries.
+pub struct WebhookDispatcher<W> {
+    store: W,
+}
+
+impl<W> WebhookDispatcher<W> {
+    pub fn new(store: W) -> Self {
+        Self { store }
+    }
+}
+
+#[async_trait]
+impl<W> EventSink for WebhookDispatcher<W>
+where
+    W: WebhookStore<Error = Box<dyn Error>>,
+{
+    async fn publish(&self, event: &OrderEvent) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let result = self.store.enqueue(event).await.map_err(|err| err.to_string())?;
+        if result > 0 {
+            log!(target: "webhook_dispatcher", Level::Info, "Event {} enqueued for {result} webhook(s)", event.id);
+        }
+        Ok(())
+    }
+}
+
+pub struct WebhookConfig {
+    pub poll_interval: Duration,
+    pub batch_size: i64,
+    pub max_attempts: i32,
+    pub disable_after: i32,
+    pub base_backoff: Duration,
+    pub max_backoff: Duration,
+    pub request_timeout: Duration,
+}
+
+impl Default for WebhookConfig {
+    fn default() -> Self {
+        Self {
+            poll_interval: Duration::from_secs(1),
+            batch_size: 50,
+            max_attempts: 8,
+            disable_after: 20,
+            base_backoff: Duration::from_secs(5),
+            max_backoff: Duration::from_secs(3600),
+            request_timeout: Duration::from_secs(10),
+        }
+    }
+}
+
+pub struct WebhookWorker<W> {
+    store: W,
+    client: reqwest::Client,
+    config: WebhookConfig,
+}
+
+impl<W> WebhookWorker<W>
+where
+    W: WebhookStore<Error = Box<dyn Error>>,
+{
+    pub fn new(store: W, config: WebhookConfig) -> Self {
+        let client = reqwest::Client::builder()
+            .timeout(config.request_timeout)
+            .build()
+            .expect("HTTP client with default TLS settings");
+        Self {
+            store,
+            client,
+            config,
+        }
+    }
+
+    pub async fn run(self) {
+        loop {
+            match self.run_once().await {
+                Ok(claimed) if claimed as i64 == self.config.batch_size => continue,
+                Ok(_) => {}
+                Err(err) => {
+                    log!(target: "webhook_worker", Level::Error, "Failed to process webhook deliveries: {err}");
+                }
+            }
+            tokio::time::sleep(self.config.poll_interval).await;
+        }
+    }
+
+    /// Attempts one batch of due deliveries, returns how many of them were claimed.
+    pub async fn run_once(&self) -> Result<usize, String> {
+        let lease = self.config.request_timeout * 2;
+        let claimed = self
+            .store
+            .claim_due(self.config.batch_size, lease)
+            .await
+            .map_err(|err| err.to_string())?;
+        let count = claimed.len();
+        for (delivery, subscription) in claimed {
+            let outcome = self.send(&delivery, &subscription).await;
+            let result = match outcome {
+                Ok(status) => {
+                    log!(target: "webhook_worker", Level::Info,
+                        "Delivery {} to subscription {} succeeded with {status}", delivery.id, subscription.id);
+                    self.store.record_success(&delivery, status).await
+                }
+                Err((status, error)) => {
+                    let attempt = delivery.attempts + 1;
+                    let retry_in = (attempt < self.config.max_attempts).then(|| self.backoff(attempt));
+                    log!(target: "webhook_worker", Level::Warn,
+                        "Delivery {} to subscription {} failed (attempt {attempt}): {error}", delivery.id, subscription.id);
+                    if subscription.consecutive_failures + 1 >= self.config.disable_after {
+                        log!(target: "webhook_worker", Level::Warn,
+                            "Subscription {} keeps failing and will be disabled", subscription.id);
+                    }
+                    self.store
+                        .record_failure(&delivery, status, &error, retry_in, self.config.disable_after)
+                        .await
+                }
+            };
+            result.map_err(|err| err.to_string())?;
+        }
+        Ok(count)
+    }
+
+    async fn send(&self, delivery: &WebhookDelivery, subscription: &WebhookSubscription) -> Result<i32, (Option<i32>, String)> {
+        let body = json!({
+            "id": delivery.event_id,
+            "type": delivery.event_type,
+            "data": delivery.payload,
+        });
+        let body = serde_json::to_vec(&body).map_err(|err| (None, err.to_string()))?;
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+        let response = self
+            .client
+            .post(&subscription.url)
+            .header("Content-Type", "application/json")
+            .header("X-Webhook-Id", delivery.id.to_string())
+            .header("X-Webhook-Event", &delivery.event_type)
+            .header("X-Webhook-Timestamp", timestamp.to_string())
+            .header("X-Webhook-Signature", sign(&subscription.secret, timestamp, &body))
+            .body(body)
+            .send()
+            .await
+            .map_err(|err| (None, err.to_string()))?;
+        let status = response.status();
+        if status.is_success() {
+            Ok(status.as_u16() as i32)
+        } else {
+            Err((Some(status.as_u16() as i32), format!("Endpoint responded with {status}")))
+        }
+    }
+
+    fn backoff(&self, attempt: i32) -> Duration {
+        let factor = 2u32.saturating_pow(attempt.saturating_sub(1) as u32);
+        self.config
+            .base_backoff
+            .saturating_mul(factor)
+            .min(self.config.max_backoff)
+    }
+}
@@ -0,0 +1,149 @@
+use crate::domain::interfaces::OrderStream;
+use crate::domain::models::Order;
+use super::flatten::{columns, rows};
+use super::parquet::ParquetEncoder;
+use futures::stream::{self, BoxStream};
+use futures::{StreamExt, TryStreamExt};
+use serde_json::Value;
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+type SendError = Box<dyn Error + Send + Sync>;
+
+/// Chunks of an export file.
+pub type ExportStream = BoxStream<'static, Result<Vec<u8>, SendError>>;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ExportFormat {
+    /// One JSON order per line
+    Ndjson,
+    /// One row per item, see `columns()`
+    Csv,
+    /// The CSV rows in a columnar file
+    Parquet,
+}
+
+impl ExportFormat {
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            Self::Ndjson => "application/x-ndjson",
+            Self::Csv => "text/csv; charset=utf-8",
+            Self::Parquet => "application/vnd.apache.parquet",
+        }
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Self::Ndjson => "ndjson",
+            Self::Csv => "csv",
+            Self::Parquet => "parquet",
+        }
+    }
+
+    pub fn encoder(&self) -> Box<dyn Encoder> {
+        match self {
+            Self::Ndjson => Box::new(NdjsonEncoder),
+            Self::Csv => Box::new(CsvEncoder),
+            Self::Parquet => Box::new(ParquetEncoder::new()),
+        }
+    }
+}
+
+impl FromStr for ExportFormat {
+    type Err = String;
+
+    fn from_str(format: &str) -> Result<Self, Self::Err> {
+        match format {
+            "ndjson" => Ok(Self::Ndjson),
+            "csv" => Ok(Self::Csv),
+            "parquet" => Ok(Self::Parquet),
+            _ => Err(format!("Unknown export format {format}, expected ndjson, csv or parquet")),
+        }
+    }
+}
+
+impl Display for ExportFormat {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.extension())
+    }
+}
+
+/// Writes orders in some file format, piece by piece.
+pub trait Encoder: Send {
+    /// Bytes preceding the first order
+    fn start(&mut self) -> Result<Vec<u8>, SendError> {
+        Ok(Vec::new())
+    }
+
+    fn order(&mut self, order: &Order) -> Result<Vec<u8>, SendError>;
+
+    /// Bytes following the last order
+    fn finish(&mut self) -> Result<Vec<u8>, SendError> {
+        Ok(Vec::new())
+    }
+}
+
+pub struct NdjsonEncoder;
+
+impl Encoder for NdjsonEncoder {
+    fn order(&mut self, order: &Order) -> Result<Vec<u8>, SendError> {
+        let mut line = serde_json::to_vec(order)?;
+        line.push(b'\n');
+        Ok(line)
+    }
+}
+
+pub struct CsvEncoder;
+
+impl CsvEncoder {
+    fn records(records: impl IntoIterator<Item = Vec<String>>) -> Result<Vec<u8>, SendError> {
+        let mut writer = csv::Writer::from_writer(Vec::new());
+        for record in records {
+            writer.write_record(record)?;
+        }
+        Ok(writer.into_inner().map_err(|err| err.to_string())?)
+    }
+}
+
+impl Encoder for CsvEncoder {
+    fn start(&mut self) -> Result<Vec<u8>, SendError> {
+        Self::records([columns().iter().map(|column| column.name.clone()).collect()])
+    }
+
+    fn order(&mut self, order: &Order) -> Result<Vec<u8>, SendError> {
+        Self::records(rows(order).into_iter().map(|row| {
+            row.into_iter()
+                .map(|value| match value {
+                    Value::String(text) => text,
+                    Value::Null => String::new(),
+                    value => value.to_string(),
+                })
+                .collect()
+        }))
+    }
+}
+
+enum Stage {
+    Start,
+    Orders,
+}
+
+/// Encodes the orders as they come. The stream ends after the first error.
+pub fn encode(orders: OrderStream, encoder: Box<dyn Encoder>) -> ExportStream {
+    stream::unfold(Some((Stage::Start, orders, encoder)), |state| async move {
+        let (stage, mut orders, mut encoder) = state?;
+        let chunk = match stage {
+            Stage::Start => encoder.start(),
+            Stage::Orders => match orders.next().await {
+                Some(Ok(order)) => encoder.order(&order),
+                Some(Err(err)) => Err(err),
+                None => return Some((encoder.finish(), None)),
+            },
+        };
+        let next = chunk.is_ok().then_some((Stage::Orders, orders, encoder));
+        Some((chunk, next))
+    })
+    .try_filter(|chunk| futures::future::ready(!chunk.is_empty()))
+    .boxed()
+}
@@ -0,0 +1,101 @@
+use crate::domain::models::{Item, Order};
+use serde_json::{Map, Value};
+use std::sync::OnceLock;
+
+/// Type of a column's values.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ColumnKind {
+    Text,
+    Integer,
+}
+
+#[derive(Debug, Clone)]
+pub struct Column {
+    pub name: String,
+    pub kind: ColumnKind,
+    source: Source,
+}
+
+#[derive(Debug, Clone)]
+enum Source {
+    /// Path to a field of the order
+    Order(Vec<String>),
+    /// Field of an element of the order's item list
+    Item(String),
+}
+
+/// Name of the order's field holding the list exploded into rows
+const ROWS_FIELD: &str = "items";
+const ROW_PREFIX: &str = "item";
+
+fn kind(value: &Value) -> ColumnKind {
+    match value {
+        Value::Number(_) => ColumnKind::Integer,
+        _ => ColumnKind::Text,
+    }
+}
+
+fn collect(object: &Map<String, Value>, path: Vec<String>, scalars: &mut Vec<Column>, nested: &mut Vec<Column>) {
+    for (key, value) in object {
+        let mut path = path.clone();
+        path.push(key.clone());
+        match value {
+            Value::Object(fields) => collect(fields, path, nested, &mut Vec::new()),
+            Value::Array(_) => {}
+            _ => scalars.push(Column { name: path.join("_"), kind: kind(value), source: Source::Order(path) }),
+        }
+    }
+}
+
+fn derive_columns() -> Vec<Column> {
+    let template = Order {
+        items: vec![Item::default()],
+        ..Default::default()
+    };
+    let Value::Object(order) = serde_json::to_value(template).unwrap() else {
+        unreachable!("orders serialize to objects")
+    };
+    let mut columns = Vec::new();
+    let mut nested = Vec::new();
+    collect(&order, Vec::new(), &mut columns, &mut nested);
+    columns.append(&mut nested);
+    if let Some(Value::Object(item)) = order[ROWS_FIELD].get(0) {
+        columns.extend(item.iter().map(|(key, value)| Column {
+            name: format!("{ROW_PREFIX}_{key}"),
+            kind: kind(value),
+            source: Source::Item(key.clone()),
+        }));
+    }
+    columns
+}
+
+/// Columns of an order flattened to one row per item: the order's own fields, then those of nested objects
+/// prefixed with their name (`delivery_name`, `payment_amount`) and item fields prefixed with `item_`,
+/// each group sorted by name. They are derived from the model, so new fields show up as new columns.
+pub fn columns() -> &'static [Column] {
+    static COLUMNS: OnceLock<Vec<Column>> = OnceLock::new();
+    COLUMNS.get_or_init(derive_columns)
+}
+
+/// Rows of an order in `columns()` order, one per item. An order without items still gets a row,
+/// with nulls in the item columns.
+pub fn rows(order: &Order) -> Vec<Vec<Value>> {
+    let order = serde_json::to_value(order).unwrap_or_default();
+    let empty = vec![Value::Null];
+    let items = match order[ROWS_FIELD].as_array() {
+        Some(items) if !items.is_empty() => items,
+        _ => &empty,
+    };
+    items
+        .iter()
+        .map(|item| {
+            columns()
+                .iter()
+                .map(|column| match &column.source {
+                    Source::Order(path) => path.iter().fold(&order, |value, key| &value[key]).clone(),
+                    Source::Item(key) => item[key].clone(),
+                })
+                .collect()
+        })
+        .collect()
+}
@@ -0,0 +1,7 @@
+mod encoder;
+mod flatten;
+mod parquet;
+
+pub use encoder::{encode, CsvEncoder, Encoder, ExportFormat, ExportStream, NdjsonEncoder};
+pub use flatten::{columns, rows, Column, ColumnKind};
+pub use parquet::ParquetEncoder;
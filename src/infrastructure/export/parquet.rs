@@ -0,0 +1,112 @@
+use crate::domain::models::Order;
+use super::encoder::Encoder;
+use super::flatten::{columns, rows, ColumnKind};
+use parquet::basic::{Compression, LogicalType, Repetition, Type as PhysicalType};
+use parquet::data_type::{ByteArray, ByteArrayType, Int64Type};
+use parquet::file::properties::WriterProperties;
+use parquet::file::writer::SerializedFileWriter;
+use parquet::schema::types::Type;
+use serde_json::Value;
+use std::error::Error;
+use std::sync::Arc;
+
+type SendError = Box<dyn Error + Send + Sync>;
+
+/// Rows buffered before they are written out as a row group
+const ROW_GROUP_SIZE: usize = 10_000;
+
+/// Parquet file with the CSV columns, every column is optional since orders without items have no item fields.
+/// Rows are written in row groups, so only one group is kept in memory, the footer comes last.
+pub struct ParquetEncoder {
+    writer: Option<SerializedFileWriter<Vec<u8>>>,
+    rows: Vec<Vec<Value>>,
+}
+
+impl ParquetEncoder {
+    pub fn new() -> Self {
+        Self {
+            writer: None,
+            rows: Vec::new(),
+        }
+    }
+
+    fn schema() -> Result<Type, SendError> {
+        let mut fields = Vec::new();
+        for column in columns() {
+            let field = match column.kind {
+                ColumnKind::Text => Type::primitive_type_builder(&column.name, PhysicalType::BYTE_ARRAY)
+                    .with_logical_type(Some(LogicalType::String)),
+                ColumnKind::Integer => Type::primitive_type_builder(&column.name, PhysicalType::INT64),
+            };
+            fields.push(Arc::new(field.with_repetition(Repetition::OPTIONAL).build()?));
+        }
+        Ok(Type::group_type_builder("order").with_fields(fields).build()?)
+    }
+
+    fn writer(&mut self) -> Result<&mut SerializedFileWriter<Vec<u8>>, SendError> {
+        if self.writer.is_none() {
+            let properties = WriterProperties::builder().set_compression(Compression::SNAPPY).build();
+            self.writer = Some(SerializedFileWriter::new(Vec::new(), Arc::new(Self::schema()?), Arc::new(properties))?);
+        }
+        Ok(self.writer.as_mut().unwrap())
+    }
+
+    /// Writes the buffered rows as a row group and takes the bytes written so far.
+    fn write_row_group(&mut self) -> Result<Vec<u8>, SendError> {
+        let rows = std::mem::take(&mut self.rows);
+        let writer = self.writer()?;
+        if !rows.is_empty() {
+            let mut row_group = writer.next_row_group()?;
+            let mut index = 0;
+            while let Some(mut column) = row_group.next_column()? {
+                let values = rows.iter().map(|row| &row[index]);
+                let levels: Vec<i16> = values.clone().map(|value| i16::from(!value.is_null())).collect();
+                match columns()[index].kind {
+                    ColumnKind::Text => {
+                        let values: Vec<ByteArray> = values
+                            .filter(|value| !value.is_null())
+                            .map(|value| match value {
+                                Value::String(text) => ByteArray::from(text.as_str()),
+                                value => ByteArray::from(value.to_string().as_str()),
+                            })
+                            .collect();
+                        column.typed::<ByteArrayType>().write_batch(&values, Some(&levels), None)?;
+                    }
+                    ColumnKind::Integer => {
+                        let values: Vec<i64> = values.filter_map(Value::as_i64).collect();
+                        column.typed::<Int64Type>().write_batch(&values, Some(&levels), None)?;
+                    }
+                }
+                column.close()?;
+                index += 1;
+            }
+            row_group.close()?;
+        }
+        writer.flush()?;
+        Ok(std::mem::take(writer.inner_mut()))
+    }
+}
+
+impl Default for ParquetEncoder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Encoder for ParquetEncoder {
+    fn order(&mut self, order: &Order) -> Result<Vec<u8>, SendError> {
+        self.rows.extend(rows(order));
+        if self.rows.len() < ROW_GROUP_SIZE {
+            return Ok(Vec::new());
+        }
+        self.write_row_group()
+    }
+
+    fn finish(&mut self) -> Result<Vec<u8>, SendError> {
+        let mut bytes = self.write_row_group()?;
+        if let Some(writer) = self.writer.take() {
+            bytes.append(&mut writer.into_inner()?);
+        }
+        Ok(bytes)
+    }
+}
@@ -0,0 +1,100 @@
+use log::{log, Level};
+use std::error::Error;
+use std::fmt;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+#[derive(Debug, Clone, Copy)]
+pub struct BreakerConfig {
+    /// Failures in a row that open the circuit
+    pub failure_threshold: u32,
+    /// How long calls are failed right away before a trial call is let through
+    pub open_for: Duration,
+}
+
+impl Default for BreakerConfig {
+    fn default() -> Self {
+        Self {
+            failure_threshold: 5,
+            open_for: Duration::from_secs(10),
+        }
+    }
+}
+
+enum State {
+    Closed { failures: u32 },
+    Open { until: Instant },
+    /// A trial call is in flight, another one is let through if it never reports back
+    HalfOpen { since: Instant },
+}
+
+/// Returned instead of calling a dependency the breaker considers down.
+#[derive(Debug)]
+pub struct CircuitOpen {
+    pub retry_in: Duration,
+}
+
+impl fmt::Display for CircuitOpen {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Circuit is open, retry in {:?}", self.retry_in)
+    }
+}
+
+impl Error for CircuitOpen {}
+
+/// Fails calls right away while the dependency keeps failing. Once the circuit has been open
+/// for a while a single trial call goes through, its success closes the circuit.
+pub struct CircuitBreaker {
+    config: BreakerConfig,
+    state: Mutex<State>,
+}
+
+impl CircuitBreaker {
+    pub fn new(config: BreakerConfig) -> Self {
+        Self {
+            config,
+            state: Mutex::new(State::Closed { failures: 0 }),
+        }
+    }
+
+    /// Whether a call may be made now.
+    pub fn check(&self) -> Result<(), CircuitOpen> {
+        let now = Instant::now();
+        let mut state = self.state.lock().unwrap();
+        match *state {
+            State::Closed { .. } => Ok(()),
+            State::Open { until } if now < until => Err(CircuitOpen { retry_in: until - now }),
+            State::HalfOpen { since } if now < since + self.config.open_for => {
+                Err(CircuitOpen { retry_in: since + self.config.open_for - now })
+            }
+            State::Open { .. } | State::HalfOpen { .. } => {
+                *state = State::HalfOpen { since: now };
+                Ok(())
+            }
+        }
+    }
+
+    pub fn success(&self) {
+        let mut state = self.state.lock().unwrap();
+        if !matches!(*state, State::Closed { .. }) {
+            log!(target: "circuit_breaker", Level::Info, "Circuit closed");
+        }
+        *state = State::Closed { failures: 0 };
+    }
+
+    pub fn failure(&self) {
+        let mut state = self.state.lock().unwrap();
+        let failures = match *state {
+            State::Closed { failures } => failures + 1,
+            State::Open { .. } | State::HalfOpen { .. } => self.config.failure_threshold,
+        };
+        if failures < self.config.failure_threshold {
+            *state = State::Closed { failures };
+            return;
+        }
+        if !matches!(*state, State::Open { .. }) {
+            log!(target: "circuit_breaker", Level::Warn, "Circuit opened for {:?} after {failures} failures", self.config.open_for);
+        }
+        *state = State::Open { until: Instant::now() + self.config.open_for };
+    }
+}
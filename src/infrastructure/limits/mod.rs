@@ -0,0 +1,5 @@
+mod rate_limiter;
+mod circuit_breaker;
+
+pub use rate_limiter::{Quota, RateLimiter};
+pub use circuit_breaker::{BreakerConfig, CircuitBreaker, CircuitOpen};
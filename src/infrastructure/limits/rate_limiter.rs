@@ -0,0 +1,68 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+#[derive(Debug, Clone, Copy)]
+pub struct Quota {
+    pub per_second: f64,
+    pub burst: u32,
+}
+
+struct Bucket {
+    tokens: f64,
+    updated: Instant,
+}
+
+/// Token bucket per key (API key, token subject or client IP).
+pub struct RateLimiter {
+    quota: Quota,
+    buckets: Mutex<HashMap<String, Bucket>>,
+    max_idle: Duration,
+}
+
+impl RateLimiter {
+    const CLEANUP_THRESHOLD: usize = 10_000;
+
+    pub fn new(quota: Quota) -> Self {
+        let burst = quota.burst.max(1);
+        let per_second = quota.per_second.max(f64::MIN_POSITIVE);
+        Self {
+            quota: Quota { per_second, burst },
+            buckets: Mutex::new(HashMap::new()),
+            // After that long a bucket is full again and is the same as a missing one
+            max_idle: Duration::from_secs_f64((burst as f64 / per_second).min(86400.0)),
+        }
+    }
+
+    pub fn quota(&self) -> Quota {
+        self.quota
+    }
+
+    /// Takes a token for `key`, otherwise returns how long to wait for the next one.
+    pub fn check(&self, key: &str) -> Result<(), Duration> {
+        self.check_at(key, Instant::now())
+    }
+
+    fn check_at(&self, key: &str, now: Instant) -> Result<(), Duration> {
+        let mut buckets = self.buckets.lock().unwrap();
+        if buckets.len() >= Self::CLEANUP_THRESHOLD && !buckets.contains_key(key) {
+            buckets.retain(|_, bucket| now.saturating_duration_since(bucket.updated) < self.max_idle);
+        }
+        let burst = self.quota.burst as f64;
+        let bucket = buckets.entry(key.to_string()).or_insert(Bucket {
+            tokens: burst,
+            updated: now,
+        });
+        let elapsed = now.saturating_duration_since(bucket.updated).as_secs_f64();
+        bucket.tokens = (bucket.tokens + elapsed * self.quota.per_second).min(burst);
+        bucket.updated = now;
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            Ok(())
+        } else {
+            // A rate too small for a Duration waits until the bucket would be forgotten anyway
+            let wait = Duration::try_from_secs_f64((1.0 - bucket.tokens) / self.quota.per_second);
+            Err(wait.map_or(self.max_idle, |wait| wait.min(self.max_idle)))
+        }
+    }
+}
@@ -1,5 +1,13 @@
 mod storage;
 mod services;
+mod events;
+mod auth;
+mod limits;
+mod export;
 
 pub use storage::*;
-pub use services::*;
\ No newline at end of file
+pub use services::*;
+pub use events::*;
+pub use auth::*;
+pub use limits::*;
+pub use export::*;
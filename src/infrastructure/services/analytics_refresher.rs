@@ -0,0 +1,31 @@
+use crate::domain::interfaces::SalesAnalytics;
+use log::{log, Level};
+use std::error::Error;
+use std::time::Duration;
+
+/// Periodically recomputes sales aggregates, reports are as fresh as the last refresh.
+pub struct AnalyticsRefresher<A> {
+    analytics: A,
+    interval: Duration,
+}
+
+impl<A> AnalyticsRefresher<A>
+where
+    A: SalesAnalytics<Error = Box<dyn Error>>,
+{
+    pub fn new(analytics: A, interval: Duration) -> Self {
+        Self { analytics, interval }
+    }
+
+    pub async fn run(self) {
+        let mut ticks = tokio::time::interval(self.interval);
+        ticks.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        loop {
+            ticks.tick().await;
+            match self.analytics.refresh().await {
+                Ok(()) => log!(target: "analytics_refresher", Level::Debug, "Sales aggregates refreshed"),
+                Err(err) => log!(target: "analytics_refresher", Level::Error, "Failed to refresh sales aggregates: {err}"),
+            }
+        }
+    }
+}
@@ -0,0 +1,73 @@
+use crate::domain::interfaces::{self, Database};
+use crate::infrastructure::Cache;
+use log::{log, Level};
+use std::collections::HashSet;
+use std::error::Error;
+use std::path::PathBuf;
+use std::time::Duration;
+
+/// Orders checked against the database in one read
+const VALIDATION_BATCH: usize = 500;
+
+/// Checks orders restored from a cache snapshot against the database, then saves a snapshot periodically.
+/// Restored orders are served while they are checked, the ones removed from the database meanwhile are evicted.
+pub struct CacheSnapshotter<D> {
+    cache: Cache,
+    database: D,
+    path: PathBuf,
+    interval: Duration,
+}
+
+impl<D> CacheSnapshotter<D>
+where
+    D: Database<Error = Box<dyn Error>>,
+{
+    pub fn new(cache: Cache, database: D, path: PathBuf, interval: Duration) -> Self {
+        Self { cache, database, path, interval }
+    }
+
+    pub async fn run(self) {
+        let order_uids = self.cache.order_uids().await;
+        for batch in order_uids.chunks(VALIDATION_BATCH) {
+            while !self.validate_logged(batch).await {
+                tokio::time::sleep(self.interval).await;
+            }
+        }
+        let mut ticks = tokio::time::interval_at(tokio::time::Instant::now() + self.interval, self.interval);
+        ticks.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        loop {
+            ticks.tick().await;
+            match self.cache.save_snapshot(&self.path).await {
+                Ok(count) => log!(target: "cache_snapshotter", Level::Debug, "Saved {count} orders to {}", self.path.display()),
+                Err(err) => log!(target: "cache_snapshotter", Level::Error, "Failed to save the cache snapshot: {err}"),
+            }
+        }
+    }
+
+    async fn validate_logged(&self, order_uids: &[String]) -> bool {
+        match self.validate(order_uids).await {
+            Ok(0) => true,
+            Ok(evicted) => {
+                log!(target: "cache_snapshotter", Level::Info, "Evicted {evicted} removed orders restored from the snapshot");
+                true
+            }
+            Err(err) => {
+                log!(target: "cache_snapshotter", Level::Error, "Failed to validate restored orders: {err}");
+                false
+            }
+        }
+    }
+
+    /// Evicts the orders of the batch the database no longer has, returns how many.
+    /// Orders never change once stored, so the ones still there need no update.
+    pub async fn validate(&self, order_uids: &[String]) -> Result<usize, Box<dyn Error>> {
+        let stored: HashSet<String> = self.database.existing(order_uids).await?.into_iter().collect();
+        let mut evicted = 0;
+        for order_uid in order_uids.iter().filter(|order_uid| !stored.contains(*order_uid)) {
+            if interfaces::Cache::remove(&self.cache, order_uid).await.is_some() {
+                evicted += 1;
+            }
+        }
+        Ok(evicted)
+    }
+}
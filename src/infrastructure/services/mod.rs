@@ -1,3 +1,9 @@
 mod order_service;
+mod analytics_refresher;
+mod replica_monitor;
+mod cache_snapshotter;
 
-pub use order_service::OrderService;
\ No newline at end of file
+pub use order_service::OrderService;
+pub use analytics_refresher::AnalyticsRefresher;
+pub use replica_monitor::ReplicaMonitor;
+pub use cache_snapshotter::CacheSnapshotter;
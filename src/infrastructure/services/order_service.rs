@@ -1,10 +1,25 @@
 use crate::domain::interfaces;
-use crate::domain::models::Order;
+use crate::domain::models::{CustomerOrders, Order, OrderLookup, OrderSummary, Principal};
 use axum::async_trait;
 use log::{log, Level};
 use std::error::Error;
+use std::sync::Arc;
 
-pub struct OrderService;
+#[derive(Default)]
+pub struct OrderService {
+    notifier: Option<Arc<dyn interfaces::OrderNotifier>>,
+}
+
+impl OrderService {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_notifier(mut self, notifier: Arc<dyn interfaces::OrderNotifier>) -> Self {
+        self.notifier = Some(notifier);
+        self
+    }
+}
 
 type Repository = dyn interfaces::Repository<Error=Box<dyn Error>>;
 
@@ -14,14 +29,20 @@ impl interfaces::OrderService for OrderService {
         &self,
         repository: &Repository,
         order: Order,
+        principal: &Principal,
     ) -> Result<(), Box<dyn Error>> {
         let order_uid = order.order_uid.clone();
+        let notified = self.notifier.as_ref().map(|_| order.clone());
         let result = repository.insert(order).await;
         if let Err(err) = result {
             log!(target: "add_order_service", Level::Error, "Insertion failed: err: {err}");
             Err(err)
         } else {
             log!(target: "add_order_service", Level::Info, "Order with order_uid: {order_uid} successfully added");
+            log!(target: "audit", Level::Info, "{principal} added order {order_uid}");
+            if let (Some(notifier), Some(order)) = (&self.notifier, notified) {
+                notifier.order_added(&order);
+            }
             Ok(())
         }
     }
@@ -30,7 +51,9 @@ impl interfaces::OrderService for OrderService {
         &self,
         order_uid: &str,
         repository: &Repository,
+        principal: &Principal,
     ) -> Result<Option<Order>, Box<dyn Error>> {
+        log!(target: "audit", Level::Info, "{principal} requested order {order_uid}");
         let result = repository.get_and_cache(order_uid).await;
         match result {
             Ok(Some(order)) => {
@@ -47,4 +70,60 @@ impl interfaces::OrderService for OrderService {
             }
         }
     }
+
+    async fn get_orders(
+        &self,
+        order_uids: &[String],
+        repository: &Repository,
+        principal: &Principal,
+    ) -> Result<Vec<Order>, Box<dyn Error>> {
+        log!(target: "audit", Level::Info, "{principal} requested orders {order_uids:?}");
+        let result = repository.get_many_and_cache(order_uids).await;
+        match &result {
+            Ok(orders) => {
+                log!(target: "get_order_service", Level::Info, "Found {} of {} orders", orders.len(), order_uids.len());
+            }
+            Err(err) => {
+                log!(target: "get_order_service", Level::Error, "Failed to get orders {order_uids:?}, error: {err}");
+            }
+        }
+        result
+    }
+
+    async fn find_orders(
+        &self,
+        lookup: &OrderLookup,
+        limit: i64,
+        offset: i64,
+        repository: &Repository,
+        principal: &Principal,
+    ) -> Result<Vec<OrderSummary>, Box<dyn Error>> {
+        log!(target: "audit", Level::Info, "{principal} looked up orders by {lookup}");
+        let result = repository.find(lookup, limit, offset).await;
+        match &result {
+            Ok(orders) => {
+                log!(target: "find_orders_service", Level::Info, "Found {} orders by {lookup}", orders.len());
+            }
+            Err(err) => {
+                log!(target: "find_orders_service", Level::Error, "Failed to look up orders by {lookup}, error: {err}");
+            }
+        }
+        result
+    }
+
+    async fn customer_orders(
+        &self,
+        customer_id: &str,
+        limit: i64,
+        offset: i64,
+        repository: &Repository,
+        principal: &Principal,
+    ) -> Result<CustomerOrders, Box<dyn Error>> {
+        log!(target: "audit", Level::Info, "{principal} requested orders of customer {customer_id}");
+        let result = repository.customer_orders(customer_id, limit, offset).await;
+        if let Err(err) = &result {
+            log!(target: "customer_orders_service", Level::Error, "Failed to get orders of customer {customer_id}, error: {err}");
+        }
+        result
+    }
 }
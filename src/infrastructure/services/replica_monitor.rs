@@ -0,0 +1,27 @@
+use crate::infrastructure::Database;
+use log::{log, Level};
+use std::time::Duration;
+
+/// Periodically measures the read replica's lag, reads go to the primary while it lags or is unreachable.
+pub struct ReplicaMonitor {
+    database: Database,
+    interval: Duration,
+}
+
+impl ReplicaMonitor {
+    pub fn new(database: Database, interval: Duration) -> Self {
+        Self { database, interval }
+    }
+
+    pub async fn run(self) {
+        let mut ticks = tokio::time::interval(self.interval);
+        ticks.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        loop {
+            ticks.tick().await;
+            match self.database.check_replica().await {
+                Ok(lag) => log!(target: "replica_monitor", Level::Debug, "Replica lag: {lag:?}"),
+                Err(err) => log!(target: "replica_monitor", Level::Error, "Failed to check the replica: {err}"),
+            }
+        }
+    }
+}
@@ -0,0 +1,70 @@
+use crate::domain::interfaces;
+use crate::domain::models::{DateRange, SalesDimension, SalesRow};
+use crate::infrastructure::Database;
+use axum::async_trait;
+use std::error::Error;
+
+/// Materialized view and its column holding the group of a dimension.
+fn source(dimension: SalesDimension) -> (&'static str, &'static str) {
+    match dimension {
+        SalesDimension::Day => ("SalesDaily", "day::TEXT"),
+        SalesDimension::Brand => ("BrandSalesDaily", "brand"),
+        SalesDimension::DeliveryService => ("SalesDaily", "delivery_service"),
+        SalesDimension::Region => ("SalesDaily", "region"),
+        SalesDimension::Provider => ("SalesDaily", "provider"),
+        SalesDimension::Bank => ("SalesDaily", "bank"),
+        SalesDimension::Currency => ("SalesDaily", "currency"),
+    }
+}
+
+#[async_trait]
+impl interfaces::SalesAnalytics for Database {
+    type Error = Box<dyn Error>;
+
+    async fn sales(&self, dimension: SalesDimension, range: &DateRange) -> Result<Vec<SalesRow>, Self::Error> {
+        let (view, key) = source(dimension);
+        let order = match dimension {
+            SalesDimension::Day => "key, currency",
+            _ => "revenue DESC, key, currency",
+        };
+        let query = format!(
+            "SELECT {key} AS key, currency, sum(orders)::BIGINT AS orders, sum(revenue)::BIGINT AS revenue
+             FROM {view}
+             WHERE ($1::TEXT IS NULL OR day >= $1::TEXT::DATE) AND ($2::TEXT IS NULL OR day <= $2::TEXT::DATE)
+             GROUP BY 1, 2
+             ORDER BY {order}"
+        );
+        let rows = self
+            .resilient("sales", || async {
+                let mut instance = self.reader(&[]).await?;
+                let transaction = instance.build_transaction().read_only(true).start().await?;
+                Self::apply_deadline(&transaction).await?;
+                let rows = transaction.query(&query, &[&range.from, &range.to]).await?;
+                transaction.commit().await?;
+                Ok(rows)
+            })
+            .await?;
+        let rows = rows
+            .iter()
+            .map(|row| SalesRow {
+                key: row.get("key"),
+                currency: row.get("currency"),
+                orders: row.get("orders"),
+                revenue: row.get("revenue"),
+            })
+            .collect();
+        Ok(rows)
+    }
+
+    async fn refresh(&self) -> Result<(), Self::Error> {
+        self.pool
+            .get()
+            .await?
+            .batch_execute(
+                "REFRESH MATERIALIZED VIEW CONCURRENTLY SalesDaily;
+                 REFRESH MATERIALIZED VIEW CONCURRENTLY BrandSalesDaily;",
+            )
+            .await?;
+        Ok(())
+    }
+}
@@ -1,20 +1,63 @@
 //Consider use Redis, but there I guess we can use HashMap shamelessly
 
 use std::collections::HashMap;
+use std::error::Error;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
 use tokio::sync::RwLock;
 use axum::{async_trait, Json};
 use crate::domain::{models::Order, interfaces};
+use super::snapshot;
 
+/// Clones share the same entries.
+#[derive(Clone)]
 pub struct Cache {
-    memory: RwLock<HashMap<String, Json<Order>>>,
+    memory: Arc<RwLock<HashMap<String, Json<Order>>>>,
 }
 
 impl Cache {
     pub fn new() -> Cache {
         Cache {
-            memory: RwLock::new(HashMap::new()),
+            memory: Arc::new(RwLock::new(HashMap::new())),
         }
     }
+
+    /// Cache holding the orders of a snapshot, they may have been removed from the database since it was saved.
+    pub async fn load_snapshot(path: &Path) -> Result<Cache, Box<dyn Error>> {
+        let bytes = tokio::fs::read(path).await?;
+        let orders = tokio::task::spawn_blocking(move || snapshot::decode(&bytes)).await??;
+        let memory = orders
+            .into_iter()
+            .map(|order| (order.order_uid.clone(), Json(order)))
+            .collect();
+        Ok(Cache {
+            memory: Arc::new(RwLock::new(memory)),
+        })
+    }
+
+    /// Writes all cached orders to `path`, replacing the previous snapshot only once the new one is complete.
+    /// Returns how many orders were written.
+    pub async fn save_snapshot(&self, path: &Path) -> Result<usize, Box<dyn Error>> {
+        let orders: Vec<Order> = self.memory.read().await.values().map(|order| order.0.clone()).collect();
+        let count = orders.len();
+        let bytes = tokio::task::spawn_blocking(move || snapshot::encode(&orders).map_err(|err| err.to_string()))
+            .await??;
+        let mut partial = PathBuf::from(path).into_os_string();
+        partial.push(".partial");
+        tokio::fs::write(&partial, bytes).await?;
+        tokio::fs::rename(&partial, path).await?;
+        Ok(count)
+    }
+
+    pub async fn order_uids(&self) -> Vec<String> {
+        self.memory.read().await.keys().cloned().collect()
+    }
+}
+
+impl Default for Cache {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 #[async_trait]
@@ -30,4 +73,4 @@ impl interfaces::Cache for Cache {
     async fn remove(&self, order_id: &str) -> Option<Order> {
         Some(self.memory.write().await.remove(order_id)?.0)
     }
-}
\ No newline at end of file
+}
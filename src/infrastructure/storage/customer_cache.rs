@@ -0,0 +1,50 @@
+use crate::domain::models::CustomerOrders;
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+use tokio::sync::RwLock;
+
+/// Cached pages of one customer by `(limit, offset)`
+type Pages = HashMap<(i64, i64), (Instant, CustomerOrders)>;
+
+/// Pages of customer histories keyed by customer and `(limit, offset)`.
+/// Entries expire so that orders added by other instances show up eventually.
+pub(super) struct CustomerCache {
+    ttl: Duration,
+    pages: RwLock<HashMap<String, Pages>>,
+}
+
+impl CustomerCache {
+    /// Expired entries are swept once this many customers are cached
+    const SWEEP_AT: usize = 1024;
+
+    pub(super) fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            pages: RwLock::new(HashMap::new()),
+        }
+    }
+
+    pub(super) async fn get(&self, customer_id: &str, limit: i64, offset: i64) -> Option<CustomerOrders> {
+        let pages = self.pages.read().await;
+        let (cached_at, orders) = pages.get(customer_id)?.get(&(limit, offset))?;
+        (cached_at.elapsed() < self.ttl).then(|| orders.clone())
+    }
+
+    pub(super) async fn add(&self, limit: i64, offset: i64, orders: CustomerOrders) {
+        let mut pages = self.pages.write().await;
+        if pages.len() >= Self::SWEEP_AT {
+            pages.retain(|_, customer| {
+                customer.retain(|_, (cached_at, _)| cached_at.elapsed() < self.ttl);
+                !customer.is_empty()
+            });
+        }
+        pages
+            .entry(orders.customer_id.clone())
+            .or_default()
+            .insert((limit, offset), (Instant::now(), orders));
+    }
+
+    pub(super) async fn invalidate(&self, customer_id: &str) {
+        self.pages.write().await.remove(customer_id);
+    }
+}
@@ -1,10 +1,21 @@
 use crate::domain::interfaces;
-use crate::domain::models::{Delivery, Item, Order, Payment};
-use crate::infrastructure::MultiError;
+use crate::domain::models::{
+    CurrencyTotal, CustomerTotals, Delivery, Item, Order, OrderEvent, OrderLookup, OrderSummary, Payment,
+};
+use crate::infrastructure::limits::{BreakerConfig, CircuitBreaker};
+use crate::infrastructure::{deadline, DeadlineExceeded, MultiError};
 use axum::async_trait;
-use deadpool_postgres::{Manager, Pool, Runtime, Timeouts, Transaction};
+use deadpool_postgres::{Manager, Object, Pool, Runtime, Timeouts, Transaction};
+use log::{log, Level};
 use std::error::Error;
-use tokio_postgres::NoTls;
+use std::future::Future;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio_postgres::{IsolationLevel, NoTls};
+use super::export::{self, EXPORT_COLUMNS};
+use super::replica::{Replica, ReplicaConfig, Subject};
+use super::retry::{classify, Failure, NoRetry, RetryPolicy};
+use super::summaries::{lookup_condition, summary, SUMMARY_COLUMNS, SUMMARY_JOINS};
 
 macro_rules! fill_fields {
     (Order, $data:expr, $($field:ident),+) => {
@@ -22,20 +33,239 @@ macro_rules! fill_fields {
             ),+
         }
     };
- }
+}
+
+pub(super) use fill_fields;
+
+/// Replica connections are given up on sooner, reads then fall back to the primary
+const REPLICA_CONNECT_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// Whether a standby streams WAL from the primary, and its replay lag in seconds, zero when it has replayed everything
+/// it received or it is not a standby. A standby whose WAL receiver is disconnected has replayed all it received
+/// and would look caught up however far behind it falls.
+const REPLICA_LAG: &str = "SELECT
+    NOT pg_is_in_recovery() OR EXISTS (SELECT 1 FROM pg_stat_wal_receiver WHERE status = 'streaming') AS streaming,
+    CASE
+        WHEN NOT pg_is_in_recovery() OR pg_last_wal_receive_lsn() = pg_last_wal_replay_lsn() THEN 0
+        ELSE coalesce(extract(epoch FROM now() - pg_last_xact_replay_timestamp()), 0)
+    END::FLOAT8 AS lag";
+
+/// Connection pool and failure handling of a `Database`, the replica's pool gets the same limits.
+#[derive(Clone, Debug)]
+pub struct DatabaseSettings {
+    /// Connections kept open at most, deadpool's default of four per CPU if not set
+    pub max_connections: Option<usize>,
+    /// How long a query waits for a free connection
+    pub wait_timeout: Duration,
+    /// How long opening a connection may take, unlimited if not set
+    pub create_timeout: Option<Duration>,
+    /// How long checking an idle connection before reuse may take, unlimited if not set
+    pub recycle_timeout: Option<Duration>,
+    pub retry: RetryPolicy,
+    pub breaker: BreakerConfig,
+}
+
+impl Default for DatabaseSettings {
+    fn default() -> Self {
+        Self {
+            max_connections: None,
+            wait_timeout: Duration::from_secs(30),
+            create_timeout: None,
+            recycle_timeout: None,
+            retry: RetryPolicy::default(),
+            breaker: BreakerConfig::default(),
+        }
+    }
+}
+
+impl DatabaseSettings {
+    fn pool(&self, config: tokio_postgres::Config, timeouts: Timeouts) -> Result<Pool, Box<dyn Error>> {
+        let mut builder = Pool::builder(Manager::new(config, NoTls))
+            .runtime(Runtime::Tokio1)
+            .timeouts(timeouts);
+        if let Some(max_connections) = self.max_connections {
+            builder = builder.max_size(max_connections);
+        }
+        Ok(builder.build()?)
+    }
 
+    fn timeouts(&self) -> Timeouts {
+        Timeouts {
+            wait: Some(self.wait_timeout),
+            create: self.create_timeout,
+            recycle: self.recycle_timeout,
+        }
+    }
+}
+
+#[derive(Clone)]
 pub struct Database {
-    pool: Pool,
+    pub(super) pool: Pool,
+    replica: Option<Arc<Replica>>,
+    settings: DatabaseSettings,
+    breaker: Arc<CircuitBreaker>,
 }
 
 impl Database {
     pub async fn new(config: String) -> Result<Database, Box<dyn Error>> {
-        let config = config.parse::<tokio_postgres::Config>()?;
-        let pool = Pool::builder(Manager::new(config, NoTls))
-            .runtime(Runtime::Tokio1)
-            .timeouts(Timeouts::wait_millis(30000))
-            .build()?;
-        Ok(Database { pool })
+        Self::from_config(config.parse::<tokio_postgres::Config>()?)
+    }
+
+    pub fn from_config(config: tokio_postgres::Config) -> Result<Database, Box<dyn Error>> {
+        Self::with_settings(config, DatabaseSettings::default())
+    }
+
+    pub fn with_settings(config: tokio_postgres::Config, settings: DatabaseSettings) -> Result<Database, Box<dyn Error>> {
+        let pool = settings.pool(config, settings.timeouts())?;
+        let breaker = Arc::new(CircuitBreaker::new(settings.breaker));
+        Ok(Database { pool, replica: None, settings, breaker })
+    }
+
+    /// Reads lookups, search, reports and exports from a replica, `ReplicaConfig` tells when the primary is used instead.
+    pub fn with_replica(mut self, config: tokio_postgres::Config, replica: ReplicaConfig) -> Result<Database, Box<dyn Error>> {
+        let timeouts = Timeouts {
+            create: Some(self.settings.create_timeout.map_or(REPLICA_CONNECT_TIMEOUT, |create| create.min(REPLICA_CONNECT_TIMEOUT))),
+            ..self.settings.timeouts()
+        };
+        let pool = self.settings.pool(config, timeouts)?;
+        self.replica = Some(Arc::new(Replica::new(pool, replica)));
+        Ok(self)
+    }
+
+    /// Runs `operation` unless the circuit breaker says Postgres is down, retrying serialization failures,
+    /// deadlocks and lost connections with jittered backoff while the request deadline allows.
+    /// Errors the operation wraps in `NoRetry` are returned unwrapped right away.
+    pub(super) async fn resilient<T, F, Fut>(&self, name: &str, operation: F) -> Result<T, Box<dyn Error>>
+    where
+        F: Fn() -> Fut,
+        Fut: Future<Output = Result<T, Box<dyn Error>>>,
+    {
+        let policy = self.settings.retry;
+        let mut attempt = 1;
+        loop {
+            self.breaker.check()?;
+            let delay = match operation().await {
+                Ok(value) => {
+                    self.breaker.success();
+                    return Ok(value);
+                }
+                Err(err) => {
+                    let (err, retryable) = match err.downcast::<NoRetry>() {
+                        Ok(no_retry) => (no_retry.0, false),
+                        Err(err) => (err, true),
+                    };
+                    let failure = classify(err.as_ref());
+                    if failure == Failure::Unavailable {
+                        self.breaker.failure();
+                    } else {
+                        self.breaker.success();
+                    }
+                    let delay = policy.delay(attempt);
+                    if !retryable
+                        || failure == Failure::Permanent
+                        || attempt >= policy.max_attempts
+                        || deadline::remaining().is_some_and(|remaining| remaining <= delay)
+                    {
+                        return Err(err);
+                    }
+                    log!(target: "database", Level::Warn, "{name} failed on attempt {attempt}, retrying in {delay:?}: {err}");
+                    delay
+                }
+            };
+            tokio::time::sleep(delay).await;
+            attempt += 1;
+        }
+    }
+
+    pub fn has_replica(&self) -> bool {
+        self.replica.is_some()
+    }
+
+    /// Measures the replica's lag, reads skip it while it's unreachable, doesn't stream from the primary
+    /// or lags more than allowed. `None` without a replica.
+    pub async fn check_replica(&self) -> Result<Option<Duration>, Box<dyn Error>> {
+        let Some(replica) = &self.replica else {
+            return Ok(None);
+        };
+        let lag = async {
+            let row = replica.pool.get().await?.query_one(REPLICA_LAG, &[]).await?;
+            let lag = Duration::from_secs_f64(row.get::<_, f64>("lag").max(0.0));
+            Ok::<_, Box<dyn Error>>((row.get::<_, bool>("streaming"), lag))
+        }
+        .await;
+        match lag {
+            Ok((false, _)) => {
+                replica.set_usable(false, "it doesn't stream WAL from the primary");
+                return Err("Replica doesn't stream WAL from the primary, its lag is unknown".into());
+            }
+            Ok((_, lag)) if lag > replica.config.max_lag => replica.set_usable(false, &format!("it lags {lag:?} behind")),
+            Ok(_) => replica.set_usable(true, "it caught up"),
+            Err(err) => {
+                replica.set_usable(false, &format!("it is unreachable, {err}"));
+                return Err(err);
+            }
+        }
+        lag.map(|(_, lag)| Some(lag))
+    }
+
+    /// Connection for a read about `subjects`: the replica unless there is none, it's skipped or they were just written.
+    pub(super) async fn reader(&self, subjects: &[Subject<'_>]) -> Result<Object, Box<dyn Error>> {
+        let replica = self
+            .replica
+            .as_ref()
+            .filter(|replica| replica.usable() && !replica.recently_written(subjects));
+        if let Some(replica) = replica {
+            match replica.pool.get().await {
+                Ok(client) => return Ok(client),
+                Err(err) => replica.set_usable(false, &format!("it is unreachable, {err}")),
+            }
+        }
+        Ok(self.pool.get().await?)
+    }
+
+    /// Pool for reads that aren't about particular orders or customers.
+    pub(super) fn read_pool(&self) -> &Pool {
+        match &self.replica {
+            Some(replica) if replica.usable() => &replica.pool,
+            _ => &self.pool,
+        }
+    }
+
+    fn wrote(&self, subjects: &[Subject<'_>]) {
+        if let Some(replica) = &self.replica {
+            replica.wrote(subjects);
+        }
+    }
+
+    /// Latest migration in `migrations/` the code relies on.
+    pub const SCHEMA_VERSION: i32 = 10;
+
+    /// Latest migration applied by refinery, `None` if the database was never migrated with it.
+    pub async fn schema_version(&self) -> Result<Option<i32>, Box<dyn Error>> {
+        let client = self.pool.get().await?;
+        let history = client
+            .query_one("SELECT to_regclass('refinery_schema_history') IS NOT NULL", &[])
+            .await?;
+        if !history.get::<_, bool>(0) {
+            return Ok(None);
+        }
+        let row = client.query_one("SELECT max(version) FROM refinery_schema_history", &[]).await?;
+        Ok(row.get(0))
+    }
+
+    /// Makes Postgres abort the transaction's statements once the request deadline passes,
+    /// so that timed out requests don't keep working in the database.
+    pub(super) async fn apply_deadline(transaction: &Transaction<'_>) -> Result<(), Box<dyn Error>> {
+        if let Some(remaining) = deadline::remaining() {
+            if remaining.is_zero() {
+                return Err(DeadlineExceeded.into());
+            }
+            let millis = remaining.as_millis().max(1);
+            transaction
+                .batch_execute(&format!("SET LOCAL statement_timeout = {millis}"))
+                .await?;
+        }
+        Ok(())
     }
 
     async fn insert_order<'a>(
@@ -159,16 +389,20 @@ impl Database {
         data: &Order,
     ) -> Result<Transaction<'a>, Box<dyn Error>> {
         let items_insert = transaction
-            .prepare("INSERT INTO Items VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11) ON CONFLICT DO NOTHING;")
-            .await?;
-        let order_items = transaction
-            .prepare("INSERT INTO OrderItems VALUES ($1, $2);")
+            .prepare(
+                "INSERT INTO Items(order_uid, position, chrt_id, track_number, price, rid, name, sale, size,
+                                   total_price, nm_id, brand, status)
+                 VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13)",
+            )
             .await?;
-        for item in &data.items {
+        for (position, item) in data.items.iter().enumerate() {
+            let position = position as i32;
             let result = transaction
                 .execute(
                     &items_insert,
                     &[
+                        &data.order_uid,
+                        &position,
                         &item.chrt_id,
                         &item.track_number,
                         &item.price,
@@ -183,15 +417,6 @@ impl Database {
                     ],
                 )
                 .await;
-            if let Err(err) = result {
-                if let Err(roll_err) = transaction.rollback().await {
-                    return Err(roll_err.into());
-                }
-                return Err(err.into());
-            }
-            let result = transaction
-                .execute(&order_items, &[&data.order_uid, &item.chrt_id])
-                .await;
             if let Err(err) = result {
                 if let Err(roll_err) = transaction.rollback().await {
                     return Err(MultiError::new(vec![err.into(), roll_err.into()]).into());
@@ -201,12 +426,29 @@ impl Database {
         }
         Ok(transaction)
     }
+
+    async fn insert_outbox<'a>(
+        transaction: Transaction<'a>,
+        data: &Order,
+    ) -> Result<Transaction<'a>, Box<dyn Error>> {
+        let payload = serde_json::to_string(data)?;
+        let result = transaction
+            .execute(
+                "INSERT INTO Outbox(event_type, aggregate_id, payload) VALUES ($1, $2, CAST($3::TEXT AS JSONB))",
+                &[&OrderEvent::ORDER_CREATED, &data.order_uid, &payload],
+            )
+            .await;
+        if let Err(err) = result {
+            if let Err(roll_err) = transaction.rollback().await {
+                return Err(MultiError::new(vec![err.into(), roll_err.into()]).into());
+            }
+            return Err(err.into());
+        }
+        Ok(transaction)
+    }
     
-    async fn get_order(&self, order_id: &str) -> Result<Option<Order>, Box<dyn Error>> {
-        let result = self
-            .pool
-            .get()
-            .await?
+    async fn get_order(transaction: &Transaction<'_>, order_id: &str) -> Result<Option<Order>, Box<dyn Error>> {
+        let result = transaction
             .query("SELECT * FROM Orders WHERE order_uid = $1", &[&order_id])
             .await;
         match result {
@@ -234,11 +476,8 @@ impl Database {
         }
     }
 
-    async fn get_delivery(&self, order_id: &str) -> Result<Option<Delivery>, Box<dyn Error>> {
-        let result = self
-            .pool
-            .get()
-            .await?
+    async fn get_delivery(transaction: &Transaction<'_>, order_id: &str) -> Result<Option<Delivery>, Box<dyn Error>> {
+        let result = transaction
             .query(
                 "SELECT * FROM Deliveries d
                 JOIN OrderDeliveries od ON od.delivery_id = d.id
@@ -259,11 +498,8 @@ impl Database {
         }
     }
 
-    async fn get_payment(&self, order_uid: &str) -> Result<Option<Payment>, Box<dyn Error>> {
-        let result = self
-            .pool
-            .get()
-            .await?
+    async fn get_payment(transaction: &Transaction<'_>, order_uid: &str) -> Result<Option<Payment>, Box<dyn Error>> {
+        let result = transaction
             .query(
                 "SELECT * FROM Payments p
                  JOIN OrderPayments op ON p.transaction = op.payment_id
@@ -295,47 +531,193 @@ impl Database {
         }
     }
 
-    async fn get_items(&self, order_id: &str) -> Result<Option<Vec<Item>>, Box<dyn Error>> {
-        let result = self
-            .pool
-            .get()
-            .await?
+    async fn get_items(transaction: &Transaction<'_>, order_id: &str) -> Result<Vec<Item>, Box<dyn Error>> {
+        let rows = transaction
+            .query("SELECT chrt_id, track_number, price, rid, name, sale, size, total_price, nm_id, brand, status
+                 FROM Items WHERE order_uid = $1 ORDER BY position", &[&order_id])
+            .await?;
+        let items = rows
+            .iter()
+            .map(|row| {
+                fill_fields!(
+                    Item,
+                    row,
+                    chrt_id,
+                    track_number,
+                    price,
+                    rid,
+                    name,
+                    sale,
+                    size,
+                    total_price,
+                    nm_id,
+                    brand,
+                    status
+                )
+            })
+            .collect();
+        Ok(items)
+    }
+
+    async fn try_insert(&self, data: &Order) -> Result<(), Box<dyn Error>> {
+        let mut instance = self.pool.get().await?;
+        let transaction = instance.transaction().await?;
+        Self::apply_deadline(&transaction).await?;
+        let transaction = Self::insert_order(transaction, data).await?;
+        let transaction = Self::insert_delivery(transaction, data).await?;
+        let transaction = Self::insert_payment(transaction, data).await?;
+        let transaction = Self::insert_items(transaction, data).await?;
+        let transaction = Self::insert_outbox(transaction, data).await?;
+        // Postgres rolls back on contention, but after a lost connection the order may be stored already
+        // and a retry would report the caller's own write as a conflict
+        transaction.commit().await.map_err(|err| -> Box<dyn Error> {
+            let err: Box<dyn Error> = err.into();
+            match classify(err.as_ref()) {
+                Failure::Contention => err,
+                _ => Box::new(NoRetry(err)),
+            }
+        })?;
+        self.wrote(&[Subject::Order(&data.order_uid), Subject::Customer(&data.customer_id)]);
+        Ok(())
+    }
+
+    async fn try_get(&self, id: &str) -> Result<Option<Order>, Box<dyn Error>> {
+        let mut instance = self.reader(&[Subject::Order(id)]).await?;
+        let transaction = instance.build_transaction().read_only(true).start().await?;
+        Self::apply_deadline(&transaction).await?;
+        let order = Self::get_order(&transaction, id).await?;
+        if order.is_none() {
+            return Ok(None);
+        }
+        let mut order = order.unwrap();
+        let payment = Self::get_payment(&transaction, id).await?;
+        if payment.is_none() {
+            return Ok(None);
+        }
+        order.payment = payment.unwrap();
+        let delivery = Self::get_delivery(&transaction, id).await?;
+        if delivery.is_none() {
+            return Ok(None);
+        }
+        order.delivery = delivery.unwrap();
+        order.items = Self::get_items(&transaction, id).await?;
+        transaction.commit().await?;
+        Ok(Some(order))
+    }
+
+    async fn try_get_many(&self, ids: &[String]) -> Result<Vec<Order>, Box<dyn Error>> {
+        if ids.is_empty() {
+            return Ok(Vec::new());
+        }
+        let query = format!("SELECT {EXPORT_COLUMNS} FROM Orders o {SUMMARY_JOINS} WHERE o.order_uid = ANY($1)");
+        let subjects: Vec<Subject> = ids.iter().map(|id| Subject::Order(id)).collect();
+        let mut instance = self.reader(&subjects).await?;
+        let transaction = instance.build_transaction().read_only(true).start().await?;
+        Self::apply_deadline(&transaction).await?;
+        let rows = transaction.query(&query, &[&ids]).await?;
+        transaction.commit().await?;
+        rows.iter().map(|row| export::order(row).map_err(|err| -> Box<dyn Error> { err })).collect()
+    }
+
+    async fn try_existing(&self, ids: &[String]) -> Result<Vec<String>, Box<dyn Error>> {
+        if ids.is_empty() {
+            return Ok(Vec::new());
+        }
+        let subjects: Vec<Subject> = ids.iter().map(|id| Subject::Order(id)).collect();
+        let instance = self.reader(&subjects).await?;
+        let rows = instance.query("SELECT order_uid FROM Orders WHERE order_uid = ANY($1)", &[&ids]).await?;
+        Ok(rows.iter().map(|row| row.get(0)).collect())
+    }
+
+    async fn get_summaries(
+        transaction: &Transaction<'_>,
+        lookup: &OrderLookup,
+        limit: i64,
+        offset: i64,
+    ) -> Result<Vec<OrderSummary>, Box<dyn Error>> {
+        let (condition, key) = lookup_condition(lookup, "$1");
+        let query = format!(
+            "SELECT {SUMMARY_COLUMNS} FROM Orders o {SUMMARY_JOINS}
+             WHERE {condition}
+             ORDER BY o.date_created DESC, o.order_uid
+             LIMIT $2 OFFSET $3"
+        );
+        let rows = transaction.query(&query, &[key, &limit, &offset]).await?;
+        Ok(rows.iter().map(summary).collect())
+    }
+
+    async fn get_customer_totals(transaction: &Transaction<'_>, customer_id: &str) -> Result<CustomerTotals, Box<dyn Error>> {
+        // The row of the empty grouping set covers all currencies and is there even without orders
+        let rows = transaction
             .query(
-                "SELECT i.* FROM Items i 
-                 JOIN OrderItems oi ON i.chrt_id = oi.chrt_id 
-                 WHERE oi.order_uid = $1",
-                &[&order_id],
+                "SELECT GROUPING(p.currency) AS overall, p.currency, count(*) AS order_count,
+                        coalesce(sum(p.amount), 0)::BIGINT AS spent, max(o.date_created) AS last_order_date
+                 FROM Orders o
+                 JOIN OrderPayments op ON op.order_uid = o.order_uid
+                 JOIN Payments p ON p.transaction = op.payment_id
+                 WHERE o.customer_id = $1
+                 GROUP BY GROUPING SETS ((p.currency), ())
+                 ORDER BY overall DESC, p.currency",
+                &[&customer_id],
             )
-            .await;
-        match result {
-            Ok(rows) => {
-                if rows.is_empty() {
-                    return Ok(None);
-                }
-                let items: Vec<Item> = rows
-                    .iter()
-                    .map(|row| {
-                        fill_fields!(
-                            Item,
-                            row,
-                            chrt_id,
-                            track_number,
-                            price,
-                            rid,
-                            name,
-                            sale,
-                            size,
-                            total_price,
-                            nm_id,
-                            brand,
-                            status
-                        )
-                    })
-                    .collect();
-                Ok(Some(items))
+            .await?;
+        let mut totals = CustomerTotals::default();
+        for row in rows {
+            if row.get::<_, i32>("overall") == 1 {
+                totals.order_count = row.get("order_count");
+                totals.last_order_date = row.get("last_order_date");
+            } else {
+                totals.spent.push(CurrencyTotal {
+                    currency: row.get("currency"),
+                    amount: row.get("spent"),
+                });
             }
-            Err(err) => Err(err.into()),
         }
+        Ok(totals)
+    }
+
+    async fn try_find(&self, lookup: &OrderLookup, limit: i64, offset: i64) -> Result<Vec<OrderSummary>, Box<dyn Error>> {
+        let subjects = match lookup {
+            OrderLookup::CustomerId(customer_id) => vec![Subject::Customer(customer_id)],
+            _ => Vec::new(),
+        };
+        let mut instance = self.reader(&subjects).await?;
+        let transaction = instance.build_transaction().read_only(true).start().await?;
+        Self::apply_deadline(&transaction).await?;
+        let orders = Self::get_summaries(&transaction, lookup, limit, offset).await?;
+        transaction.commit().await?;
+        Ok(orders)
+    }
+
+    async fn try_customer_totals(&self, customer_id: &str) -> Result<CustomerTotals, Box<dyn Error>> {
+        let mut instance = self.reader(&[Subject::Customer(customer_id)]).await?;
+        let transaction = instance.build_transaction().read_only(true).start().await?;
+        Self::apply_deadline(&transaction).await?;
+        let totals = Self::get_customer_totals(&transaction, customer_id).await?;
+        transaction.commit().await?;
+        Ok(totals)
+    }
+
+    async fn try_customer_orders(
+        &self,
+        customer_id: &str,
+        limit: i64,
+        offset: i64,
+    ) -> Result<(Vec<OrderSummary>, CustomerTotals), Box<dyn Error>> {
+        let mut instance = self.reader(&[Subject::Customer(customer_id)]).await?;
+        // Both statements see the same snapshot, an order committed in between shows up in neither
+        let transaction = instance
+            .build_transaction()
+            .isolation_level(IsolationLevel::RepeatableRead)
+            .read_only(true)
+            .start()
+            .await?;
+        Self::apply_deadline(&transaction).await?;
+        let lookup = OrderLookup::CustomerId(customer_id.to_string());
+        let orders = Self::get_summaries(&transaction, &lookup, limit, offset).await?;
+        let totals = Self::get_customer_totals(&transaction, customer_id).await?;
+        transaction.commit().await?;
+        Ok((orders, totals))
     }
 }
 
@@ -344,23 +726,35 @@ impl interfaces::Database for Database {
     type Error = Box<dyn Error>;
     
     async fn insert(&self, data: Order) -> Result<(), Self::Error> {
-        let mut instance = self.pool.get().await?;
-        let transaction = instance.transaction().await?;
-        let transaction = Self::insert_order(transaction, &data).await?;
-        let transaction = Self::insert_delivery(transaction, &data).await?;
-        let transaction = Self::insert_payment(transaction, &data).await?;
-        let transaction = Self::insert_items(transaction, &data).await?;
-        Ok(transaction.commit().await?)
+        self.resilient("insert", || self.try_insert(&data)).await
     }
 
     async fn remove(&self, id: &str) -> Result<(), Self::Error> {
         let mut instance = self.pool.get().await?;
         let transaction = instance.transaction().await?;
         let result = transaction
-            .execute("DELETE FROM Orders CASCADE WHERE order_id = $1", &[&id])
+            .query(
+                "WITH payments AS (
+                     DELETE FROM Payments
+                     WHERE transaction IN (SELECT payment_id FROM OrderPayments WHERE order_uid = $1)
+                 ), deliveries AS (
+                     DELETE FROM Deliveries
+                     WHERE id IN (SELECT delivery_id FROM OrderDeliveries WHERE order_uid = $1)
+                 )
+                 DELETE FROM Orders WHERE order_uid = $1
+                 RETURNING customer_id",
+                &[&id],
+            )
             .await;
         match result {
-            Ok(_) => Ok(transaction.commit().await?),
+            Ok(rows) => {
+                transaction.commit().await?;
+                let customers: Vec<String> = rows.iter().map(|row| row.get("customer_id")).collect();
+                let mut subjects = vec![Subject::Order(id)];
+                subjects.extend(customers.iter().map(|customer_id| Subject::Customer(customer_id)));
+                self.wrote(&subjects);
+                Ok(())
+            }
             Err(err) => {
                 transaction.rollback().await?;
                 Err(err.into())
@@ -369,26 +763,107 @@ impl interfaces::Database for Database {
     }
 
     async fn get(&self, id: &str) -> Result<Option<Order>, Self::Error> {
-        let order = self.get_order(id).await?;
-        if order.is_none() {
-            return Ok(None);
-        }
-        let mut order = order.unwrap();
-        let payment = self.get_payment(id).await?;
-        if payment.is_none() {
-            return Ok(None);
-        }
-        order.payment = payment.unwrap();
-        let delivery = self.get_delivery(id).await?;
-        if delivery.is_none() {
-            return Ok(None);
-        }
-        order.delivery = delivery.unwrap();
-        let items = self.get_items(id).await?;
-        if items.is_none() {
-            return Ok(None);
+        self.resilient("get", || self.try_get(id)).await
+    }
+
+    /// A single statement for all of them, with items aggregated per order.
+    async fn get_many(&self, ids: &[String]) -> Result<Vec<Order>, Self::Error> {
+        self.resilient("get_many", || self.try_get_many(ids)).await
+    }
+
+    async fn existing(&self, ids: &[String]) -> Result<Vec<String>, Self::Error> {
+        self.resilient("existing", || self.try_existing(ids)).await
+    }
+
+    async fn find(&self, lookup: &OrderLookup, limit: i64, offset: i64) -> Result<Vec<OrderSummary>, Self::Error> {
+        self.resilient("find", || self.try_find(lookup, limit, offset)).await
+    }
+
+    async fn customer_totals(&self, customer_id: &str) -> Result<CustomerTotals, Self::Error> {
+        self.resilient("customer_totals", || self.try_customer_totals(customer_id)).await
+    }
+
+    async fn customer_orders(
+        &self,
+        customer_id: &str,
+        limit: i64,
+        offset: i64,
+    ) -> Result<(Vec<OrderSummary>, CustomerTotals), Self::Error> {
+        self.resilient("customer_orders", || self.try_customer_orders(customer_id, limit, offset)).await
+    }
+}
+
+#[async_trait]
+impl interfaces::Outbox for Database {
+    type Error = Box<dyn Error>;
+
+    async fn claim_pending(&self, limit: i64, lease: Duration) -> Result<Vec<OrderEvent>, Self::Error> {
+        let rows = self
+            .pool
+            .get()
+            .await?
+            .query(
+                "UPDATE Outbox SET next_attempt_at = now() + make_interval(secs => $2)
+                 WHERE id IN (
+                     SELECT id FROM Outbox
+                     WHERE delivered_at IS NULL AND failed_at IS NULL AND next_attempt_at <= now()
+                     ORDER BY id
+                     LIMIT $1
+                     FOR UPDATE SKIP LOCKED
+                 )
+                 RETURNING id, event_type, aggregate_id, created_at::TEXT AS created_at, attempts, payload::TEXT AS payload",
+                &[&limit, &lease.as_secs_f64()],
+            )
+            .await?;
+        let mut events = Vec::with_capacity(rows.len());
+        for row in rows {
+            events.push(OrderEvent {
+                id: row.get("id"),
+                event_type: row.get("event_type"),
+                order_uid: row.get("aggregate_id"),
+                created_at: row.get("created_at"),
+                attempts: row.get("attempts"),
+                payload: serde_json::from_str(row.get("payload"))?,
+            });
         }
-        order.items = items.unwrap();
-        Ok(Some(order))
+        events.sort_by_key(|event| event.id);
+        Ok(events)
+    }
+
+    async fn mark_delivered(&self, id: i64) -> Result<(), Self::Error> {
+        self.pool
+            .get()
+            .await?
+            .execute(
+                "UPDATE Outbox SET delivered_at = now(), attempts = attempts + 1, last_error = NULL WHERE id = $1",
+                &[&id],
+            )
+            .await?;
+        Ok(())
+    }
+
+    async fn mark_failed(&self, id: i64, error: &str, retry_in: Option<Duration>) -> Result<(), Self::Error> {
+        let client = self.pool.get().await?;
+        match retry_in {
+            Some(delay) => {
+                client
+                    .execute(
+                        "UPDATE Outbox SET attempts = attempts + 1, last_error = $2,
+                         next_attempt_at = now() + make_interval(secs => $3)
+                         WHERE id = $1",
+                        &[&id, &error, &delay.as_secs_f64()],
+                    )
+                    .await?
+            }
+            None => {
+                client
+                    .execute(
+                        "UPDATE Outbox SET attempts = attempts + 1, last_error = $2, failed_at = now() WHERE id = $1",
+                        &[&id, &error],
+                    )
+                    .await?
+            }
+        };
+        Ok(())
     }
 }
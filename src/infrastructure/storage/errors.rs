@@ -21,4 +21,35 @@ impl Display for MultiError {
     }
 }
 
-impl Error for MultiError {}
\ No newline at end of file
+impl Error for MultiError {}
+
+#[derive(Debug)]
+pub struct DeadlineExceeded;
+
+impl Display for DeadlineExceeded {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Request deadline exceeded")
+    }
+}
+
+impl Error for DeadlineExceeded {}
+
+/// Order or one of its unique parts is already stored.
+#[derive(Debug)]
+pub struct Conflict(pub String);
+
+impl Display for Conflict {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} already exists", self.0)
+    }
+}
+
+impl Error for Conflict {}
+
+/// Whether the error is a `Conflict` or Postgres refusing a duplicate key.
+pub fn is_conflict(error: &(dyn Error + 'static)) -> bool {
+    error.is::<Conflict>()
+        || error
+            .downcast_ref::<tokio_postgres::Error>()
+            .is_some_and(|err| err.code() == Some(&tokio_postgres::error::SqlState::UNIQUE_VIOLATION))
+}
@@ -0,0 +1,162 @@
+use crate::domain::interfaces::{self, OrderStream};
+use crate::domain::models::{Delivery, Item, Order, OrderFilter, Payment};
+use crate::infrastructure::Database;
+use super::database::fill_fields;
+use super::summaries::{lookup_condition, SUMMARY_JOINS};
+use axum::async_trait;
+use deadpool_postgres::Pool;
+use futures::StreamExt;
+use std::error::Error;
+use tokio::sync::{mpsc, oneshot};
+use tokio_postgres::types::ToSql;
+use tokio_postgres::Row;
+use tokio_stream::wrappers::ReceiverStream;
+
+/// Rows fetched from the cursor at once
+const BATCH: i32 = 500;
+
+pub(super) const EXPORT_COLUMNS: &str = "o.order_uid, o.track_number, o.entry, o.locale, o.internal_signature, o.customer_id,
+    o.delivery_service, o.shardkey, o.sm_id, o.date_created, o.oof_shard,
+    d.name AS delivery_name, d.phone AS delivery_phone, d.zip AS delivery_zip, d.address AS delivery_address,
+    d.region AS delivery_region, d.email AS delivery_email,
+    p.transaction, p.request_id, p.currency, p.provider, p.amount, p.payment_dt, p.bank, p.delivery_cost,
+    p.goods_total, p.custom_fee,
+    coalesce((SELECT json_agg(json_build_object(
+                  'chrt_id', i.chrt_id, 'track_number', i.track_number, 'price', i.price, 'rid', i.rid,
+                  'name', i.name, 'sale', i.sale, 'size', i.size, 'total_price', i.total_price,
+                  'nm_id', i.nm_id, 'brand', i.brand, 'status', i.status) ORDER BY i.position)
+              FROM Items i WHERE i.order_uid = o.order_uid), '[]')::TEXT AS items";
+
+type SendError = Box<dyn Error + Send + Sync>;
+
+pub(super) fn order(row: &Row) -> Result<Order, SendError> {
+    let mut order = fill_fields!(
+        Order,
+        row,
+        order_uid,
+        track_number,
+        entry,
+        locale,
+        internal_signature,
+        customer_id,
+        delivery_service,
+        shardkey,
+        sm_id,
+        date_created,
+        oof_shard
+    );
+    order.delivery = Delivery {
+        name: row.get("delivery_name"),
+        phone: row.get("delivery_phone"),
+        zip: row.get("delivery_zip"),
+        address: row.get("delivery_address"),
+        region: row.get("delivery_region"),
+        email: row.get("delivery_email"),
+    };
+    order.payment = fill_fields!(
+        Payment,
+        row,
+        transaction,
+        request_id,
+        currency,
+        provider,
+        amount,
+        payment_dt,
+        bank,
+        delivery_cost,
+        goods_total,
+        custom_fee
+    );
+    order.items = serde_json::from_str::<Vec<Item>>(row.get("items"))?;
+    Ok(order)
+}
+
+/// Reads the orders through a portal, a server-side cursor, a batch at a time as the receiver keeps up.
+/// The outcome of starting the query is reported through `ready` before any order is sent.
+async fn stream_orders(
+    pool: Pool,
+    filter: OrderFilter,
+    ready: oneshot::Sender<Result<(), SendError>>,
+    sender: mpsc::Sender<Result<Order, SendError>>,
+) {
+    let mut conditions = Vec::new();
+    let mut params: Vec<&(dyn ToSql + Sync)> = Vec::new();
+    if let Some(lookup) = &filter.lookup {
+        let (condition, key) = lookup_condition(lookup, "$1");
+        conditions.push(condition);
+        params.push(key);
+    }
+    for (bound, operator) in [(&filter.range.from, ">="), (&filter.range.to, "<=")] {
+        if let Some(day) = bound {
+            params.push(day);
+            conditions.push(format!("order_day(o.date_created) {operator} ${}::TEXT::DATE", params.len()));
+        }
+    }
+    let condition = if conditions.is_empty() { "TRUE".to_string() } else { conditions.join(" AND ") };
+    let query = format!(
+        "SELECT {EXPORT_COLUMNS} FROM Orders o {SUMMARY_JOINS}
+         WHERE {condition}
+         ORDER BY o.date_created, o.order_uid"
+    );
+    let mut client = match pool.get().await {
+        Ok(client) => client,
+        Err(err) => {
+            let _ = ready.send(Err(err.into()));
+            return;
+        }
+    };
+    let transaction = match client.build_transaction().read_only(true).start().await {
+        Ok(transaction) => transaction,
+        Err(err) => {
+            let _ = ready.send(Err(err.into()));
+            return;
+        }
+    };
+    let portal = match transaction.bind(&query, &params).await {
+        Ok(portal) => portal,
+        Err(err) => {
+            let _ = ready.send(Err(err.into()));
+            return;
+        }
+    };
+    if ready.send(Ok(())).is_err() {
+        return;
+    }
+    loop {
+        let rows = match transaction.query_portal(&portal, BATCH).await {
+            Ok(rows) => rows,
+            Err(err) => {
+                let _ = sender.send(Err(err.into())).await;
+                return;
+            }
+        };
+        let last = rows.len() < BATCH as usize;
+        for row in rows {
+            // The receiver is gone, dropping the transaction rolls it back
+            if sender.send(order(&row)).await.is_err() {
+                return;
+            }
+        }
+        if last {
+            break;
+        }
+    }
+    if let Err(err) = transaction.commit().await {
+        let _ = sender.send(Err(err.into())).await;
+    }
+}
+
+#[async_trait]
+impl interfaces::OrderExport for Database {
+    type Error = Box<dyn Error>;
+
+    async fn export(&self, filter: &OrderFilter) -> Result<OrderStream, Self::Error> {
+        let (ready, started) = oneshot::channel();
+        let (sender, receiver) = mpsc::channel(BATCH as usize);
+        tokio::spawn(stream_orders(self.read_pool().clone(), filter.clone(), ready, sender));
+        match started.await? {
+            Ok(()) => Ok(ReceiverStream::new(receiver).boxed()),
+            Err(err) => Err(err),
+        }
+    }
+}
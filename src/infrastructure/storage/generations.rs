@@ -0,0 +1,66 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+struct Entry {
+    generation: u64,
+    readers: usize,
+}
+
+/// Write generations of the ids being read, so that a read racing a write can tell its result is outdated.
+/// Ids are only tracked while reads of them are in flight.
+pub(super) struct Generations {
+    ids: Mutex<HashMap<String, Entry>>,
+}
+
+/// A read of one id in flight, ends when dropped.
+pub(super) struct Read<'a> {
+    generations: &'a Generations,
+    id: String,
+    generation: u64,
+}
+
+impl Generations {
+    pub(super) fn new() -> Self {
+        Self {
+            ids: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Starts a read of `id`, it must start before the database is asked.
+    pub(super) fn begin(&self, id: &str) -> Read<'_> {
+        let mut ids = self.ids.lock().unwrap();
+        let entry = ids.entry(id.to_string()).or_insert(Entry { generation: 0, readers: 0 });
+        entry.readers += 1;
+        Read {
+            generations: self,
+            id: id.to_string(),
+            generation: entry.generation,
+        }
+    }
+
+    /// Marks reads of `id` in flight as outdated, called once a write of it is committed.
+    pub(super) fn bump(&self, id: &str) {
+        if let Some(entry) = self.ids.lock().unwrap().get_mut(id) {
+            entry.generation += 1;
+        }
+    }
+}
+
+impl Read<'_> {
+    /// Whether the id was written since the read started.
+    pub(super) fn outdated(&self) -> bool {
+        self.generations.ids.lock().unwrap().get(&self.id).is_some_and(|entry| entry.generation != self.generation)
+    }
+}
+
+impl Drop for Read<'_> {
+    fn drop(&mut self) {
+        let mut ids = self.generations.ids.lock().unwrap();
+        if let Some(entry) = ids.get_mut(&self.id) {
+            entry.readers -= 1;
+            if entry.readers == 0 {
+                ids.remove(&self.id);
+            }
+        }
+    }
+}
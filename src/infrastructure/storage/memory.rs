@@ -0,0 +1,129 @@
+use crate::domain::interfaces::{self, OrderStream};
+use crate::domain::models::{CurrencyTotal, CustomerTotals, Order, OrderFilter, OrderLookup, OrderSummary};
+use super::errors::Conflict;
+use axum::async_trait;
+use futures::StreamExt;
+use std::collections::HashMap;
+use std::error::Error;
+use tokio::sync::RwLock;
+
+#[derive(Default)]
+struct Orders {
+    by_uid: HashMap<String, Order>,
+    /// Payment transactions are unique across orders, like their primary key in Postgres
+    transactions: HashMap<String, String>,
+}
+
+/// Keeps orders in process memory, for development and tests. Everything is lost on restart.
+#[derive(Default)]
+pub struct MemoryDatabase {
+    orders: RwLock<Orders>,
+}
+
+impl Orders {
+    fn find(&self, lookup: &OrderLookup, limit: i64, offset: i64) -> Vec<OrderSummary> {
+        let mut found: Vec<&Order> = self.by_uid.values().filter(|order| lookup.matches(order)).collect();
+        found.sort_by(|a, b| b.date_created.cmp(&a.date_created).then_with(|| a.order_uid.cmp(&b.order_uid)));
+        found
+            .into_iter()
+            .skip(offset.max(0) as usize)
+            .take(limit.max(0) as usize)
+            .map(OrderSummary::from)
+            .collect()
+    }
+
+    fn customer_totals(&self, customer_id: &str) -> CustomerTotals {
+        let mut totals = CustomerTotals::default();
+        let mut spent: HashMap<&str, i64> = HashMap::new();
+        for order in self.by_uid.values().filter(|order| order.customer_id == customer_id) {
+            totals.order_count += 1;
+            if totals.last_order_date.as_ref().is_none_or(|last| *last < order.date_created) {
+                totals.last_order_date = Some(order.date_created.clone());
+            }
+            *spent.entry(&order.payment.currency).or_default() += order.payment.amount as i64;
+        }
+        totals.spent = spent
+            .into_iter()
+            .map(|(currency, amount)| CurrencyTotal { currency: currency.to_string(), amount })
+            .collect();
+        totals.spent.sort_by(|a, b| a.currency.cmp(&b.currency));
+        totals
+    }
+}
+
+impl MemoryDatabase {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl interfaces::Database for MemoryDatabase {
+    type Error = Box<dyn Error>;
+
+    async fn insert(&self, data: Order) -> Result<(), Self::Error> {
+        let mut orders = self.orders.write().await;
+        if orders.by_uid.contains_key(&data.order_uid) {
+            return Err(Conflict(format!("Order {}", data.order_uid)).into());
+        }
+        if orders.transactions.contains_key(&data.payment.transaction) {
+            return Err(Conflict(format!("Payment {}", data.payment.transaction)).into());
+        }
+        orders.transactions.insert(data.payment.transaction.clone(), data.order_uid.clone());
+        orders.by_uid.insert(data.order_uid.clone(), data);
+        Ok(())
+    }
+
+    async fn remove(&self, id: &str) -> Result<(), Self::Error> {
+        let mut orders = self.orders.write().await;
+        if let Some(order) = orders.by_uid.remove(id) {
+            orders.transactions.remove(&order.payment.transaction);
+        }
+        Ok(())
+    }
+
+    async fn get(&self, id: &str) -> Result<Option<Order>, Self::Error> {
+        Ok(self.orders.read().await.by_uid.get(id).cloned())
+    }
+
+    async fn get_many(&self, ids: &[String]) -> Result<Vec<Order>, Self::Error> {
+        let orders = self.orders.read().await;
+        Ok(ids.iter().filter_map(|id| orders.by_uid.get(id).cloned()).collect())
+    }
+
+    async fn existing(&self, ids: &[String]) -> Result<Vec<String>, Self::Error> {
+        let orders = self.orders.read().await;
+        Ok(ids.iter().filter(|id| orders.by_uid.contains_key(*id)).cloned().collect())
+    }
+
+    async fn find(&self, lookup: &OrderLookup, limit: i64, offset: i64) -> Result<Vec<OrderSummary>, Self::Error> {
+        Ok(self.orders.read().await.find(lookup, limit, offset))
+    }
+
+    async fn customer_totals(&self, customer_id: &str) -> Result<CustomerTotals, Self::Error> {
+        Ok(self.orders.read().await.customer_totals(customer_id))
+    }
+
+    async fn customer_orders(
+        &self,
+        customer_id: &str,
+        limit: i64,
+        offset: i64,
+    ) -> Result<(Vec<OrderSummary>, CustomerTotals), Self::Error> {
+        let orders = self.orders.read().await;
+        let lookup = OrderLookup::CustomerId(customer_id.to_string());
+        Ok((orders.find(&lookup, limit, offset), orders.customer_totals(customer_id)))
+    }
+}
+
+#[async_trait]
+impl interfaces::OrderExport for MemoryDatabase {
+    type Error = Box<dyn Error>;
+
+    async fn export(&self, filter: &OrderFilter) -> Result<OrderStream, Self::Error> {
+        let orders = self.orders.read().await;
+        let mut found: Vec<Order> = orders.by_uid.values().filter(|order| filter.matches(order)).cloned().collect();
+        found.sort_by(|a, b| a.date_created.cmp(&b.date_created).then_with(|| a.order_uid.cmp(&b.order_uid)));
+        Ok(futures::stream::iter(found.into_iter().map(Ok)).boxed())
+    }
+}
@@ -2,8 +2,28 @@ mod database;
 mod cache;
 mod repository;
 mod errors;
+mod webhook_store;
+mod search;
+mod summaries;
+mod customer_cache;
+mod not_found_cache;
+mod generations;
+mod single_flight;
+mod analytics;
+mod export;
+mod memory;
+mod sqlite;
+mod replica;
+mod snapshot;
+mod retry;
+pub mod deadline;
 
 pub use cache::Cache;
-pub use database::Database;
+pub use database::{Database, DatabaseSettings};
 pub use repository::Repository;
-pub use errors::MultiError;
+pub use errors::{is_conflict, Conflict, DeadlineExceeded, MultiError};
+pub use memory::MemoryDatabase;
+pub use sqlite::SqliteDatabase;
+pub use replica::ReplicaConfig;
+pub use snapshot::InvalidSnapshot;
+pub use retry::RetryPolicy;
@@ -0,0 +1,68 @@
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+use tokio::sync::RwLock;
+
+struct Ids {
+    added_at: HashMap<String, Instant>,
+    /// Size at which expired ids are swept next, twice what was left after the last sweep
+    sweep_at: usize,
+    swept_at: Instant,
+}
+
+/// Ids recently looked up and not found, so that probing them doesn't reach the database every time.
+/// Entries expire so that orders added by other instances show up eventually.
+pub(super) struct NotFoundCache {
+    ttl: Duration,
+    ids: RwLock<Ids>,
+}
+
+impl NotFoundCache {
+    /// Expired ids are never swept before this many are cached
+    const MIN_SWEEP_AT: usize = 1024;
+    /// Ids cached at most, more of them are simply not remembered until some expire
+    const CAPACITY: usize = 65_536;
+
+    pub(super) fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            ids: RwLock::new(Ids {
+                added_at: HashMap::new(),
+                sweep_at: Self::MIN_SWEEP_AT,
+                swept_at: Instant::now(),
+            }),
+        }
+    }
+
+    pub(super) async fn contains(&self, id: &str) -> bool {
+        self.ids
+            .read()
+            .await
+            .added_at
+            .get(id)
+            .is_some_and(|added_at| added_at.elapsed() < self.ttl)
+    }
+
+    pub(super) async fn add(&self, id: &str) {
+        if self.ttl.is_zero() {
+            return;
+        }
+        let mut ids = self.ids.write().await;
+        // A full cache of live ids is swept a few times per TTL rather than on every add
+        let sweep = match ids.added_at.len() {
+            len if len >= Self::CAPACITY => ids.swept_at.elapsed() >= self.ttl / 4,
+            len => len >= ids.sweep_at,
+        };
+        if sweep {
+            ids.added_at.retain(|_, added_at| added_at.elapsed() < self.ttl);
+            ids.sweep_at = (ids.added_at.len() * 2).clamp(Self::MIN_SWEEP_AT, Self::CAPACITY);
+            ids.swept_at = Instant::now();
+        }
+        if ids.added_at.len() < Self::CAPACITY || ids.added_at.contains_key(id) {
+            ids.added_at.insert(id.to_string(), Instant::now());
+        }
+    }
+
+    pub(super) async fn invalidate(&self, id: &str) {
+        self.ids.write().await.added_at.remove(id);
+    }
+}
@@ -0,0 +1,94 @@
+use deadpool_postgres::Pool;
+use log::{log, Level};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// How reads are split between the primary and a read replica.
+#[derive(Clone, Debug)]
+pub struct ReplicaConfig {
+    /// Reads of an order or a customer written this recently go to the primary. It covers the rest of the
+    /// request that wrote them too, zero sends every read to the replica
+    pub read_after_write: Duration,
+    /// Replication lag beyond which reads go to the primary
+    pub max_lag: Duration,
+}
+
+impl Default for ReplicaConfig {
+    fn default() -> Self {
+        Self {
+            read_after_write: Duration::from_secs(5),
+            max_lag: Duration::from_secs(10),
+        }
+    }
+}
+
+/// What a read or a write is about, reads of anything written within the window see the primary.
+pub(super) enum Subject<'a> {
+    Order(&'a str),
+    Customer(&'a str),
+}
+
+impl Subject<'_> {
+    fn key(&self) -> String {
+        match self {
+            Subject::Order(order_uid) => format!("order:{order_uid}"),
+            Subject::Customer(customer_id) => format!("customer:{customer_id}"),
+        }
+    }
+}
+
+pub(super) struct Replica {
+    pub(super) pool: Pool,
+    pub(super) config: ReplicaConfig,
+    /// Cleared when the replica can't be connected to or lags behind, set again by `Database::check_replica`
+    usable: AtomicBool,
+    written: Mutex<HashMap<String, Instant>>,
+}
+
+impl Replica {
+    pub(super) fn new(pool: Pool, config: ReplicaConfig) -> Self {
+        Self {
+            pool,
+            config,
+            usable: AtomicBool::new(true),
+            written: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub(super) fn usable(&self) -> bool {
+        self.usable.load(Ordering::Relaxed)
+    }
+
+    pub(super) fn set_usable(&self, usable: bool, reason: &str) {
+        if self.usable.swap(usable, Ordering::Relaxed) != usable {
+            let level = if usable { Level::Info } else { Level::Warn };
+            log!(target: "replica", level, "Replica {}: {reason}", if usable { "is back" } else { "is skipped" });
+        }
+    }
+
+    pub(super) fn wrote(&self, subjects: &[Subject<'_>]) {
+        let window = self.config.read_after_write;
+        if window.is_zero() {
+            return;
+        }
+        let now = Instant::now();
+        let mut written = self.written.lock().unwrap();
+        written.retain(|_, at| now.duration_since(*at) < window);
+        for subject in subjects {
+            written.insert(subject.key(), now);
+        }
+    }
+
+    /// Whether any of the subjects was written within the read-after-write window.
+    pub(super) fn recently_written(&self, subjects: &[Subject<'_>]) -> bool {
+        let window = self.config.read_after_write;
+        let written = self.written.lock().unwrap();
+        subjects.iter().any(|subject| {
+            written
+                .get(&subject.key())
+                .is_some_and(|at| at.elapsed() < window)
+        })
+    }
+}
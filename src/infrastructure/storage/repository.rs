@@ -1,13 +1,31 @@
 use crate::domain::interfaces;
 use crate::domain::interfaces::{Cache, Database};
-use crate::domain::models::Order;
+use crate::domain::models::{CustomerOrders, Order, OrderLookup, OrderPage, OrderSummary};
+use super::customer_cache::CustomerCache;
+use super::generations::{Generations, Read};
+use super::not_found_cache::NotFoundCache;
+use super::single_flight::SingleFlight;
+use std::collections::HashMap;
 use std::error::Error;
+use std::time::Duration;
 use axum::async_trait;
 use log::{log, Level};
 
+/// How long customer histories are served from memory
+const CUSTOMER_CACHE_TTL: Duration = Duration::from_secs(30);
+
+/// How long uids that weren't found are answered with `None` without asking the database
+const NOT_FOUND_TTL: Duration = Duration::from_secs(5);
+
 pub struct Repository<C, D> {
     cache: C,
     database: D,
+    customers: CustomerCache,
+    not_found: NotFoundCache,
+    /// Database reads of orders missing from the cache
+    reads: SingleFlight<Option<Order>>,
+    /// Bumped by inserts and removals so that reads racing them don't record what they found
+    generations: Generations,
 }
 
 impl<C, D> Repository<C, D>
@@ -19,10 +37,48 @@ where
         Self {
             cache,
             database,
+            customers: CustomerCache::new(CUSTOMER_CACHE_TTL),
+            not_found: NotFoundCache::new(NOT_FOUND_TTL),
+            reads: SingleFlight::new(),
+            generations: Generations::new(),
+        }
+    }
+}
+
+impl<C, D> Repository<C, D>
+where
+    C: Cache,
+    D: Database,
+{
+    /// Caches what a read of `id` found, unless a write of it raced the read.
+    async fn record(&self, id: &str, found: Option<&Order>, read: &Read<'_>) {
+        // Writes bump the generation before they invalidate, so a write either sees what is
+        // recorded here or is noticed below and it's taken back
+        match found {
+            Some(order) => self.cache.add(id.to_string(), order.clone()).await,
+            None => self.not_found.add(id).await,
+        }
+        if read.outdated() {
+            match found {
+                Some(_) => {
+                    self.cache.remove(id).await;
+                }
+                None => self.not_found.invalidate(id).await,
+            }
         }
     }
 }
 
+impl<C, D> Default for Repository<C, D>
+where
+    C: Cache + Default,
+    D: Database + Default,
+{
+    fn default() -> Self {
+        Self::new(C::default(), D::default())
+    }
+}
+
 #[async_trait]
 impl<C, D> interfaces::Repository for Repository<C, D>
 where
@@ -33,12 +89,22 @@ where
     
     type Error = Box<dyn Error>;
     async fn insert(&self, order: Order) -> Result<(), Self::Error> {
-        self.database.insert(order.clone()).await
+        let customer_id = order.customer_id.clone();
+        let order_uid = order.order_uid.clone();
+        self.database.insert(order).await?;
+        self.generations.bump(&order_uid);
+        self.not_found.invalidate(&order_uid).await;
+        self.reads.forget(&order_uid);
+        self.customers.invalidate(&customer_id).await;
+        Ok(())
     }
 
     async fn remove(&self, id: &str) -> Result<(), Self::Error> {
+        self.database.remove(id).await?;
+        self.generations.bump(id);
         self.cache.remove(id).await;
-        self.database.remove(id).await
+        self.reads.forget(id);
+        Ok(())
     }
 
     async fn get(&self, id: &str) -> Result<Option<Order>, Self::Error> {
@@ -49,19 +115,91 @@ where
         self.database.get(id).await
     }
 
+    /// Concurrent misses of the same uid share one database read, uids that weren't found
+    /// are answered from memory for a few seconds.
     async fn get_and_cache(&self, id: &str) -> Result<Option<Order>, Self::Error> {
-        let found = self.get(id).await?;
-        match found {
-            Some(order) => {
-                if self.cache.get(id).await.is_none() {
-                    self.cache.add(id.to_string(), order.clone()).await;
-                    return Ok(Some(order));
-                }
-                Ok(Some(order))
+        if let Some(order) = self.cache.get(id).await {
+            log!(target: "repository", Level::Info, "Order with uid: {id} found in cache");
+            return Ok(Some(order));
+        }
+        if self.not_found.contains(id).await {
+            log!(target: "repository", Level::Info, "Order with uid: {id} recently not found");
+            return Ok(None);
+        }
+        let found = self
+            .reads
+            .run(id, || async {
+                let read = self.generations.begin(id);
+                let found = self.database.get(id).await?;
+                self.record(id, found.as_ref(), &read).await;
+                Ok::<_, Box<dyn Error>>(found)
+            })
+            .await?;
+        Ok(found)
+    }
+
+    /// Cached orders are served from memory, the rest are read from the database at once.
+    async fn get_many(&self, ids: &[String]) -> Result<Vec<Order>, Self::Error> {
+        let mut orders = Vec::with_capacity(ids.len());
+        let mut missing = Vec::new();
+        for id in ids {
+            match self.cache.get(id).await {
+                Some(order) => orders.push(order),
+                None => missing.push(id.clone()),
             }
-            None => {
-                Ok(None)
+        }
+        if !missing.is_empty() {
+            orders.extend(self.database.get_many(&missing).await?);
+        }
+        Ok(orders)
+    }
+
+    /// Like `get_and_cache` for many uids, the ones missing from both caches are read from the database at once.
+    async fn get_many_and_cache(&self, ids: &[String]) -> Result<Vec<Order>, Self::Error> {
+        let mut orders = Vec::with_capacity(ids.len());
+        let mut missing = Vec::new();
+        for id in ids {
+            if let Some(order) = self.cache.get(id).await {
+                orders.push(order);
+            } else if !self.not_found.contains(id).await {
+                missing.push(id.clone());
             }
         }
+        if missing.is_empty() {
+            return Ok(orders);
+        }
+        let reads: Vec<Read> = missing.iter().map(|id| self.generations.begin(id)).collect();
+        let mut found: HashMap<String, Order> = self
+            .database
+            .get_many(&missing)
+            .await?
+            .into_iter()
+            .map(|order| (order.order_uid.clone(), order))
+            .collect();
+        for (id, read) in missing.iter().zip(&reads) {
+            let order = found.remove(id);
+            self.record(id, order.as_ref(), read).await;
+            orders.extend(order);
+        }
+        Ok(orders)
+    }
+
+    async fn find(&self, lookup: &OrderLookup, limit: i64, offset: i64) -> Result<Vec<OrderSummary>, Self::Error> {
+        self.database.find(lookup, limit, offset).await
+    }
+
+    async fn customer_orders(&self, customer_id: &str, limit: i64, offset: i64) -> Result<CustomerOrders, Self::Error> {
+        if let Some(orders) = self.customers.get(customer_id, limit, offset).await {
+            log!(target: "repository", Level::Info, "Orders of customer {customer_id} found in cache");
+            return Ok(orders);
+        }
+        let (orders, totals) = self.database.customer_orders(customer_id, limit + 1, offset).await?;
+        let orders = CustomerOrders {
+            customer_id: customer_id.to_string(),
+            totals,
+            page: OrderPage::from_overfetched(orders, limit, offset),
+        };
+        self.customers.add(limit, offset, orders.clone()).await;
+        Ok(orders)
     }
 }
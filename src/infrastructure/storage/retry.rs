@@ -0,0 +1,92 @@
+use deadpool::managed::TimeoutType;
+use deadpool_postgres::PoolError;
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+use std::time::Duration;
+use tokio_postgres::error::SqlState;
+
+/// How often and how patiently transient database failures are retried.
+#[derive(Clone, Copy, Debug)]
+pub struct RetryPolicy {
+    /// Attempts including the first one, one disables retries
+    pub max_attempts: u32,
+    /// Upper bound of the first delay, doubled with every attempt
+    pub base_delay: Duration,
+    pub max_delay: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            base_delay: Duration::from_millis(50),
+            max_delay: Duration::from_secs(1),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Random delay up to the exponential backoff of the attempt that failed, so that requests
+    /// that failed together don't retry together.
+    pub(super) fn delay(&self, attempt: u32) -> Duration {
+        let ceiling = self
+            .base_delay
+            .saturating_mul(2u32.saturating_pow(attempt.saturating_sub(1)))
+            .min(self.max_delay);
+        ceiling.mul_f64(rand::random::<f64>())
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub(super) enum Failure {
+    /// Retrying won't help: bad data, conflicts, timeouts of the request itself
+    Permanent,
+    /// Postgres gave up on the transaction, another attempt is likely to succeed
+    Contention,
+    /// Postgres can't be reached or dropped the connection, these trip the circuit breaker
+    Unavailable,
+}
+
+pub(super) fn classify(error: &(dyn Error + 'static)) -> Failure {
+    if let Some(err) = error.downcast_ref::<PoolError>() {
+        return match err {
+            PoolError::Backend(err) => classify(err),
+            PoolError::Timeout(TimeoutType::Create) => Failure::Unavailable,
+            _ => Failure::Permanent,
+        };
+    }
+    let Some(err) = error.downcast_ref::<tokio_postgres::Error>() else {
+        return Failure::Permanent;
+    };
+    match err.code() {
+        Some(code) if *code == SqlState::T_R_SERIALIZATION_FAILURE || *code == SqlState::T_R_DEADLOCK_DETECTED => {
+            Failure::Contention
+        }
+        Some(code)
+            if code.code().starts_with("08")
+                || *code == SqlState::ADMIN_SHUTDOWN
+                || *code == SqlState::CRASH_SHUTDOWN
+                || *code == SqlState::CANNOT_CONNECT_NOW =>
+        {
+            Failure::Unavailable
+        }
+        Some(_) => Failure::Permanent,
+        None if err.is_closed() || err.source().is_some_and(|source| source.is::<std::io::Error>()) => {
+            Failure::Unavailable
+        }
+        None => Failure::Permanent,
+    }
+}
+
+/// Failure that must not be retried whatever its class, e.g. a lost connection during a commit that may have
+/// gone through. Only wraps errors on their way out of an attempt, callers get the inner error.
+#[derive(Debug)]
+pub(super) struct NoRetry(pub Box<dyn Error>);
+
+impl Display for NoRetry {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl Error for NoRetry {}
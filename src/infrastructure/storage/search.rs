@@ -0,0 +1,65 @@
+use crate::domain::interfaces;
+use crate::domain::models::OrderSummary;
+use crate::infrastructure::Database;
+use axum::async_trait;
+use std::error::Error;
+use super::summaries::{summary, SUMMARY_COLUMNS, SUMMARY_JOINS};
+
+/// Every word of the query becomes a prefix match. Candidates are collected through the
+/// per-table indexes with any of the words, then the whole order document has to match all of them.
+fn search_query() -> String {
+    format!(
+        "WITH terms AS (
+            SELECT to_tsquery('simple', string_agg(quote_literal(lexeme) || ':*', ' & ')) AS all_terms,
+                   to_tsquery('simple', string_agg(quote_literal(lexeme) || ':*', ' | ')) AS any_term
+            FROM unnest(tsvector_to_array(to_tsvector('simple', translate($1, '+', ' ')))) AS lexeme
+        ),
+        candidates AS (
+            SELECT o.order_uid FROM Orders o, terms WHERE o.search_vector @@ terms.any_term
+            UNION
+            SELECT od.order_uid FROM Deliveries d JOIN OrderDeliveries od ON od.delivery_id = d.id, terms
+            WHERE d.search_vector @@ terms.any_term
+            UNION
+            SELECT i.order_uid FROM Items i, terms WHERE i.search_vector @@ terms.any_term
+        ),
+        documents AS (
+            SELECT c.order_uid,
+                   o.search_vector
+                       || coalesce((SELECT tsvector_agg(d.search_vector) FROM Deliveries d
+                                    JOIN OrderDeliveries od ON od.delivery_id = d.id WHERE od.order_uid = c.order_uid), '')
+                       || coalesce((SELECT tsvector_agg(i.search_vector) FROM Items i
+                                    WHERE i.order_uid = c.order_uid), '')
+                       AS document
+            FROM candidates c JOIN Orders o ON o.order_uid = c.order_uid
+        ),
+        ranked AS (
+            SELECT documents.order_uid, ts_rank(documents.document, terms.all_terms) AS rank
+            FROM documents, terms
+            WHERE documents.document @@ terms.all_terms
+        )
+        SELECT {SUMMARY_COLUMNS}
+        FROM ranked r
+        JOIN Orders o ON o.order_uid = r.order_uid
+        {SUMMARY_JOINS}
+        ORDER BY r.rank DESC, o.date_created DESC, o.order_uid
+        LIMIT $2 OFFSET $3"
+    )
+}
+
+#[async_trait]
+impl interfaces::OrderSearch for Database {
+    type Error = Box<dyn Error>;
+
+    async fn search(&self, query: &str, limit: i64, offset: i64) -> Result<Vec<OrderSummary>, Self::Error> {
+        let search = search_query();
+        self.resilient("search", || async {
+            let mut instance = self.reader(&[]).await?;
+            let transaction = instance.build_transaction().read_only(true).start().await?;
+            Self::apply_deadline(&transaction).await?;
+            let rows = transaction.query(&search, &[&query, &limit, &offset]).await?;
+            transaction.commit().await?;
+            Ok(rows.iter().map(summary).collect())
+        })
+        .await
+    }
+}
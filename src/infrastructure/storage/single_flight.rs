@@ -0,0 +1,38 @@
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use tokio::sync::OnceCell;
+
+/// Concurrent loads of the same key share one call.
+/// Failures aren't shared: waiters of a failed or cancelled call load in turn until one succeeds.
+pub(super) struct SingleFlight<T> {
+    calls: Mutex<HashMap<String, Arc<OnceCell<T>>>>,
+}
+
+impl<T: Clone> SingleFlight<T> {
+    pub(super) fn new() -> Self {
+        Self {
+            calls: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Result of `load`, or of the call for `key` already in flight.
+    pub(super) async fn run<E, F, Fut>(&self, key: &str, load: F) -> Result<T, E>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<T, E>>,
+    {
+        let call = self.calls.lock().unwrap().entry(key.to_string()).or_default().clone();
+        let result = call.get_or_try_init(load).await.cloned();
+        let mut calls = self.calls.lock().unwrap();
+        if calls.get(key).is_some_and(|current| Arc::ptr_eq(current, &call)) {
+            calls.remove(key);
+        }
+        result
+    }
+
+    /// Loads of `key` started after this don't wait for the one in flight, its result may be stale.
+    pub(super) fn forget(&self, key: &str) {
+        self.calls.lock().unwrap().remove(key);
+    }
+}
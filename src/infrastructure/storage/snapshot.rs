@@ -0,0 +1,48 @@
+use crate::domain::models::Order;
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+
+const MAGIC: &[u8; 4] = b"WBCS";
+/// Bumped when the layout of the file changes. Orders are written with field names, so added fields with defaults
+/// and reordered ones still load, snapshots with fields that no longer decode are ignored like damaged ones
+const VERSION: u32 = 2;
+const HEADER_LEN: usize = MAGIC.len() + 4;
+const CHECKSUM_LEN: usize = 4;
+
+/// Snapshot file that can't be used: foreign, written by another version or damaged.
+#[derive(Debug)]
+pub struct InvalidSnapshot(pub String);
+
+impl Display for InvalidSnapshot {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Invalid cache snapshot: {}", self.0)
+    }
+}
+
+impl Error for InvalidSnapshot {}
+
+/// Magic and version, the orders as a MessagePack array of maps and a CRC32 of everything before it.
+pub(super) fn encode(orders: &[Order]) -> Result<Vec<u8>, Box<dyn Error>> {
+    let mut bytes = Vec::with_capacity(HEADER_LEN);
+    bytes.extend_from_slice(MAGIC);
+    bytes.extend_from_slice(&VERSION.to_le_bytes());
+    rmp_serde::encode::write_named(&mut bytes, orders)?;
+    let checksum = crc32fast::hash(&bytes);
+    bytes.extend_from_slice(&checksum.to_le_bytes());
+    Ok(bytes)
+}
+
+pub(super) fn decode(bytes: &[u8]) -> Result<Vec<Order>, InvalidSnapshot> {
+    if bytes.len() < HEADER_LEN + CHECKSUM_LEN || &bytes[..MAGIC.len()] != MAGIC {
+        return Err(InvalidSnapshot("not a cache snapshot".to_string()));
+    }
+    let version = u32::from_le_bytes(bytes[MAGIC.len()..HEADER_LEN].try_into().unwrap());
+    if version != VERSION {
+        return Err(InvalidSnapshot(format!("version {version}, expected {VERSION}")));
+    }
+    let (content, checksum) = bytes.split_at(bytes.len() - CHECKSUM_LEN);
+    if crc32fast::hash(content).to_le_bytes() != checksum {
+        return Err(InvalidSnapshot("checksum mismatch".to_string()));
+    }
+    rmp_serde::from_slice(&content[HEADER_LEN..]).map_err(|err| InvalidSnapshot(err.to_string()))
+}
@@ -0,0 +1,321 @@
+use crate::domain::interfaces::{self, OrderStream};
+use crate::domain::models::{CurrencyTotal, CustomerTotals, Order, OrderFilter, OrderLookup, OrderSummary};
+use super::errors::Conflict;
+use axum::async_trait;
+use futures::StreamExt;
+use rusqlite::{params, params_from_iter, Connection, ErrorCode, OptionalExtension, ToSql};
+use std::error::Error;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use tokio::sync::mpsc;
+use tokio_stream::wrappers::ReceiverStream;
+
+/// Orders read at once by exports
+const EXPORT_BATCH: usize = 500;
+
+/// Orders are kept whole as JSON, the columns next to them are only there to look orders up.
+const SCHEMA: &str = "
+    PRAGMA foreign_keys = ON;
+    CREATE TABLE IF NOT EXISTS Orders
+    (
+        order_uid    TEXT PRIMARY KEY,
+        track_number TEXT    NOT NULL,
+        customer_id  TEXT    NOT NULL,
+        date_created TEXT    NOT NULL,
+        payment_id   TEXT    NOT NULL UNIQUE,
+        request_id   TEXT    NOT NULL,
+        currency     TEXT    NOT NULL,
+        amount       INTEGER NOT NULL,
+        data         TEXT    NOT NULL
+    );
+    CREATE TABLE IF NOT EXISTS Items
+    (
+        order_uid TEXT    NOT NULL REFERENCES Orders (order_uid) ON DELETE CASCADE,
+        position  INTEGER NOT NULL,
+        chrt_id   INTEGER NOT NULL,
+        nm_id     INTEGER NOT NULL,
+        PRIMARY KEY (order_uid, position)
+    );
+    CREATE INDEX IF NOT EXISTS orders_track_number_idx ON Orders (track_number);
+    CREATE INDEX IF NOT EXISTS orders_customer_id_idx ON Orders (customer_id, date_created);
+    CREATE INDEX IF NOT EXISTS orders_request_id_idx ON Orders (request_id);
+    CREATE INDEX IF NOT EXISTS items_chrt_id_idx ON Items (chrt_id);
+    CREATE INDEX IF NOT EXISTS items_nm_id_idx ON Items (nm_id);
+";
+
+/// Orders in a single SQLite file, for running the service without Postgres.
+/// Queries run one at a time on a blocking thread.
+#[derive(Clone)]
+pub struct SqliteDatabase {
+    connection: Arc<Mutex<Connection>>,
+}
+
+impl SqliteDatabase {
+    /// Opens or creates the database file, `:memory:` keeps it in memory.
+    pub fn open(path: impl AsRef<Path>) -> Result<SqliteDatabase, Box<dyn Error>> {
+        let connection = Connection::open(path)?;
+        connection.execute_batch(SCHEMA)?;
+        Ok(SqliteDatabase {
+            connection: Arc::new(Mutex::new(connection)),
+        })
+    }
+
+    async fn run<T, F>(&self, work: F) -> Result<T, Box<dyn Error>>
+    where
+        T: Send + 'static,
+        F: FnOnce(&mut Connection) -> rusqlite::Result<T> + Send + 'static,
+    {
+        let connection = self.connection.clone();
+        let result = tokio::task::spawn_blocking(move || work(&mut connection.lock().unwrap())).await?;
+        result.map_err(|err| match err.sqlite_error_code() {
+            Some(ErrorCode::ConstraintViolation) => Conflict(format!("Order or its payment ({err})")).into(),
+            _ => err.into(),
+        })
+    }
+}
+
+#[async_trait]
+impl interfaces::Database for SqliteDatabase {
+    type Error = Box<dyn Error>;
+
+    async fn insert(&self, data: Order) -> Result<(), Self::Error> {
+        let json = serde_json::to_string(&data)?;
+        self.run(move |connection| {
+            let transaction = connection.transaction()?;
+            transaction.execute(
+                "INSERT INTO Orders VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+                params![
+                    data.order_uid,
+                    data.track_number,
+                    data.customer_id,
+                    data.date_created,
+                    data.payment.transaction,
+                    data.payment.request_id,
+                    data.payment.currency,
+                    data.payment.amount,
+                    json,
+                ],
+            )?;
+            {
+                let mut items_insert = transaction.prepare("INSERT INTO Items VALUES (?1, ?2, ?3, ?4)")?;
+                for (position, item) in data.items.iter().enumerate() {
+                    items_insert.execute(params![data.order_uid, position as i64, item.chrt_id, item.nm_id])?;
+                }
+            }
+            transaction.commit()
+        })
+        .await
+    }
+
+    async fn remove(&self, id: &str) -> Result<(), Self::Error> {
+        let id = id.to_string();
+        self.run(move |connection| connection.execute("DELETE FROM Orders WHERE order_uid = ?1", [id]).map(|_| ()))
+            .await
+    }
+
+    async fn get(&self, id: &str) -> Result<Option<Order>, Self::Error> {
+        let id = id.to_string();
+        let json = self
+            .run(move |connection| {
+                connection
+                    .query_row("SELECT data FROM Orders WHERE order_uid = ?1", [id], |row| row.get::<_, String>(0))
+                    .optional()
+            })
+            .await?;
+        match json {
+            Some(json) => Ok(Some(serde_json::from_str(&json)?)),
+            None => Ok(None),
+        }
+    }
+
+    async fn get_many(&self, ids: &[String]) -> Result<Vec<Order>, Self::Error> {
+        if ids.is_empty() {
+            return Ok(Vec::new());
+        }
+        let ids = ids.to_vec();
+        let rows = self
+            .run(move |connection| {
+                let mut rows = Vec::with_capacity(ids.len());
+                // Older SQLite builds allow only 999 bound parameters per statement
+                for chunk in ids.chunks(500) {
+                    let placeholders = vec!["?"; chunk.len()].join(", ");
+                    let mut select = connection.prepare(&format!("SELECT data FROM Orders WHERE order_uid IN ({placeholders})"))?;
+                    let found = select.query_map(params_from_iter(chunk), |row| row.get::<_, String>(0))?;
+                    rows.extend(found.collect::<rusqlite::Result<Vec<String>>>()?);
+                }
+                Ok(rows)
+            })
+            .await?;
+        Ok(rows.iter().map(|json| serde_json::from_str(json)).collect::<Result<_, _>>()?)
+    }
+
+    async fn existing(&self, ids: &[String]) -> Result<Vec<String>, Self::Error> {
+        if ids.is_empty() {
+            return Ok(Vec::new());
+        }
+        let ids = ids.to_vec();
+        self.run(move |connection| {
+            let mut existing = Vec::with_capacity(ids.len());
+            for chunk in ids.chunks(500) {
+                let placeholders = vec!["?"; chunk.len()].join(", ");
+                let mut select = connection.prepare(&format!("SELECT order_uid FROM Orders WHERE order_uid IN ({placeholders})"))?;
+                let found = select.query_map(params_from_iter(chunk), |row| row.get::<_, String>(0))?;
+                existing.extend(found.collect::<rusqlite::Result<Vec<String>>>()?);
+            }
+            Ok(existing)
+        })
+        .await
+    }
+
+    async fn find(&self, lookup: &OrderLookup, limit: i64, offset: i64) -> Result<Vec<OrderSummary>, Self::Error> {
+        let lookup = lookup.clone();
+        let rows = self
+            .run(move |connection| select_summaries(connection, &lookup, limit, offset))
+            .await?;
+        summaries(rows)
+    }
+
+    async fn customer_totals(&self, customer_id: &str) -> Result<CustomerTotals, Self::Error> {
+        let customer_id = customer_id.to_string();
+        let rows = self.run(move |connection| select_totals(connection, &customer_id)).await?;
+        Ok(totals(rows))
+    }
+
+    async fn customer_orders(
+        &self,
+        customer_id: &str,
+        limit: i64,
+        offset: i64,
+    ) -> Result<(Vec<OrderSummary>, CustomerTotals), Self::Error> {
+        let customer_id = customer_id.to_string();
+        let (orders, totals_rows) = self
+            .run(move |connection| {
+                // A read transaction keeps writers of other connections out until both are read
+                let transaction = connection.transaction()?;
+                let lookup = OrderLookup::CustomerId(customer_id.clone());
+                let orders = select_summaries(&transaction, &lookup, limit, offset)?;
+                let totals = select_totals(&transaction, &customer_id)?;
+                transaction.commit()?;
+                Ok((orders, totals))
+            })
+            .await?;
+        Ok((summaries(orders)?, totals(totals_rows)))
+    }
+}
+
+/// Per-currency order count, amount spent and the last order date.
+type CurrencyRow = (String, i64, i64, String);
+
+/// Orders matching the lookup as JSON, newest first.
+fn select_summaries(connection: &Connection, lookup: &OrderLookup, limit: i64, offset: i64) -> rusqlite::Result<Vec<String>> {
+    let (condition, key): (&str, &dyn ToSql) = match lookup {
+        OrderLookup::TrackNumber(track_number) => ("o.track_number = ?1", track_number),
+        OrderLookup::CustomerId(customer_id) => ("o.customer_id = ?1", customer_id),
+        OrderLookup::Transaction(transaction) => ("o.payment_id = ?1", transaction),
+        OrderLookup::RequestId(request_id) => ("o.request_id = ?1", request_id),
+        OrderLookup::ChrtId(chrt_id) => (
+            "EXISTS (SELECT 1 FROM Items i WHERE i.order_uid = o.order_uid AND i.chrt_id = ?1)",
+            chrt_id,
+        ),
+        OrderLookup::NmId(nm_id) => (
+            "EXISTS (SELECT 1 FROM Items i WHERE i.order_uid = o.order_uid AND i.nm_id = ?1)",
+            nm_id,
+        ),
+    };
+    let mut statement = connection.prepare(&format!(
+        "SELECT o.data FROM Orders o
+         WHERE {condition}
+         ORDER BY o.date_created DESC, o.order_uid
+         LIMIT ?2 OFFSET ?3"
+    ))?;
+    let rows = statement.query_map(params![key, limit, offset], |row| row.get::<_, String>(0))?;
+    rows.collect()
+}
+
+fn select_totals(connection: &Connection, customer_id: &str) -> rusqlite::Result<Vec<CurrencyRow>> {
+    let mut statement = connection.prepare(
+        "SELECT currency, count(*), sum(amount), max(date_created) FROM Orders
+         WHERE customer_id = ?1
+         GROUP BY currency
+         ORDER BY currency",
+    )?;
+    let rows = statement.query_map([customer_id], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?)))?;
+    rows.collect()
+}
+
+fn summaries(rows: Vec<String>) -> Result<Vec<OrderSummary>, Box<dyn Error>> {
+    let mut summaries = Vec::with_capacity(rows.len());
+    for json in rows {
+        summaries.push(OrderSummary::from(&serde_json::from_str::<Order>(&json)?));
+    }
+    Ok(summaries)
+}
+
+fn totals(rows: Vec<CurrencyRow>) -> CustomerTotals {
+    let mut totals = CustomerTotals::default();
+    for (currency, order_count, amount, last_order_date) in rows {
+        totals.order_count += order_count;
+        if totals.last_order_date.as_ref().is_none_or(|last| *last < last_order_date) {
+            totals.last_order_date = Some(last_order_date);
+        }
+        totals.spent.push(CurrencyTotal { currency, amount });
+    }
+    totals
+}
+
+#[async_trait]
+impl interfaces::OrderExport for SqliteDatabase {
+    type Error = Box<dyn Error>;
+
+    /// Walks all orders in pages and filters them here, it's meant for development-sized databases.
+    async fn export(&self, filter: &OrderFilter) -> Result<OrderStream, Self::Error> {
+        let (sender, receiver) = mpsc::channel(EXPORT_BATCH);
+        let database = self.clone();
+        let filter = filter.clone();
+        tokio::spawn(async move {
+            let mut after = (String::new(), String::new());
+            loop {
+                let (date_created, order_uid) = after.clone();
+                let page = database
+                    .run(move |connection| {
+                        let mut statement = connection.prepare(
+                            "SELECT data FROM Orders
+                             WHERE (date_created, order_uid) > (?1, ?2)
+                             ORDER BY date_created, order_uid
+                             LIMIT ?3",
+                        )?;
+                        let rows = statement
+                            .query_map(params![date_created, order_uid, EXPORT_BATCH as i64], |row| row.get(0))?;
+                        rows.collect::<rusqlite::Result<Vec<String>>>()
+                    })
+                    .await
+                    .map_err(|err| err.to_string());
+                let page = match page {
+                    Ok(page) => page,
+                    Err(err) => {
+                        let _ = sender.send(Err(err.into())).await;
+                        return;
+                    }
+                };
+                let last = page.len() < EXPORT_BATCH;
+                for json in page {
+                    let order = match serde_json::from_str::<Order>(&json) {
+                        Ok(order) => order,
+                        Err(err) => {
+                            let _ = sender.send(Err(err.into())).await;
+                            return;
+                        }
+                    };
+                    after = (order.date_created.clone(), order.order_uid.clone());
+                    if filter.matches(&order) && sender.send(Ok(order)).await.is_err() {
+                        return;
+                    }
+                }
+                if last {
+                    return;
+                }
+            }
+        });
+        Ok(ReceiverStream::new(receiver).boxed())
+    }
+}
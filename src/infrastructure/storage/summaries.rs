@@ -0,0 +1,47 @@
+use crate::domain::models::{OrderLookup, OrderSummary};
+use tokio_postgres::types::ToSql;
+use tokio_postgres::Row;
+
+/// Columns of an `OrderSummary` for orders `o` joined with `SUMMARY_JOINS`.
+pub(super) const SUMMARY_COLUMNS: &str = "o.order_uid, o.track_number, o.customer_id, d.name AS customer_name,
+    o.delivery_service, o.date_created, p.amount, p.currency,
+    (SELECT count(*) FROM Items i WHERE i.order_uid = o.order_uid) AS item_count";
+
+pub(super) const SUMMARY_JOINS: &str = "JOIN OrderDeliveries od ON od.order_uid = o.order_uid
+    JOIN Deliveries d ON d.id = od.delivery_id
+    JOIN OrderPayments op ON op.order_uid = o.order_uid
+    JOIN Payments p ON p.transaction = op.payment_id";
+
+pub(super) fn summary(row: &Row) -> OrderSummary {
+    OrderSummary {
+        order_uid: row.get("order_uid"),
+        track_number: row.get("track_number"),
+        customer_id: row.get("customer_id"),
+        customer_name: row.get("customer_name"),
+        delivery_service: row.get("delivery_service"),
+        date_created: row.get("date_created"),
+        amount: row.get("amount"),
+        currency: row.get("currency"),
+        item_count: row.get("item_count"),
+    }
+}
+
+/// Condition on orders `o` joined with `SUMMARY_JOINS` selecting the lookup's orders,
+/// its key goes into the parameter `placeholder`.
+pub(super) fn lookup_condition<'a>(lookup: &'a OrderLookup, placeholder: &str) -> (String, &'a (dyn ToSql + Sync)) {
+    let (condition, key): (String, &(dyn ToSql + Sync)) = match lookup {
+        OrderLookup::TrackNumber(track_number) => (format!("o.track_number = {placeholder}"), track_number),
+        OrderLookup::CustomerId(customer_id) => (format!("o.customer_id = {placeholder}"), customer_id),
+        OrderLookup::Transaction(transaction) => (format!("p.transaction = {placeholder}"), transaction),
+        OrderLookup::RequestId(request_id) => (format!("p.request_id = {placeholder}"), request_id),
+        OrderLookup::ChrtId(chrt_id) => (
+            format!("EXISTS (SELECT 1 FROM Items i WHERE i.order_uid = o.order_uid AND i.chrt_id = {placeholder})"),
+            chrt_id,
+        ),
+        OrderLookup::NmId(nm_id) => (
+            format!("EXISTS (SELECT 1 FROM Items i WHERE i.order_uid = o.order_uid AND i.nm_id = {placeholder})"),
+            nm_id,
+        ),
+    };
+    (condition, key)
+}
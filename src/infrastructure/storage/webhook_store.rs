@@ -0,0 +1,248 @@
+use crate::domain::interfaces;
+use crate::domain::models::{OrderEvent, WebhookDelivery, WebhookSubscription, WebhookSubscriptionRequest};
+use crate::infrastructure::Database;
+use axum::async_trait;
+use std::error::Error;
+use std::time::Duration;
+use tokio_postgres::Row;
+
+const SUBSCRIPTION_COLUMNS: &str = "s.id, s.url, s.event_types, s.delivery_service, s.customer_id, s.secret, s.enabled,
+    s.consecutive_failures, s.created_at::TEXT AS created_at, s.disabled_at::TEXT AS disabled_at";
+
+const DELIVERY_COLUMNS: &str = "d.id AS delivery_id, d.subscription_id, d.event_id, d.event_type, d.status, d.attempts,
+    d.last_status_code, d.last_error, d.created_at::TEXT AS delivery_created_at,
+    d.delivered_at::TEXT AS delivered_at, d.payload::TEXT AS payload";
+
+fn subscription(row: &Row) -> WebhookSubscription {
+    WebhookSubscription {
+        id: row.get("id"),
+        url: row.get("url"),
+        event_types: row.get("event_types"),
+        delivery_service: row.get("delivery_service"),
+        customer_id: row.get("customer_id"),
+        secret: row.get("secret"),
+        enabled: row.get("enabled"),
+        consecutive_failures: row.get("consecutive_failures"),
+        created_at: row.get("created_at"),
+        disabled_at: row.get("disabled_at"),
+    }
+}
+
+fn delivery(row: &Row) -> Result<WebhookDelivery, Box<dyn Error>> {
+    Ok(WebhookDelivery {
+        id: row.get("delivery_id"),
+        subscription_id: row.get("subscription_id"),
+        event_id: row.get("event_id"),
+        event_type: row.get("event_type"),
+        status: row.get("status"),
+        attempts: row.get("attempts"),
+        last_status_code: row.get("last_status_code"),
+        last_error: row.get("last_error"),
+        created_at: row.get("delivery_created_at"),
+        delivered_at: row.get("delivered_at"),
+        payload: serde_json::from_str(row.get("payload"))?,
+    })
+}
+
+#[async_trait]
+impl interfaces::WebhookStore for Database {
+    type Error = Box<dyn Error>;
+
+    async fn create(&self, request: WebhookSubscriptionRequest) -> Result<WebhookSubscription, Self::Error> {
+        let row = self
+            .pool
+            .get()
+            .await?
+            .query_one(
+                &format!(
+                    "INSERT INTO WebhookSubscriptions AS s (url, event_types, delivery_service, customer_id, secret, enabled)
+                     VALUES ($1, $2, $3, $4, $5, $6) RETURNING {SUBSCRIPTION_COLUMNS}"
+                ),
+                &[
+                    &request.url,
+                    &request.event_types,
+                    &request.delivery_service,
+                    &request.customer_id,
+                    &request.secret,
+                    &request.enabled,
+                ],
+            )
+            .await?;
+        Ok(subscription(&row))
+    }
+
+    async fn list(&self) -> Result<Vec<WebhookSubscription>, Self::Error> {
+        let rows = self
+            .pool
+            .get()
+            .await?
+            .query(&format!("SELECT {SUBSCRIPTION_COLUMNS} FROM WebhookSubscriptions s ORDER BY s.id"), &[])
+            .await?;
+        Ok(rows.iter().map(subscription).collect())
+    }
+
+    async fn get(&self, id: i64) -> Result<Option<WebhookSubscription>, Self::Error> {
+        let row = self
+            .pool
+            .get()
+            .await?
+            .query_opt(&format!("SELECT {SUBSCRIPTION_COLUMNS} FROM WebhookSubscriptions s WHERE s.id = $1"), &[&id])
+            .await?;
+        Ok(row.as_ref().map(subscription))
+    }
+
+    async fn update(&self, id: i64, request: WebhookSubscriptionRequest) -> Result<Option<WebhookSubscription>, Self::Error> {
+        let row = self
+            .pool
+            .get()
+            .await?
+            .query_opt(
+                &format!(
+                    "UPDATE WebhookSubscriptions s SET url = $2, event_types = $3, delivery_service = $4,
+                         customer_id = $5, secret = $6, enabled = $7,
+                         consecutive_failures = CASE WHEN $7 AND NOT s.enabled THEN 0 ELSE s.consecutive_failures END,
+                         disabled_at = CASE WHEN $7 THEN NULL ELSE coalesce(s.disabled_at, now()) END
+                     WHERE s.id = $1 RETURNING {SUBSCRIPTION_COLUMNS}"
+                ),
+                &[
+                    &id,
+                    &request.url,
+                    &request.event_types,
+                    &request.delivery_service,
+                    &request.customer_id,
+                    &request.secret,
+                    &request.enabled,
+                ],
+            )
+            .await?;
+        Ok(row.as_ref().map(subscription))
+    }
+
+    async fn delete(&self, id: i64) -> Result<bool, Self::Error> {
+        let deleted = self
+            .pool
+            .get()
+            .await?
+            .execute("DELETE FROM WebhookSubscriptions WHERE id = $1", &[&id])
+            .await?;
+        Ok(deleted > 0)
+    }
+
+    async fn deliveries(&self, subscription_id: i64, limit: i64) -> Result<Vec<WebhookDelivery>, Self::Error> {
+        let rows = self
+            .pool
+            .get()
+            .await?
+            .query(
+                &format!(
+                    "SELECT {DELIVERY_COLUMNS} FROM WebhookDeliveries d
+                     WHERE d.subscription_id = $1 ORDER BY d.id DESC LIMIT $2"
+                ),
+                &[&subscription_id, &limit],
+            )
+            .await?;
+        rows.iter().map(delivery).collect()
+    }
+
+    async fn enqueue(&self, event: &OrderEvent) -> Result<u64, Self::Error> {
+        let payload = serde_json::to_string(&event.payload)?;
+        let enqueued = self
+            .pool
+            .get()
+            .await?
+            .execute(
+                "INSERT INTO WebhookDeliveries(subscription_id, event_id, event_type, payload)
+                 SELECT s.id, $1, $2, CAST($3::TEXT AS JSONB) FROM WebhookSubscriptions s
+                 WHERE s.enabled AND $2 = ANY(s.event_types)
+                   AND (s.delivery_service IS NULL OR s.delivery_service = CAST($3::TEXT AS JSONB) ->> 'delivery_service')
+                   AND (s.customer_id IS NULL OR s.customer_id = CAST($3::TEXT AS JSONB) ->> 'customer_id')
+                 ON CONFLICT DO NOTHING",
+                &[&event.id, &event.event_type, &payload],
+            )
+            .await?;
+        Ok(enqueued)
+    }
+
+    async fn claim_due(&self, limit: i64, lease: Duration) -> Result<Vec<(WebhookDelivery, WebhookSubscription)>, Self::Error> {
+        let rows = self
+            .pool
+            .get()
+            .await?
+            .query(
+                &format!(
+                    "WITH claimed AS (
+                         UPDATE WebhookDeliveries SET next_attempt_at = now() + make_interval(secs => $2)
+                         WHERE id IN (
+                             SELECT d.id FROM WebhookDeliveries d
+                             JOIN WebhookSubscriptions s ON s.id = d.subscription_id
+                             WHERE d.status = 'pending' AND d.next_attempt_at <= now() AND s.enabled
+                             ORDER BY d.id
+                             LIMIT $1
+                             FOR UPDATE OF d SKIP LOCKED
+                         )
+                         RETURNING *
+                     )
+                     SELECT {DELIVERY_COLUMNS}, {SUBSCRIPTION_COLUMNS} FROM claimed d
+                     JOIN WebhookSubscriptions s ON s.id = d.subscription_id
+                     ORDER BY d.id"
+                ),
+                &[&limit, &lease.as_secs_f64()],
+            )
+            .await?;
+        rows.iter()
+            .map(|row| Ok((delivery(row)?, subscription(row))))
+            .collect()
+    }
+
+    async fn record_success(&self, delivery: &WebhookDelivery, status_code: i32) -> Result<(), Self::Error> {
+        let mut client = self.pool.get().await?;
+        let transaction = client.transaction().await?;
+        transaction
+            .execute(
+                "UPDATE WebhookDeliveries SET status = 'delivered', attempts = attempts + 1, delivered_at = now(),
+                 last_status_code = $2, last_error = NULL WHERE id = $1",
+                &[&delivery.id, &status_code],
+            )
+            .await?;
+        transaction
+            .execute(
+                "UPDATE WebhookSubscriptions SET consecutive_failures = 0 WHERE id = $1",
+                &[&delivery.subscription_id],
+            )
+            .await?;
+        Ok(transaction.commit().await?)
+    }
+
+    async fn record_failure(
+        &self,
+        delivery: &WebhookDelivery,
+        status_code: Option<i32>,
+        error: &str,
+        retry_in: Option<Duration>,
+        disable_after: i32,
+    ) -> Result<(), Self::Error> {
+        let retry_in = retry_in.map(|delay| delay.as_secs_f64());
+        let mut client = self.pool.get().await?;
+        let transaction = client.transaction().await?;
+        transaction
+            .execute(
+                "UPDATE WebhookDeliveries SET attempts = attempts + 1, last_status_code = $2, last_error = $3,
+                 status = CASE WHEN $4::FLOAT8 IS NULL THEN 'failed' ELSE status END,
+                 next_attempt_at = CASE WHEN $4::FLOAT8 IS NULL THEN next_attempt_at
+                                        ELSE now() + make_interval(secs => $4::FLOAT8) END
+                 WHERE id = $1",
+                &[&delivery.id, &status_code, &error, &retry_in],
+            )
+            .await?;
+        transaction
+            .execute(
+                "UPDATE WebhookSubscriptions SET consecutive_failures = consecutive_failures + 1,
+                 enabled = enabled AND consecutive_failures + 1 < $2,
+                 disabled_at = CASE WHEN enabled AND consecutive_failures + 1 >= $2 THEN now() ELSE disabled_at END
+                 WHERE id = $1",
+                &[&delivery.subscription_id, &disable_after],
+            )
+            .await?;
+        Ok(transaction.commit().await?)
+    }
+}
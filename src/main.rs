@@ -1,37 +1,55 @@
+mod commands;
+
 use {
-    axum::routing::{get, post},
-    clap::Parser,
-    log::info,
-    std::sync::Arc,
-    wb_tech_l0::{
-        application::controllers::{add_order, get_order},
-        application::AppState,
-        infrastructure::{Cache, Database, OrderService, Repository},
-    },
+    clap::{error::ErrorKind, parser::ValueSource, ArgMatches, Args as _, CommandFactory, FromArgMatches, Parser},
+    commands::{with_database, Command, ServeArgs, StorageArgs},
 };
 
 #[derive(Parser, Debug)]
 struct Args {
-    //Database connection URI
-    #[arg(short, long, required = true)]
-    database: String,
+    #[command(subcommand)]
+    command: Option<Command>,
+    #[command(flatten)]
+    storage: StorageArgs,
+    //Server settings when it's started without a command
+    #[command(flatten)]
+    serve: ServeArgs,
+}
+
+/// Server settings are accepted before a command too, where they would be silently ignored.
+fn reject_misplaced_serve_args(matches: &ArgMatches) {
+    let Some(command) = matches.subcommand_name() else {
+        return;
+    };
+    let serve_args = ServeArgs::augment_args(clap::Command::new("serve"));
+    for arg in serve_args.get_arguments() {
+        if matches.value_source(arg.get_id().as_str()) == Some(ValueSource::CommandLine) {
+            let flag = arg.get_long().unwrap_or(arg.get_id().as_str());
+            Args::command()
+                .error(ErrorKind::ArgumentConflict, format!("--{flag} is a server setting, it can't be used with {command}"))
+                .exit();
+        }
+    }
 }
 
 #[tokio::main(flavor = "multi_thread", worker_threads = 3)]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
-    let addr = "0.0.0.0:7878";
     env_logger::init();
-    let args = Args::parse();
-    let cache = Cache::new();
-    let database = Database::new(args.database).await?;
-    let repository = Box::new(Repository::new(cache, database));
-    let order_service = Box::new(OrderService);
-    let app_state = Arc::new(AppState::new(repository, order_service));
-    let router = axum::Router::new()
-        .route("/order/:order_uid", get(get_order))
-        .route("/add_order", post(add_order))
-        .with_state(app_state);
-    let listener = tokio::net::TcpListener::bind(addr).await?;
-    info!("Listening on {addr}");
-    Ok(axum::serve(listener, router).await?)
+    let matches = Args::command().get_matches();
+    reject_misplaced_serve_args(&matches);
+    let args = Args::from_arg_matches(&matches).unwrap_or_else(|err| err.exit());
+    match args.command {
+        None => commands::serve(args.serve, args.storage.open().await?).await,
+        Some(Command::Serve(serve)) => commands::serve(serve, args.storage.open().await?).await,
+        Some(Command::Import(import)) => {
+            with_database!(args.storage.open().await?, database => commands::import(import, database).await)
+        }
+        Some(Command::Export(export)) => {
+            with_database!(args.storage.open().await?, database => commands::export(export, database).await)
+        }
+        Some(Command::Get(get)) => {
+            with_database!(args.storage.open().await?, database => commands::get(get, database).await)
+        }
+        Some(Command::Check(check)) => commands::check(check, args.storage.open().await).await,
+    }
 }
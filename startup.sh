@@ -1,4 +1,6 @@
 #!/bin/bash
 set -e
-refinery migrate -e DATABASE_URL -p ./migrations/V1__init_up.sql
-exec ./target/release/wb_tech_l0 --database $DATABASE_URL
\ No newline at end of file
+for migration in $(ls -v ./migrations/V*__*_up.sql); do
+  refinery migrate -e DATABASE_URL -p "$migration"
+done
+exec ./target/release/wb_tech_l0 --database $DATABASE_URL
@@ -0,0 +1,188 @@
+//! Fixtures shared by the integration tests, each test binary uses some of them.
+
+#![allow(dead_code)]
+
+use std::error::Error;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use wb_tech_l0::infrastructure::{Cache, MemoryDatabase, Repository};
+use wb_tech_l0::interfaces::Database;
+use wb_tech_l0::models::{CustomerTotals, Delivery, Item, Order, OrderLookup, OrderSummary, Payment};
+
+/// The real repository over orders kept in memory.
+pub type MemoryRepository = Repository<Cache, MemoryDatabase>;
+
+/// An order of one item paid in USD by customer `test` on 2021-11-26, the transaction is the uid.
+pub fn order(uid: &str) -> OrderBuilder {
+    OrderBuilder(Order {
+        order_uid: uid.to_string(),
+        track_number: "WBILMTESTTRACK".to_string(),
+        entry: "WBIL".to_string(),
+        delivery: Delivery { name: "Test Testov".to_string(), ..Default::default() },
+        payment: Payment {
+            transaction: uid.to_string(),
+            currency: "USD".to_string(),
+            amount: 1817,
+            ..Default::default()
+        },
+        items: vec![item(9934930)],
+        customer_id: "test".to_string(),
+        date_created: "2021-11-26T06:22:19Z".to_string(),
+        ..Default::default()
+    })
+}
+
+/// An item of the sample order with another `chrt_id`.
+pub fn item(chrt_id: i32) -> Item {
+    Item {
+        chrt_id,
+        track_number: "WBILMTESTTRACK".to_string(),
+        price: 453,
+        rid: "ab4219087a764ae0btest".to_string(),
+        name: "Mascaras".to_string(),
+        sale: 30,
+        size: "0".to_string(),
+        total_price: 317,
+        nm_id: 2389212,
+        brand: "Vivienne Sabo".to_string(),
+        status: 202,
+    }
+}
+
+pub struct OrderBuilder(Order);
+
+impl OrderBuilder {
+    pub fn track_number(mut self, track_number: &str) -> Self {
+        self.0.track_number = track_number.to_string();
+        self
+    }
+
+    pub fn entry(mut self, entry: &str) -> Self {
+        self.0.entry = entry.to_string();
+        self
+    }
+
+    pub fn delivery(mut self, delivery: Delivery) -> Self {
+        self.0.delivery = delivery;
+        self
+    }
+
+    pub fn paid(mut self, amount: i32, currency: &str) -> Self {
+        self.0.payment.amount = amount;
+        self.0.payment.currency = currency.to_string();
+        self
+    }
+
+    pub fn items(mut self, items: Vec<Item>) -> Self {
+        self.0.items = items;
+        self
+    }
+
+    pub fn customer_id(mut self, customer_id: &str) -> Self {
+        self.0.customer_id = customer_id.to_string();
+        self
+    }
+
+    pub fn date_created(mut self, date_created: &str) -> Self {
+        self.0.date_created = date_created.to_string();
+        self
+    }
+
+    /// Any other change.
+    pub fn with(mut self, change: impl FnOnce(&mut Order)) -> Self {
+        change(&mut self.0);
+        self
+    }
+
+    pub fn build(self) -> Order {
+        self.0
+    }
+}
+
+/// What reached a [`CountingDatabase`], shared with the test.
+#[derive(Default, Clone)]
+pub struct Counters {
+    reads: Arc<AtomicUsize>,
+    batches: Arc<Mutex<Vec<usize>>>,
+    queries: Arc<AtomicUsize>,
+}
+
+impl Counters {
+    /// Reads of single orders
+    pub fn reads(&self) -> usize {
+        self.reads.load(Ordering::SeqCst)
+    }
+
+    /// Sizes of the batches orders were read in
+    pub fn batches(&self) -> Vec<usize> {
+        self.batches.lock().unwrap().clone()
+    }
+
+    /// Lookups, customer totals and customer histories
+    pub fn queries(&self) -> usize {
+        self.queries.load(Ordering::SeqCst)
+    }
+}
+
+/// Memory database counting reads and queries. Reads of single orders take `delay` after reading,
+/// the first `failures` of them fail.
+#[derive(Default)]
+pub struct CountingDatabase {
+    pub inner: MemoryDatabase,
+    pub counters: Counters,
+    pub delay: Duration,
+    pub failures: usize,
+}
+
+#[axum::async_trait]
+impl Database for CountingDatabase {
+    type Error = Box<dyn Error>;
+
+    async fn insert(&self, data: Order) -> Result<(), Self::Error> {
+        self.inner.insert(data).await
+    }
+
+    async fn remove(&self, id: &str) -> Result<(), Self::Error> {
+        self.inner.remove(id).await
+    }
+
+    async fn get(&self, id: &str) -> Result<Option<Order>, Self::Error> {
+        let read = self.counters.reads.fetch_add(1, Ordering::SeqCst);
+        let found = self.inner.get(id).await?;
+        tokio::time::sleep(self.delay).await;
+        if read < self.failures {
+            return Err("Connection reset".into());
+        }
+        Ok(found)
+    }
+
+    async fn get_many(&self, ids: &[String]) -> Result<Vec<Order>, Self::Error> {
+        self.counters.batches.lock().unwrap().push(ids.len());
+        self.inner.get_many(ids).await
+    }
+
+    async fn existing(&self, ids: &[String]) -> Result<Vec<String>, Self::Error> {
+        self.inner.existing(ids).await
+    }
+
+    async fn find(&self, lookup: &OrderLookup, limit: i64, offset: i64) -> Result<Vec<OrderSummary>, Self::Error> {
+        self.counters.queries.fetch_add(1, Ordering::SeqCst);
+        self.inner.find(lookup, limit, offset).await
+    }
+
+    async fn customer_totals(&self, customer_id: &str) -> Result<CustomerTotals, Self::Error> {
+        self.counters.queries.fetch_add(1, Ordering::SeqCst);
+        self.inner.customer_totals(customer_id).await
+    }
+
+    async fn customer_orders(
+        &self,
+        customer_id: &str,
+        limit: i64,
+        offset: i64,
+    ) -> Result<(Vec<OrderSummary>, CustomerTotals), Self::Error> {
+        self.counters.queries.fetch_add(1, Ordering::SeqCst);
+        self.inner.customer_orders(customer_id, limit, offset).await
+    }
+}
@@ -0,0 +1,188 @@
+//! Throwaway Postgres databases with all migrations applied.
+//!
+//! Tests use the server from `TEST_DATABASE_URL` (the user must be allowed to create databases).
+//! Without it a temporary cluster is started with `initdb` and `postgres` found in `PG_BIN` or `PATH`,
+//! it lives in a temporary directory and goes away together with the test process.
+//! When neither is available the tests fail, unless `SKIP_PG_TESTS=1` skips them explicitly.
+
+// Each test binary uses its own part of the harness
+#![allow(dead_code)]
+
+use std::path::{Path, PathBuf};
+use std::process::{ChildStdin, Command, Stdio};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+use tokio_postgres::{Client, Config, NoTls};
+use wb_tech_l0::infrastructure::Database;
+
+static DATABASES: AtomicUsize = AtomicUsize::new(0);
+static SERVER: OnceLock<Result<Config, String>> = OnceLock::new();
+/// Keeps the pipe to the supervising shell open, the cluster is stopped once it closes at exit
+static SUPERVISOR: Mutex<Option<ChildStdin>> = Mutex::new(None);
+
+const LOCAL_PORT: u16 = 5432;
+
+pub struct TestDatabase {
+    pub database: Database,
+    config: Config,
+    version: u32,
+    name: String,
+    admin: Config,
+}
+
+async fn connect(config: &Config) -> Client {
+    let (client, connection) = config.connect(NoTls).await.expect("Can't connect to the test server");
+    tokio::spawn(connection);
+    client
+}
+
+/// Up migrations ordered by version, the way `startup.sh` applies them.
+fn migrations() -> Vec<(u32, PathBuf)> {
+    let dir = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("migrations");
+    let mut migrations: Vec<(u32, PathBuf)> = std::fs::read_dir(dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter_map(|path| {
+            let name = path.file_name()?.to_str()?;
+            let version = name.strip_prefix('V')?.split_once("__")?.0.parse().ok()?;
+            name.ends_with("_up.sql").then_some((version, path))
+        })
+        .collect();
+    migrations.sort();
+    migrations
+}
+
+async fn apply(client: &Client, from: u32, to: u32) {
+    for (_, migration) in migrations().into_iter().filter(|(version, _)| (from..=to).contains(version)) {
+        let sql = std::fs::read_to_string(&migration).unwrap();
+        if let Err(err) = client.batch_execute(&sql).await {
+            panic!("Migration {} failed: {err}", migration.display());
+        }
+    }
+}
+
+fn postgres_binary(name: &str) -> Option<PathBuf> {
+    let dirs = std::env::var_os("PG_BIN")
+        .map(|dir| vec![PathBuf::from(dir)])
+        .or_else(|| std::env::var_os("PATH").map(|path| std::env::split_paths(&path).collect()))?;
+    dirs.into_iter().map(|dir| dir.join(name)).find(|path| path.is_file())
+}
+
+/// Starts a cluster under a shell that stops it and removes its directory once its stdin closes.
+fn start_local_server() -> Result<Config, String> {
+    let initdb = postgres_binary("initdb").ok_or("initdb not found")?;
+    let postgres = postgres_binary("postgres").ok_or("postgres not found")?;
+    let dir = std::env::temp_dir().join(format!("wb_tech_l0_pg_{}", std::process::id()));
+    let data = dir.join("data");
+    let _ = std::fs::remove_dir_all(&dir);
+    let output = Command::new(initdb)
+        .args(["-U", "postgres", "--auth=trust", "-E", "UTF8", "--locale=C", "-N", "-D"])
+        .arg(&data)
+        .output()
+        .map_err(|err| format!("initdb failed: {err}"))?;
+    if !output.status.success() {
+        return Err(format!("initdb failed: {}", String::from_utf8_lossy(&output.stderr)));
+    }
+    let mut supervisor = Command::new("sh")
+        .args([
+            "-c",
+            r#""$1" -D "$2" -k "$2" -p "$4" -c listen_addresses= -c fsync=off > "$3/log" 2>&1 &
+               pid=$!; read _; kill -INT $pid; wait $pid; rm -rf "$3""#,
+            "sh",
+        ])
+        .arg(&postgres)
+        .arg(&data)
+        .arg(&dir)
+        .arg(LOCAL_PORT.to_string())
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .map_err(|err| format!("Can't start postgres: {err}"))?;
+    *SUPERVISOR.lock().unwrap() = supervisor.stdin.take();
+    wait_for_socket(&dir, &data)?;
+    let mut config = Config::new();
+    config.host_path(&data).port(LOCAL_PORT).user("postgres").dbname("postgres");
+    Ok(config)
+}
+
+fn wait_for_socket(dir: &Path, data: &Path) -> Result<(), String> {
+    let socket = data.join(format!(".s.PGSQL.{LOCAL_PORT}"));
+    let started = Instant::now();
+    while !socket.exists() {
+        if started.elapsed() > Duration::from_secs(30) {
+            let log = std::fs::read_to_string(dir.join("log")).unwrap_or_default();
+            return Err(format!("postgres didn't start: {log}"));
+        }
+        std::thread::sleep(Duration::from_millis(50));
+    }
+    // The socket appears slightly before the server accepts connections
+    std::thread::sleep(Duration::from_millis(200));
+    Ok(())
+}
+
+/// `None` only when Postgres tests are skipped with `SKIP_PG_TESTS=1`.
+fn server() -> Option<Config> {
+    if std::env::var_os("SKIP_PG_TESTS").is_some_and(|skip| skip == "1") {
+        eprintln!("SKIP_PG_TESTS=1, skipping");
+        return None;
+    }
+    let server = SERVER.get_or_init(|| match std::env::var("TEST_DATABASE_URL") {
+        Ok(url) => url.parse().map_err(|err| format!("Malformed TEST_DATABASE_URL: {err}")),
+        Err(_) => start_local_server(),
+    });
+    match server {
+        Ok(config) => Some(config.clone()),
+        Err(err) => panic!("No TEST_DATABASE_URL and no local Postgres ({err}), set SKIP_PG_TESTS=1 to skip Postgres tests"),
+    }
+}
+
+impl TestDatabase {
+    pub async fn create() -> Option<Self> {
+        Self::create_at(u32::MAX).await
+    }
+
+    /// Database with migrations up to `version` applied.
+    pub async fn create_at(version: u32) -> Option<Self> {
+        let admin = server()?;
+        let name = format!(
+            "wb_tech_l0_test_{}_{}",
+            std::process::id(),
+            DATABASES.fetch_add(1, Ordering::SeqCst)
+        );
+        let server = connect(&admin).await;
+        server.batch_execute(&format!("DROP DATABASE IF EXISTS {name}")).await.unwrap();
+        server.batch_execute(&format!("CREATE DATABASE {name}")).await.unwrap();
+        let mut config = admin.clone();
+        config.dbname(&name);
+        apply(&connect(&config).await, 0, version).await;
+        let database = Database::from_config(config.clone()).unwrap();
+        Some(Self { database, config, version, name, admin })
+    }
+
+    pub fn config(&self) -> &Config {
+        &self.config
+    }
+
+    pub async fn client(&self) -> Client {
+        connect(&self.config).await
+    }
+
+    /// Applies the remaining migrations.
+    pub async fn migrate(&mut self) {
+        apply(&self.client().await, self.version + 1, u32::MAX).await;
+        self.version = u32::MAX;
+    }
+
+    pub async fn drop(self) {
+        let Self { database, name, admin, .. } = self;
+        drop(database);
+        connect(&admin)
+            .await
+            .batch_execute(&format!("DROP DATABASE {name} WITH (FORCE)"))
+            .await
+            .unwrap();
+    }
+}
+
@@ -0,0 +1,233 @@
+mod common;
+mod pg;
+
+use axum::async_trait;
+use axum::body::{to_bytes, Body};
+use axum::http::{Request, StatusCode};
+use common::{order, MemoryRepository};
+use pg::TestDatabase;
+use serde_json::Value;
+use std::error::Error;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tower::ServiceExt;
+use wb_tech_l0::application::{router, AppState};
+use wb_tech_l0::infrastructure::{AnalyticsRefresher, ApiKeyConfig, AuthConfig, Authenticator, OrderService};
+use wb_tech_l0::interfaces::{self, Database as _, SalesAnalytics as _};
+use wb_tech_l0::models::{DateRange, Item, SalesDimension, SalesRow};
+
+type Requests = Arc<Mutex<Vec<(SalesDimension, Option<String>, Option<String>)>>>;
+
+#[derive(Default, Clone)]
+struct MockAnalytics {
+    requests: Requests,
+    refreshes: Arc<AtomicUsize>,
+}
+
+#[async_trait]
+impl interfaces::SalesAnalytics for MockAnalytics {
+    type Error = Box<dyn Error>;
+
+    async fn sales(&self, dimension: SalesDimension, range: &DateRange) -> Result<Vec<SalesRow>, Self::Error> {
+        self.requests.lock().unwrap().push((dimension, range.from.clone(), range.to.clone()));
+        Ok(vec![SalesRow {
+            key: "Vivienne Sabo".to_string(),
+            currency: "USD".to_string(),
+            orders: 2,
+            revenue: 906,
+        }])
+    }
+
+    async fn refresh(&self) -> Result<(), Self::Error> {
+        self.refreshes.fetch_add(1, Ordering::SeqCst);
+        Ok(())
+    }
+}
+
+fn authenticator() -> Authenticator {
+    let key = |name: &str, scopes: &[&str]| ApiKeyConfig {
+        name: name.to_string(),
+        sha256: Authenticator::hash_key(name),
+        scopes: scopes.iter().map(|scope| scope.to_string()).collect(),
+    };
+    Authenticator::new(AuthConfig {
+        api_keys: vec![key("reader", &["orders:read"]), key("analyst", &["analytics:read"])],
+        jwt: None,
+    })
+    .unwrap()
+}
+
+fn app(analytics: Option<MockAnalytics>) -> axum::Router {
+    let mut state = AppState::new(Box::<MemoryRepository>::default(), Box::new(OrderService::new()))
+        .with_authenticator(Box::new(authenticator()));
+    if let Some(analytics) = analytics {
+        state = state.with_analytics(Box::new(analytics));
+    }
+    router(Arc::new(state))
+}
+
+async fn get(app: &axum::Router, uri: &str, api_key: &str) -> (StatusCode, Value) {
+    let request = Request::get(uri).header("X-API-Key", api_key).body(Body::empty()).unwrap();
+    let response = app.clone().oneshot(request).await.unwrap();
+    let status = response.status();
+    let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    (status, serde_json::from_slice(&body).unwrap_or(Value::Null))
+}
+
+#[tokio::test]
+async fn report_is_grouped_by_requested_dimension() {
+    let analytics = MockAnalytics::default();
+    let app = app(Some(analytics.clone()));
+    let (status, body) = get(&app, "/analytics/brand?from=2021-11-01&to=2021-11-30", "analyst").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["dimension"], "brand");
+    assert_eq!(body["range"]["from"], "2021-11-01");
+    assert_eq!(body["rows"][0]["revenue"], 906);
+    let (status, _) = get(&app, "/analytics/delivery_service", "analyst").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(
+        *analytics.requests.lock().unwrap(),
+        [
+            (SalesDimension::Brand, Some("2021-11-01".to_string()), Some("2021-11-30".to_string())),
+            (SalesDimension::DeliveryService, None, None),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn invalid_requests_are_rejected() {
+    let app = app(Some(MockAnalytics::default()));
+    assert_eq!(get(&app, "/analytics/weather", "analyst").await.0, StatusCode::BAD_REQUEST);
+    assert_eq!(get(&app, "/analytics/day?from=yesterday", "analyst").await.0, StatusCode::BAD_REQUEST);
+    assert_eq!(get(&app, "/analytics/day?to=2021-13-01", "analyst").await.0, StatusCode::BAD_REQUEST);
+    assert_eq!(get(&app, "/analytics/day", "reader").await.0, StatusCode::FORBIDDEN);
+}
+
+#[tokio::test]
+async fn analytics_without_store_are_not_implemented() {
+    assert_eq!(get(&app(None), "/analytics/day", "analyst").await.0, StatusCode::NOT_IMPLEMENTED);
+}
+
+#[tokio::test(start_paused = true)]
+async fn refresher_refreshes_periodically() {
+    let analytics = MockAnalytics::default();
+    let refresher = tokio::spawn(AnalyticsRefresher::new(analytics.clone(), Duration::from_secs(20)).run());
+    // The clock is paused and only moves once every task is idle, so ticks at 0, 20, 40 and 60s all run
+    tokio::time::sleep(Duration::from_secs(70)).await;
+    refresher.abort();
+    assert_eq!(analytics.refreshes.load(Ordering::SeqCst), 4);
+}
+
+/// Items of the given brands and total prices.
+fn brands(items: &[(&str, i32)]) -> Vec<Item> {
+    let item = |&(brand, total_price): &(&str, i32)| Item { brand: brand.to_string(), total_price, ..Default::default() };
+    items.iter().map(item).collect()
+}
+
+fn range(from: Option<&str>, to: Option<&str>) -> DateRange {
+    DateRange { from: from.map(str::to_string), to: to.map(str::to_string) }
+}
+
+fn rows(rows: Vec<SalesRow>) -> Vec<(String, String, i64, i64)> {
+    rows.into_iter().map(|row| (row.key, row.currency, row.orders, row.revenue)).collect()
+}
+
+fn row(key: &str, currency: &str, orders: i64, revenue: i64) -> (String, String, i64, i64) {
+    (key.to_string(), currency.to_string(), orders, revenue)
+}
+
+#[tokio::test]
+async fn postgres_order_day_is_the_utc_day() {
+    let Some(test) = TestDatabase::create().await else { return };
+    let client = test.client().await;
+    for (date_created, day) in [
+        ("2021-11-26T06:22:19Z", Some("2021-11-26")),
+        ("2021-11-26T23:30:00-05:00", Some("2021-11-27")),
+        ("2021-11-27T01:00:00+03:00", Some("2021-11-26")),
+        ("2021-11-26", Some("2021-11-26")),
+        ("yesterday", None),
+        ("", None),
+    ] {
+        let row = client
+            .query_one("SELECT order_day($1)::TEXT", &[&date_created])
+            .await
+            .unwrap();
+        assert_eq!(row.get::<_, Option<&str>>(0), day, "{date_created}");
+    }
+    // Timestamps without an offset are UTC whatever the session's time zone
+    client.batch_execute("SET timezone = 'Europe/Moscow'").await.unwrap();
+    for date_created in ["2021-11-26", "2021-11-26 01:00", "2021-11-26T23:30:00"] {
+        let row = client
+            .query_one("SELECT order_day($1)::TEXT", &[&date_created])
+            .await
+            .unwrap();
+        assert_eq!(row.get::<_, Option<&str>>(0), Some("2021-11-26"), "{date_created}");
+    }
+    test.drop().await;
+}
+
+#[tokio::test]
+async fn postgres_reports_are_as_fresh_as_the_last_refresh() {
+    let Some(test) = TestDatabase::create().await else { return };
+    let database = &test.database;
+    let first = order("first").date_created("2021-11-26T06:22:19Z").paid(1000, "USD");
+    database.insert(first.items(brands(&[("Vivienne Sabo", 600), ("Loreal", 300)])).build()).await.unwrap();
+    assert!(database.sales(SalesDimension::Day, &DateRange::default()).await.unwrap().is_empty());
+
+    database.refresh().await.unwrap();
+    let sales = database.sales(SalesDimension::Day, &DateRange::default()).await.unwrap();
+    assert_eq!(rows(sales), [row("2021-11-26", "USD", 1, 1000)]);
+
+    let second = order("second").date_created("2021-11-26T10:00:00Z").paid(500, "USD");
+    database.insert(second.items(brands(&[("Loreal", 450)])).build()).await.unwrap();
+    database.refresh().await.unwrap();
+    let sales = database.sales(SalesDimension::Day, &DateRange::default()).await.unwrap();
+    assert_eq!(rows(sales), [row("2021-11-26", "USD", 2, 1500)]);
+    test.drop().await;
+}
+
+#[tokio::test]
+async fn postgres_reports_group_by_every_dimension() {
+    let Some(test) = TestDatabase::create().await else { return };
+    let database = &test.database;
+    let orders = [
+        ("first", "2021-11-26T06:22:19Z", "USD", 1000, brands(&[("Vivienne Sabo", 600), ("Loreal", 300)])),
+        // The 27th in UTC
+        ("late", "2021-11-26T23:30:00-05:00", "USD", 500, brands(&[("Loreal", 450), ("Loreal", 40)])),
+        ("rubles", "2021-11-27T12:00:00Z", "RUB", 9000, brands(&[("Vivienne Sabo", 8000)])),
+        ("undated", "yesterday", "USD", 700, brands(&[("Loreal", 700)])),
+    ];
+    for (uid, date_created, currency, amount, items) in orders {
+        let order = order(uid).date_created(date_created).paid(amount, currency).items(items);
+        database.insert(order.build()).await.unwrap();
+    }
+    database.refresh().await.unwrap();
+    let sales = |dimension, range| async move { rows(database.sales(dimension, &range).await.unwrap()) };
+
+    assert_eq!(
+        sales(SalesDimension::Day, DateRange::default()).await,
+        [row("2021-11-26", "USD", 1, 1000), row("2021-11-27", "RUB", 1, 9000), row("2021-11-27", "USD", 1, 500)]
+    );
+    // Brand revenue sums item prices, an order counts once per brand
+    assert_eq!(
+        sales(SalesDimension::Brand, DateRange::default()).await,
+        [row("Vivienne Sabo", "RUB", 1, 8000), row("Loreal", "USD", 2, 790), row("Vivienne Sabo", "USD", 1, 600)]
+    );
+    for dimension in [SalesDimension::DeliveryService, SalesDimension::Region, SalesDimension::Provider, SalesDimension::Bank] {
+        let sales = sales(dimension, DateRange::default()).await;
+        assert_eq!(sales.iter().map(|row| (row.1.as_str(), row.2, row.3)).collect::<Vec<_>>(), [("RUB", 1, 9000), ("USD", 2, 1500)]);
+    }
+    assert_eq!(
+        sales(SalesDimension::Currency, DateRange::default()).await,
+        [row("RUB", "RUB", 1, 9000), row("USD", "USD", 2, 1500)]
+    );
+
+    assert_eq!(
+        sales(SalesDimension::Day, range(Some("2021-11-27"), None)).await,
+        [row("2021-11-27", "RUB", 1, 9000), row("2021-11-27", "USD", 1, 500)]
+    );
+    assert_eq!(sales(SalesDimension::Currency, range(None, Some("2021-11-26"))).await, [row("USD", "USD", 1, 1000)]);
+    assert!(sales(SalesDimension::Brand, range(Some("2021-12-01"), Some("2021-12-31"))).await.is_empty());
+    test.drop().await;
+}
@@ -0,0 +1,159 @@
+mod common;
+
+use axum::body::Body;
+use axum::http::{Request, StatusCode};
+use common::{order, MemoryRepository};
+use jsonwebtoken::{encode, Algorithm, EncodingKey, Header};
+use serde_json::json;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tower::ServiceExt;
+use wb_tech_l0::application::{router, AppState};
+use wb_tech_l0::infrastructure::{ApiKeyConfig, AuthConfig, Authenticator, JwtConfig, OrderService};
+
+const JWT_SECRET: &[u8] = b"jwt-secret-for-tests-0123456789ab";
+
+static JWKS_FILES: AtomicUsize = AtomicUsize::new(0);
+
+/// JWKS with the HS256 test key, written for one authenticator and removed when dropped.
+struct JwksFile(PathBuf);
+
+impl JwksFile {
+    fn new() -> Self {
+        let name = format!("wb_tech_l0_jwks_{}_{}.json", std::process::id(), JWKS_FILES.fetch_add(1, Ordering::SeqCst));
+        let path = std::env::temp_dir().join(name);
+        let jwks = json!({"keys": [{"kty": "oct", "kid": "test", "alg": "HS256", "k": "and0LXNlY3JldC1mb3ItdGVzdHMtMDEyMzQ1Njc4OWFi"}]});
+        std::fs::write(&path, jwks.to_string()).unwrap();
+        Self(path)
+    }
+}
+
+impl Drop for JwksFile {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.0);
+    }
+}
+
+fn app(authenticator: Authenticator) -> axum::Router {
+    let state = AppState::new(Box::<MemoryRepository>::default(), Box::new(OrderService::new()))
+        .with_authenticator(Box::new(authenticator));
+    router(Arc::new(state))
+}
+
+fn authenticator() -> Authenticator {
+    let key = |name: &str, secret: &str, scopes: &[&str]| ApiKeyConfig {
+        name: name.to_string(),
+        sha256: Authenticator::hash_key(secret),
+        scopes: scopes.iter().map(|scope| scope.to_string()).collect(),
+    };
+    // The keys are read right away, the file isn't needed afterwards
+    let jwks = JwksFile::new();
+    Authenticator::new(AuthConfig {
+        api_keys: vec![
+            key("producer", "producer-key", &["orders:write"]),
+            key("reader", "reader-key", &["orders:read"]),
+        ],
+        jwt: Some(JwtConfig {
+            jwks_path: jwks.0.clone(),
+            issuer: Some("https://issuer.test".to_string()),
+            audience: None,
+            leeway_secs: 0,
+        }),
+    })
+    .unwrap()
+}
+
+fn token(scope: &str, issuer: &str, expires_in: i64) -> String {
+    token_with(Algorithm::HS256, scope, issuer, expires_in)
+}
+
+fn token_with(algorithm: Algorithm, scope: &str, issuer: &str, expires_in: i64) -> String {
+    let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs() as i64;
+    let claims = json!({"sub": "mobile-app", "scope": scope, "iss": issuer, "exp": now + expires_in});
+    let header = Header {
+        kid: Some("test".to_string()),
+        ..Header::new(algorithm)
+    };
+    encode(&header, &claims, &EncodingKey::from_secret(JWT_SECRET)).unwrap()
+}
+
+fn add_order_request(header: Option<(&str, String)>) -> Request<Body> {
+    let order = order("order1").build();
+    let mut builder = Request::post("/add_order").header("Content-Type", "application/json");
+    if let Some((name, value)) = header {
+        builder = builder.header(name, value);
+    }
+    builder.body(Body::from(serde_json::to_vec(&order).unwrap())).unwrap()
+}
+
+fn get_order_request(header: Option<(&str, String)>) -> Request<Body> {
+    let mut builder = Request::get("/order/order1");
+    if let Some((name, value)) = header {
+        builder = builder.header(name, value);
+    }
+    builder.body(Body::empty()).unwrap()
+}
+
+async fn status(app: &axum::Router, request: Request<Body>) -> StatusCode {
+    app.clone().oneshot(request).await.unwrap().status()
+}
+
+#[tokio::test]
+async fn api_key_scopes_are_enforced() {
+    let app = app(authenticator());
+
+    assert_eq!(status(&app, add_order_request(None)).await, StatusCode::UNAUTHORIZED);
+    assert_eq!(status(&app, add_order_request(Some(("X-API-Key", "wrong".to_string())))).await, StatusCode::UNAUTHORIZED);
+    assert_eq!(status(&app, add_order_request(Some(("X-API-Key", "reader-key".to_string())))).await, StatusCode::FORBIDDEN);
+    assert_eq!(status(&app, add_order_request(Some(("X-API-Key", "producer-key".to_string())))).await, StatusCode::CREATED);
+    assert_eq!(
+        status(&app, get_order_request(Some(("Authorization", "ApiKey reader-key".to_string())))).await,
+        StatusCode::OK
+    );
+    assert_eq!(status(&app, get_order_request(Some(("X-API-Key", "producer-key".to_string())))).await, StatusCode::FORBIDDEN);
+}
+
+#[tokio::test]
+async fn jwt_bearer_tokens_are_verified() {
+    let app = app(authenticator());
+    let bearer = |token: String| Some(("Authorization", format!("Bearer {token}")));
+
+    let valid = token("orders:read orders:write", "https://issuer.test", 60);
+    assert_eq!(status(&app, add_order_request(bearer(valid.clone()))).await, StatusCode::CREATED);
+    assert_eq!(status(&app, get_order_request(bearer(valid))).await, StatusCode::OK);
+
+    let read_only = token("orders:read", "https://issuer.test", 60);
+    assert_eq!(status(&app, add_order_request(bearer(read_only))).await, StatusCode::FORBIDDEN);
+
+    let expired = token("orders:write", "https://issuer.test", -60);
+    assert_eq!(status(&app, add_order_request(bearer(expired))).await, StatusCode::UNAUTHORIZED);
+
+    let foreign = token("orders:write", "https://other.test", 60);
+    assert_eq!(status(&app, add_order_request(bearer(foreign))).await, StatusCode::UNAUTHORIZED);
+
+    // The key is for HS256, a token can't pick another algorithm for it
+    let other_algorithm = token_with(Algorithm::HS384, "orders:write", "https://issuer.test", 60);
+    assert_eq!(status(&app, add_order_request(bearer(other_algorithm))).await, StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn disabled_auth_allows_everything() {
+    let app = app(Authenticator::disabled());
+    let response = app.oneshot(add_order_request(None)).await.unwrap();
+    assert_eq!(response.status(), StatusCode::CREATED);
+}
+
+#[test]
+fn api_key_digest_must_be_sha256() {
+    let config = AuthConfig {
+        api_keys: vec![ApiKeyConfig {
+            name: "plain".to_string(),
+            sha256: "not-a-digest".to_string(),
+            scopes: vec![],
+        }],
+        jwt: None,
+    };
+    assert!(Authenticator::new(config).is_err());
+}
@@ -0,0 +1,120 @@
+//! Saving the order cache to disk and restoring it on start.
+
+mod common;
+
+use common::{order, CountingDatabase};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+use wb_tech_l0::infrastructure::{Cache, CacheSnapshotter, InvalidSnapshot, MemoryDatabase};
+use wb_tech_l0::interfaces::{Cache as _, Database as _};
+
+static FILES: AtomicUsize = AtomicUsize::new(0);
+
+/// Snapshot path removed when dropped, with whatever was left next to it.
+struct TempSnapshot(PathBuf);
+
+impl TempSnapshot {
+    fn new() -> Self {
+        let name = format!("wb_tech_l0_snapshot_{}_{}.bin", std::process::id(), FILES.fetch_add(1, Ordering::SeqCst));
+        Self(std::env::temp_dir().join(name))
+    }
+
+    fn path(&self) -> &Path {
+        &self.0
+    }
+}
+
+impl Drop for TempSnapshot {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.0);
+    }
+}
+
+async fn cache_of(uids: &[&str]) -> Cache {
+    let cache = Cache::new();
+    for uid in uids {
+        cache.add(uid.to_string(), order(uid).build()).await;
+    }
+    cache
+}
+
+#[tokio::test]
+async fn snapshots_restore_the_cached_orders() {
+    let file = TempSnapshot::new();
+    let cache = cache_of(&["first", "second", "third"]).await;
+    assert_eq!(cache.save_snapshot(file.path()).await.unwrap(), 3);
+
+    let restored = Cache::load_snapshot(file.path()).await.unwrap();
+    let mut uids = restored.order_uids().await;
+    uids.sort();
+    assert_eq!(uids, ["first", "second", "third"]);
+    assert_eq!(restored.get("second").await.unwrap(), order("second").build());
+}
+
+#[tokio::test]
+async fn damaged_snapshots_are_rejected() {
+    let file = TempSnapshot::new();
+    cache_of(&["first", "second"]).await.save_snapshot(file.path()).await.unwrap();
+    let bytes = std::fs::read(file.path()).unwrap();
+
+    let mut flipped = bytes.clone();
+    flipped[bytes.len() / 2] ^= 0xff;
+    let mut other_version = bytes.clone();
+    other_version[4] += 1;
+    for damaged in [flipped, other_version, bytes[..bytes.len() - 1].to_vec(), b"not a snapshot".to_vec()] {
+        std::fs::write(file.path(), damaged).unwrap();
+        let err = Cache::load_snapshot(file.path()).await.err().unwrap();
+        assert!(err.is::<InvalidSnapshot>(), "{err}");
+    }
+}
+
+#[tokio::test]
+async fn snapshots_survive_fields_being_reordered_or_added() {
+    let file = TempSnapshot::new();
+    cache_of(&["first"]).await.save_snapshot(file.path()).await.unwrap();
+    let bytes = std::fs::read(file.path()).unwrap();
+    let (header, content) = bytes[..bytes.len() - 4].split_at(8);
+
+    // Maps come back with their keys sorted, as if the fields were declared in another order
+    let mut orders: serde_json::Value = rmp_serde::from_slice(content).unwrap();
+    orders[0]["added_later"] = serde_json::json!(true);
+    let mut rewritten = header.to_vec();
+    rewritten.extend(rmp_serde::to_vec_named(&orders).unwrap());
+    rewritten.extend(crc32fast::hash(&rewritten).to_le_bytes());
+    std::fs::write(file.path(), rewritten).unwrap();
+
+    let restored = Cache::load_snapshot(file.path()).await.unwrap();
+    assert_eq!(restored.get("first").await.unwrap(), order("first").build());
+}
+
+#[tokio::test]
+async fn restored_orders_removed_from_the_database_are_evicted() {
+    let database = CountingDatabase::default();
+    database.insert(order("kept").build()).await.unwrap();
+    let counters = database.counters.clone();
+    let cache = cache_of(&["kept", "removed"]).await;
+    let snapshotter = CacheSnapshotter::new(cache.clone(), database, PathBuf::new(), Duration::from_secs(60));
+
+    assert_eq!(snapshotter.validate(&["kept".to_string(), "removed".to_string()]).await.unwrap(), 1);
+    assert!(cache.get("kept").await.is_some());
+    assert!(cache.get("removed").await.is_none());
+    // Only existence is checked, the orders themselves aren't read
+    assert_eq!(counters.reads(), 0);
+    assert!(counters.batches().is_empty());
+}
+
+#[tokio::test]
+async fn the_cache_is_saved_periodically() {
+    let file = TempSnapshot::new();
+    let database = MemoryDatabase::new();
+    database.insert(order("stored").build()).await.unwrap();
+    let cache = cache_of(&["stored", "removed"]).await;
+    let snapshotter = CacheSnapshotter::new(cache.clone(), database, file.path().to_path_buf(), Duration::from_millis(50));
+    let worker = tokio::spawn(snapshotter.run());
+
+    tokio::time::sleep(Duration::from_millis(200)).await;
+    worker.abort();
+    let restored = Cache::load_snapshot(file.path()).await.unwrap();
+    assert_eq!(restored.order_uids().await, ["stored"]);
+}
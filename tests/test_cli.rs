@@ -0,0 +1,196 @@
+mod common;
+mod pg;
+
+use common::order;
+use pg::TestDatabase;
+use serde_json::{json, Value};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use wb_tech_l0::infrastructure::Database;
+
+static FILES: AtomicUsize = AtomicUsize::new(0);
+
+/// Temporary file removed when dropped.
+struct TempFile(PathBuf);
+
+impl TempFile {
+    fn new(extension: &str) -> Self {
+        let name = format!("wb_tech_l0_cli_{}_{}.{extension}", std::process::id(), FILES.fetch_add(1, Ordering::SeqCst));
+        Self(std::env::temp_dir().join(name))
+    }
+
+    fn with(extension: &str, contents: &str) -> Self {
+        let file = Self::new(extension);
+        std::fs::write(&file.0, contents).unwrap();
+        file
+    }
+
+    fn path(&self) -> &str {
+        self.0.to_str().unwrap()
+    }
+}
+
+impl Drop for TempFile {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.0);
+    }
+}
+
+/// An order as import files have it.
+fn order_json(uid: &str, date_created: &str) -> Value {
+    let order = order(uid).track_number(&format!("{uid}-track")).date_created(date_created);
+    serde_json::to_value(order.build()).unwrap()
+}
+
+fn run(database: &Path, args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_wb_tech_l0"))
+        .args(["--storage", "sqlite", "--database", database.to_str().unwrap()])
+        .args(args)
+        .output()
+        .unwrap()
+}
+
+fn stdout(output: &Output) -> String {
+    String::from_utf8(output.stdout.clone()).unwrap()
+}
+
+#[test]
+fn import_skips_existing_orders_and_reports_bad_ones() {
+    let database = TempFile::new("sqlite");
+    let single = TempFile::with("json", &order_json("first", "2021-11-26T06:22:19Z").to_string());
+    let array = TempFile::with("json", &json!([order_json("first", "2021-11-26T06:22:19Z"), order_json("second", "2021-11-27T06:22:19Z")]).to_string());
+    let lines = format!("{}\n\n{{broken\n{}\n", order_json("third", "2021-11-28T06:22:19Z"), json!({"order_uid": "partial"}));
+    let ndjson = TempFile::with("ndjson", &lines);
+
+    let output = run(&database.0, &["import", single.path()]);
+    assert!(output.status.success());
+    assert_eq!(stdout(&output), "Imported 1, skipped 0 existing, 0 failed\n");
+
+    let output = run(&database.0, &["import", array.path()]);
+    assert!(output.status.success());
+    assert_eq!(stdout(&output), "Imported 1, skipped 1 existing, 0 failed\n");
+
+    let output = run(&database.0, &["import", ndjson.path()]);
+    assert!(!output.status.success());
+    assert_eq!(stdout(&output), "Imported 1, skipped 0 existing, 2 failed\n");
+    let errors = String::from_utf8(output.stderr).unwrap();
+    assert!(errors.contains("line 3: "), "{errors}");
+    assert!(errors.contains("line 4: "), "{errors}");
+}
+
+#[test]
+fn get_prints_the_order() {
+    let database = TempFile::new("sqlite");
+    let orders = TempFile::with("json", &json!([order_json("first", "2021-11-26T06:22:19Z")]).to_string());
+    assert!(run(&database.0, &["import", orders.path()]).status.success());
+
+    let output = run(&database.0, &["get", "first"]);
+    assert!(output.status.success());
+    let printed: Value = serde_json::from_str(&stdout(&output)).unwrap();
+    assert_eq!(printed, order_json("first", "2021-11-26T06:22:19Z"));
+
+    let output = run(&database.0, &["get", "missing"]);
+    assert!(!output.status.success());
+    assert!(output.stdout.is_empty());
+}
+
+#[test]
+fn export_writes_filtered_orders() {
+    let database = TempFile::new("sqlite");
+    let orders = [
+        order_json("late", "2021-11-28T06:22:19Z"),
+        order_json("early", "2021-11-26T06:22:19Z"),
+        order_json("middle", "2021-11-27T06:22:19Z"),
+    ];
+    let file = TempFile::with("json", &Value::from(orders.to_vec()).to_string());
+    assert!(run(&database.0, &["import", file.path()]).status.success());
+
+    let output = run(&database.0, &["export", "--from", "2021-11-27"]);
+    assert!(output.status.success());
+    let exported: Vec<Value> = stdout(&output).lines().map(|line| serde_json::from_str(line).unwrap()).collect();
+    assert_eq!(exported, [orders[2].clone(), orders[0].clone()]);
+
+    let csv = TempFile::new("csv");
+    let output = run(&database.0, &["export", "--format", "csv", "--track-number", "early-track", "-o", csv.path()]);
+    assert!(output.status.success());
+    let written = std::fs::read_to_string(&csv.0).unwrap();
+    let mut rows = written.lines();
+    let header: Vec<&str> = rows.next().unwrap().split(',').collect();
+    let row: Vec<&str> = rows.next().unwrap().split(',').collect();
+    assert_eq!(rows.next(), None);
+    let column = |name: &str| row[header.iter().position(|column| *column == name).unwrap()];
+    assert_eq!(column("order_uid"), "early");
+    assert_eq!(column("delivery_name"), "Test Testov");
+    assert_eq!(column("payment_amount"), "1817");
+    assert_eq!(column("item_chrt_id"), "9934930");
+
+    assert!(!run(&database.0, &["export", "--from", "27.11.2021"]).status.success());
+    assert!(!run(&database.0, &["export", "--nm-id", "1", "--chrt-id", "2"]).status.success());
+}
+
+#[test]
+fn check_reports_each_problem() {
+    let database = TempFile::new("sqlite");
+    let output = run(&database.0, &["check"]);
+    assert!(output.status.success());
+    assert!(stdout(&output).lines().all(|line| line.starts_with("ok ")));
+
+    let auth = TempFile::with("json", "not json");
+    let output = run(&database.0, &["check", "--auth-config", auth.path()]);
+    assert!(!output.status.success());
+    assert!(stdout(&output).starts_with("FAIL  Auth config"));
+
+    let output = run(&database.0, &["check", "--replica-database", "host=replica"]);
+    assert!(!output.status.success());
+    assert!(stdout(&output).contains("FAIL  Storage: --replica-database is only supported by postgres storage"));
+}
+
+#[test]
+fn server_settings_are_rejected_with_commands() {
+    let database = TempFile::new("sqlite");
+    let output = Command::new(env!("CARGO_BIN_EXE_wb_tech_l0"))
+        .args(["--read-rate-limit", "5", "check"])
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    assert!(String::from_utf8(output.stderr).unwrap().contains("--read-rate-limit"));
+    assert!(!run(&database.0, &["--storage", "memory", "get", "first"]).status.success());
+}
+
+#[test]
+fn rate_limits_must_be_positive() {
+    for rate in ["0", "-1", "inf", "NaN"] {
+        let output = Command::new(env!("CARGO_BIN_EXE_wb_tech_l0"))
+            .args([&format!("--write-rate-limit={rate}"), "check"])
+            .output()
+            .unwrap();
+        assert!(!output.status.success());
+        assert!(String::from_utf8(output.stderr).unwrap().contains("must be a positive number"), "{rate}");
+    }
+}
+
+#[tokio::test]
+async fn schema_version_comes_from_refinery_history() {
+    let latest = std::fs::read_dir(Path::new(env!("CARGO_MANIFEST_DIR")).join("migrations"))
+        .unwrap()
+        .filter_map(|entry| {
+            let name = entry.unwrap().file_name().into_string().unwrap();
+            name.strip_prefix('V')?.split_once("__")?.0.parse::<i32>().ok()
+        })
+        .max();
+    assert_eq!(latest, Some(Database::SCHEMA_VERSION));
+
+    let Some(test) = TestDatabase::create().await else { return };
+    assert_eq!(test.database.schema_version().await.unwrap(), None);
+    test.client()
+        .await
+        .batch_execute(
+            "CREATE TABLE refinery_schema_history (version INT4 PRIMARY KEY, name TEXT);
+             INSERT INTO refinery_schema_history VALUES (1, 'init'), (8, 'order_scoped_items');",
+        )
+        .await
+        .unwrap();
+    assert_eq!(test.database.schema_version().await.unwrap(), Some(8));
+    test.drop().await;
+}
@@ -0,0 +1,74 @@
+mod common;
+
+use axum::body::{to_bytes, Body};
+use axum::http::{Request, StatusCode};
+use common::{order, CountingDatabase, MemoryRepository};
+use serde_json::{json, Value};
+use std::sync::Arc;
+use tower::ServiceExt;
+use wb_tech_l0::application::{router, AppState};
+use wb_tech_l0::infrastructure::{Cache, OrderService, Repository};
+use wb_tech_l0::interfaces::Repository as _;
+
+async fn get(app: &axum::Router, uri: &str) -> (StatusCode, Value) {
+    let response = app.clone().oneshot(Request::get(uri).body(Body::empty()).unwrap()).await.unwrap();
+    let status = response.status();
+    let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    (status, serde_json::from_slice(&body).unwrap())
+}
+
+#[tokio::test]
+async fn history_is_paginated_with_totals() {
+    let repository = MemoryRepository::default();
+    let orders = [
+        order("first").customer_id("alice").date_created("2021-11-26T06:22:19Z").paid(100, "USD"),
+        order("second").customer_id("alice").date_created("2021-11-27T06:22:19Z").paid(250, "RUB"),
+        order("third").customer_id("alice").date_created("2021-11-28T06:22:19Z").paid(50, "USD"),
+        order("other").customer_id("bob").date_created("2021-11-29T06:22:19Z").paid(10, "USD"),
+    ];
+    for order in orders {
+        repository.insert(order.build()).await.unwrap();
+    }
+    let app = router(Arc::new(AppState::new(Box::new(repository), Box::new(OrderService::new()))));
+
+    let (status, body) = get(&app, "/customers/alice/orders?limit=2").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["page"]["orders"][0]["order_uid"], "third");
+    assert_eq!(body["page"]["orders"][1]["order_uid"], "second");
+    assert_eq!(body["page"]["next_offset"], 2);
+    assert_eq!(body["totals"]["order_count"], 3);
+    assert_eq!(body["totals"]["last_order_date"], "2021-11-28T06:22:19Z");
+    assert_eq!(
+        body["totals"]["spent"],
+        json!([{"currency": "RUB", "amount": 250}, {"currency": "USD", "amount": 150}])
+    );
+
+    let (status, body) = get(&app, "/customers/nobody/orders").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["totals"]["order_count"], 0);
+    assert!(body["totals"]["last_order_date"].is_null());
+    assert_eq!(get(&app, "/customers/alice/orders?limit=101").await.0, StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn history_is_cached_until_customer_orders_again() {
+    let database = CountingDatabase::default();
+    let counters = database.counters.clone();
+    let repository = Repository::new(Cache::new(), database);
+    repository.customer_orders("alice", 20, 0).await.unwrap();
+    repository.customer_orders("alice", 20, 0).await.unwrap();
+    assert_eq!(counters.queries(), 1, "The page and the totals are read together");
+
+    // Another page and another customer aren't cached yet
+    repository.customer_orders("alice", 20, 20).await.unwrap();
+    repository.customer_orders("bob", 20, 0).await.unwrap();
+    assert_eq!(counters.queries(), 3);
+
+    repository
+        .insert(order("new").customer_id("alice").date_created("2021-11-30T06:22:19Z").paid(1, "USD").build())
+        .await
+        .unwrap();
+    repository.customer_orders("alice", 20, 0).await.unwrap();
+    repository.customer_orders("bob", 20, 0).await.unwrap();
+    assert_eq!(counters.queries(), 4);
+}
@@ -0,0 +1,304 @@
+//! Behaviour every `interfaces::Database` implementation must share.
+//! Each check runs against the memory, SQLite and Postgres databases, the latter only when one is available.
+
+mod common;
+mod pg;
+
+use pg::TestDatabase;
+use std::error::Error;
+use std::future::Future;
+use std::sync::Arc;
+use wb_tech_l0::infrastructure::{is_conflict, MemoryDatabase, SqliteDatabase};
+use futures::TryStreamExt;
+use wb_tech_l0::interfaces::{Database, OrderExport};
+use wb_tech_l0::models::{DateRange, Delivery, Item, Order, OrderFilter, OrderLookup, OrderSummary};
+
+/// Everything a storage backend has to implement.
+trait Storage: Database<Error = Box<dyn Error>> + OrderExport<Error = Box<dyn Error>> {}
+
+impl<T: Database<Error = Box<dyn Error>> + OrderExport<Error = Box<dyn Error>>> Storage for T {}
+
+type Db = dyn Storage;
+/// A fresh database and, for Postgres, the test database to drop afterwards. `None` when unavailable.
+type Backend = Option<(Arc<Db>, Option<TestDatabase>)>;
+
+fn item(chrt_id: i32, nm_id: i32) -> Item {
+    Item { nm_id, rid: format!("rid-{chrt_id}"), ..common::item(chrt_id) }
+}
+
+/// Every field is set so that each column is compared when the order comes back.
+fn order(uid: &str, customer_id: &str, date_created: &str) -> Order {
+    let delivery = Delivery { name: "Тест Тестов".to_string(), phone: "+9720000000".to_string(), ..Default::default() };
+    common::order(uid)
+        .track_number(&format!("{uid}-track"))
+        .delivery(delivery)
+        .items(vec![item(9934930, 2389212), item(9934931, 2389213)])
+        .customer_id(customer_id)
+        .date_created(date_created)
+        .with(|order| {
+            order.payment.transaction = format!("{uid}-transaction");
+            order.payment.request_id = format!("{uid}-request");
+            order.sm_id = 99;
+        })
+        .build()
+}
+
+fn uids(summaries: Vec<OrderSummary>) -> Vec<String> {
+    summaries.into_iter().map(|summary| summary.order_uid).collect()
+}
+
+async fn inserted_orders_come_back_unchanged(database: Arc<Db>) {
+    let with_items = order("with-items", "alice", "2021-11-26T06:22:19Z");
+    let mut without_items = order("without-items", "alice", "2021-11-26T06:22:19Z");
+    without_items.items.clear();
+    for order in [with_items, without_items] {
+        database.insert(order.clone()).await.unwrap();
+        assert_eq!(database.get(&order.order_uid).await.unwrap(), Some(order));
+    }
+    assert_eq!(database.get("missing").await.unwrap(), None);
+}
+
+async fn orders_are_read_in_batches(database: Arc<Db>) {
+    let mut without_items = order("without-items", "bob", "2021-11-27T06:22:19Z");
+    without_items.items.clear();
+    let orders = [order("first", "alice", "2021-11-26T06:22:19Z"), without_items];
+    for order in &orders {
+        database.insert(order.clone()).await.unwrap();
+    }
+    let ids = ["without-items", "missing", "first", "first"].map(str::to_string);
+    let mut found = database.get_many(&ids).await.unwrap();
+    found.sort_by(|a, b| a.order_uid.cmp(&b.order_uid));
+    found.dedup();
+    assert_eq!(found, orders);
+    assert!(database.get_many(&[]).await.unwrap().is_empty());
+}
+
+async fn existing_orders_are_told_apart(database: Arc<Db>) {
+    database.insert(order("first", "alice", "2021-11-26T06:22:19Z")).await.unwrap();
+    database.insert(order("second", "bob", "2021-11-27T06:22:19Z")).await.unwrap();
+    let ids = ["second", "missing", "first"].map(str::to_string);
+    let mut existing = database.existing(&ids).await.unwrap();
+    existing.sort();
+    assert_eq!(existing, ["first", "second"]);
+    assert!(database.existing(&[]).await.unwrap().is_empty());
+}
+
+async fn order_uid_is_unique(database: Arc<Db>) {
+    let original = order("order", "alice", "2021-11-26T06:22:19Z");
+    database.insert(original.clone()).await.unwrap();
+    let mut duplicate = order("order", "bob", "2021-11-27T06:22:19Z");
+    duplicate.payment.transaction = "another-transaction".to_string();
+    let err = database.insert(duplicate).await.unwrap_err();
+    assert!(is_conflict(err.as_ref()), "not a conflict: {err}");
+    assert_eq!(database.get("order").await.unwrap(), Some(original));
+}
+
+async fn payment_transaction_is_unique(database: Arc<Db>) {
+    let original = order("order", "alice", "2021-11-26T06:22:19Z");
+    database.insert(original.clone()).await.unwrap();
+    let mut duplicate = order("other", "alice", "2021-11-27T06:22:19Z");
+    duplicate.payment.transaction = original.payment.transaction.clone();
+    let err = database.insert(duplicate).await.unwrap_err();
+    assert!(is_conflict(err.as_ref()), "not a conflict: {err}");
+    assert_eq!(database.get("other").await.unwrap(), None);
+    let lookup = OrderLookup::CustomerId("alice".to_string());
+    assert_eq!(uids(database.find(&lookup, 10, 0).await.unwrap()), ["order"]);
+}
+
+async fn removed_orders_are_gone(database: Arc<Db>) {
+    let order = order("order", "alice", "2021-11-26T06:22:19Z");
+    database.insert(order.clone()).await.unwrap();
+    database.remove("order").await.unwrap();
+    database.remove("never-existed").await.unwrap();
+    assert_eq!(database.get("order").await.unwrap(), None);
+    assert!(database.find(&OrderLookup::ChrtId(9934930), 10, 0).await.unwrap().is_empty());
+    assert_eq!(database.customer_totals("alice").await.unwrap().order_count, 0);
+    // Its uid and transaction are free again
+    database.insert(order.clone()).await.unwrap();
+    assert_eq!(database.get("order").await.unwrap(), Some(order));
+}
+
+async fn lookups_find_orders_newest_first(database: Arc<Db>) {
+    database.insert(order("old", "alice", "2021-11-26T06:22:19Z")).await.unwrap();
+    database.insert(order("new", "alice", "2021-11-28T06:22:19Z")).await.unwrap();
+    database.insert(order("same-day-b", "bob", "2021-11-27T06:22:19Z")).await.unwrap();
+    database.insert(order("same-day-a", "bob", "2021-11-27T06:22:19Z")).await.unwrap();
+    let mut other = order("other", "carol", "2021-11-29T06:22:19Z");
+    other.items = vec![item(1, 2)];
+    database.insert(other).await.unwrap();
+
+    let found = |lookup| {
+        let database = database.clone();
+        async move { uids(database.find(&lookup, 10, 0).await.unwrap()) }
+    };
+    assert_eq!(found(OrderLookup::CustomerId("alice".to_string())).await, ["new", "old"]);
+    assert_eq!(found(OrderLookup::TrackNumber("old-track".to_string())).await, ["old"]);
+    assert_eq!(found(OrderLookup::Transaction("new-transaction".to_string())).await, ["new"]);
+    assert_eq!(found(OrderLookup::RequestId("same-day-a-request".to_string())).await, ["same-day-a"]);
+    assert_eq!(found(OrderLookup::ChrtId(9934931)).await, ["new", "same-day-a", "same-day-b", "old"]);
+    assert_eq!(found(OrderLookup::NmId(2)).await, ["other"]);
+    assert!(found(OrderLookup::NmId(404)).await.is_empty());
+
+    let lookup = OrderLookup::ChrtId(9934930);
+    assert_eq!(uids(database.find(&lookup, 2, 1).await.unwrap()), ["same-day-a", "same-day-b"]);
+    assert!(database.find(&lookup, 2, 10).await.unwrap().is_empty());
+
+    let summary = database.find(&OrderLookup::TrackNumber("new-track".to_string()), 1, 0).await.unwrap();
+    assert_eq!(summary[0].customer_name, "Тест Тестов");
+    assert_eq!(summary[0].amount, 1817);
+    assert_eq!(summary[0].currency, "USD");
+    assert_eq!(summary[0].item_count, 2);
+}
+
+async fn customer_totals_cover_all_orders(database: Arc<Db>) {
+    let mut rubles = order("rubles", "alice", "2021-11-27T06:22:19Z");
+    rubles.payment.currency = "RUB".to_string();
+    rubles.payment.amount = 250;
+    database.insert(order("first", "alice", "2021-11-26T06:22:19Z")).await.unwrap();
+    database.insert(rubles).await.unwrap();
+    database.insert(order("last", "alice", "2021-11-28T06:22:19Z")).await.unwrap();
+    database.insert(order("other", "bob", "2021-11-29T06:22:19Z")).await.unwrap();
+
+    let totals = database.customer_totals("alice").await.unwrap();
+    assert_eq!(totals.order_count, 3);
+    assert_eq!(totals.last_order_date.as_deref(), Some("2021-11-28T06:22:19Z"));
+    let spent: Vec<(&str, i64)> = totals.spent.iter().map(|total| (total.currency.as_str(), total.amount)).collect();
+    assert_eq!(spent, [("RUB", 250), ("USD", 3634)]);
+
+    let nobody = database.customer_totals("nobody").await.unwrap();
+    assert_eq!(nobody.order_count, 0);
+    assert!(nobody.spent.is_empty());
+    assert_eq!(nobody.last_order_date, None);
+
+    let (orders, together) = database.customer_orders("alice", 2, 0).await.unwrap();
+    assert_eq!(uids(orders), ["last", "rubles"]);
+    assert_eq!(serde_json::to_value(together).unwrap(), serde_json::to_value(totals).unwrap());
+}
+
+async fn exports_filter_orders_oldest_first(database: Arc<Db>) {
+    let mut without_items = order("without-items", "bob", "2021-11-26T23:59:59Z");
+    without_items.items.clear();
+    let orders = [
+        order("late", "alice", "2021-11-28T06:22:19Z"),
+        order("early", "alice", "2021-11-25T06:22:19Z"),
+        order("same-day-b", "alice", "2021-11-26T06:22:19Z"),
+        order("same-day-a", "alice", "2021-11-26T06:22:19Z"),
+        without_items.clone(),
+    ];
+    for order in orders.clone() {
+        database.insert(order).await.unwrap();
+    }
+    let exported = |lookup: Option<OrderLookup>, from: Option<&str>, to: Option<&str>| {
+        let database = database.clone();
+        let filter = OrderFilter {
+            lookup,
+            range: DateRange {
+                from: from.map(str::to_string),
+                to: to.map(str::to_string),
+            },
+        };
+        async move {
+            let orders: Vec<Order> = database.export(&filter).await.unwrap().try_collect().await.unwrap();
+            orders
+        }
+    };
+    let all = exported(None, None, None).await;
+    assert_eq!(
+        all.iter().map(|order| order.order_uid.as_str()).collect::<Vec<_>>(),
+        ["early", "same-day-a", "same-day-b", "without-items", "late"]
+    );
+    assert_eq!(all[3], without_items);
+    assert_eq!(all[4], orders[0]);
+
+    let uids = |orders: Vec<Order>| orders.into_iter().map(|order| order.order_uid).collect::<Vec<_>>();
+    assert_eq!(
+        uids(exported(None, Some("2021-11-26"), Some("2021-11-26")).await),
+        ["same-day-a", "same-day-b", "without-items"]
+    );
+    assert_eq!(uids(exported(None, Some("2021-11-27"), None).await), ["late"]);
+    assert_eq!(
+        uids(exported(Some(OrderLookup::CustomerId("alice".to_string())), None, Some("2021-11-26")).await),
+        ["early", "same-day-a", "same-day-b"]
+    );
+    assert_eq!(uids(exported(Some(OrderLookup::ChrtId(9934930)), Some("2021-11-27"), None).await), ["late"]);
+    assert!(exported(None, Some("2021-11-29"), None).await.is_empty());
+
+    // Ranges are in UTC days, whatever offset the order was written with
+    let offsets = [
+        order("behind-utc", "carol", "2021-11-29T22:30:00-03:00"),
+        order("ahead-of-utc", "carol", "2021-12-01T01:00:00+03:00"),
+    ];
+    for order in offsets {
+        database.insert(order).await.unwrap();
+    }
+    assert!(exported(None, Some("2021-11-29"), Some("2021-11-29")).await.is_empty());
+    assert_eq!(
+        uids(exported(None, Some("2021-11-30"), Some("2021-11-30")).await),
+        ["behind-utc", "ahead-of-utc"]
+    );
+}
+
+async fn run<F, Fut>(backend: Backend, check: F)
+where
+    F: FnOnce(Arc<Db>) -> Fut,
+    Fut: Future<Output = ()>,
+{
+    let Some((database, postgres)) = backend else { return };
+    check(database).await;
+    if let Some(postgres) = postgres {
+        postgres.drop().await;
+    }
+}
+
+async fn memory() -> Backend {
+    Some((Arc::new(MemoryDatabase::new()), None))
+}
+
+async fn sqlite() -> Backend {
+    Some((Arc::new(SqliteDatabase::open(":memory:").unwrap()), None))
+}
+
+async fn postgres() -> Backend {
+    let test = TestDatabase::create().await?;
+    Some((Arc::new(test.database.clone()), Some(test)))
+}
+
+macro_rules! conformance {
+    ($($backend:ident),+ => $checks:tt) => {
+        $(conformance!(@backend $backend $checks);)+
+    };
+    (@backend $backend:ident [$($check:ident),+ $(,)?]) => {
+        mod $backend {
+            $(
+                #[tokio::test]
+                async fn $check() {
+                    super::run(super::$backend().await, super::$check).await;
+                }
+            )+
+        }
+    };
+}
+
+conformance!(memory, sqlite, postgres => [
+    inserted_orders_come_back_unchanged,
+    orders_are_read_in_batches,
+    existing_orders_are_told_apart,
+    order_uid_is_unique,
+    payment_transaction_is_unique,
+    removed_orders_are_gone,
+    lookups_find_orders_newest_first,
+    customer_totals_cover_all_orders,
+    exports_filter_orders_oldest_first,
+]);
+
+#[tokio::test]
+async fn sqlite_keeps_orders_in_its_file() {
+    let path = std::env::temp_dir().join(format!("wb_tech_l0_conformance_{}.sqlite", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    let order = order("order", "alice", "2021-11-26T06:22:19Z");
+    SqliteDatabase::open(&path).unwrap().insert(order.clone()).await.unwrap();
+    let reopened = SqliteDatabase::open(&path).unwrap();
+    assert_eq!(reopened.get("order").await.unwrap(), Some(order));
+    drop(reopened);
+    std::fs::remove_file(&path).unwrap();
+}
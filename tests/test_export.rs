@@ -0,0 +1,173 @@
+mod common;
+mod pg;
+
+use axum::body::{to_bytes, Body, Bytes};
+use axum::http::{header, Request, StatusCode};
+use common::MemoryRepository;
+use parquet::file::reader::{FileReader, SerializedFileReader};
+use parquet::record::Field;
+use pg::TestDatabase;
+use std::sync::Arc;
+use tower::ServiceExt;
+use wb_tech_l0::application::{router, AppState};
+use futures::{stream, StreamExt, TryStreamExt};
+use wb_tech_l0::infrastructure::{columns, encode, Cache, OrderService, ParquetEncoder, Repository, SqliteDatabase};
+use wb_tech_l0::interfaces::{self, Database};
+use wb_tech_l0::models::{Delivery, Item, Order};
+
+fn item(chrt_id: i32, name: &str) -> Item {
+    Item { name: name.to_string(), ..common::item(chrt_id) }
+}
+
+/// Has a recipient name that needs quoting in CSV.
+fn order(uid: &str, date_created: &str, items: Vec<Item>) -> Order {
+    common::order(uid)
+        .delivery(Delivery { name: "Test, \"Testov\"".to_string(), ..Default::default() })
+        .items(items)
+        .date_created(date_created)
+        .with(|order| order.payment.payment_dt = 1637907727)
+        .build()
+}
+
+fn app(export: Option<Box<dyn interfaces::OrderExport<Error = Box<dyn std::error::Error>>>>) -> axum::Router {
+    let mut state = AppState::new(Box::<MemoryRepository>::default(), Box::new(OrderService::new()));
+    if let Some(export) = export {
+        state = state.with_export(export);
+    }
+    router(Arc::new(state))
+}
+
+async fn sqlite_app(orders: Vec<Order>) -> axum::Router {
+    let database = SqliteDatabase::open(":memory:").unwrap();
+    for order in orders {
+        database.insert(order).await.unwrap();
+    }
+    let state = AppState::new(Box::new(Repository::new(Cache::new(), database.clone())), Box::new(OrderService::new()))
+        .with_export(Box::new(database));
+    router(Arc::new(state))
+}
+
+async fn get(app: &axum::Router, uri: &str) -> (StatusCode, Option<String>, Bytes) {
+    let response = app.clone().oneshot(Request::get(uri).body(Body::empty()).unwrap()).await.unwrap();
+    let status = response.status();
+    let content_type = response
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .map(|value| value.to_str().unwrap().to_string());
+    (status, content_type, to_bytes(response.into_body(), usize::MAX).await.unwrap())
+}
+
+fn sample() -> Vec<Order> {
+    vec![
+        order("late", "2021-11-28T06:22:19Z", vec![item(1, "Mascaras")]),
+        order("two-items", "2021-11-26T06:22:19Z", vec![item(2, "Lipstick"), item(3, "Brush\nset")]),
+        order("no-items", "2021-11-27T06:22:19Z", vec![]),
+    ]
+}
+
+#[tokio::test]
+async fn csv_has_a_row_per_item() {
+    let app = sqlite_app(sample()).await;
+    let (status, content_type, body) = get(&app, "/orders/export?format=csv&from=2021-11-26&to=2021-11-27").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(content_type.as_deref(), Some("text/csv; charset=utf-8"));
+
+    let mut reader = csv::Reader::from_reader(body.as_ref());
+    let header: Vec<String> = reader.headers().unwrap().iter().map(str::to_string).collect();
+    let names: Vec<&str> = columns().iter().map(|column| column.name.as_str()).collect();
+    assert_eq!(header, names);
+    let rows: Vec<csv::StringRecord> = reader.records().map(Result::unwrap).collect();
+    let column = |row: usize, name: &str| rows[row][header.iter().position(|column| column == name).unwrap()].to_string();
+    assert_eq!(rows.len(), 3);
+    assert_eq!(column(0, "order_uid"), "two-items");
+    assert_eq!(column(0, "item_chrt_id"), "2");
+    assert_eq!(column(1, "item_name"), "Brush\nset");
+    assert_eq!(column(1, "delivery_name"), "Test, \"Testov\"");
+    assert_eq!(column(1, "payment_payment_dt"), "1637907727");
+    assert_eq!(column(2, "order_uid"), "no-items");
+    assert_eq!(column(2, "item_chrt_id"), "");
+}
+
+#[tokio::test]
+async fn parquet_has_the_csv_columns() {
+    let app = sqlite_app(sample()).await;
+    let (status, content_type, body) = get(&app, "/orders/export?format=parquet").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(content_type.as_deref(), Some("application/vnd.apache.parquet"));
+
+    let reader = SerializedFileReader::new(body).unwrap();
+    let schema = reader.metadata().file_metadata().schema_descr();
+    let names: Vec<&str> = schema.columns().iter().map(|column| column.name()).collect();
+    let expected: Vec<&str> = columns().iter().map(|column| column.name.as_str()).collect();
+    assert_eq!(names, expected);
+    let position = |name: &str| expected.iter().position(|column| *column == name).unwrap();
+    let rows: Vec<_> = reader.get_row_iter(None).unwrap().map(Result::unwrap).collect();
+    let field = |row: usize, name: &str| rows[row].get_column_iter().nth(position(name)).unwrap().1.clone();
+    assert_eq!(rows.len(), 4);
+    assert_eq!(field(0, "order_uid"), Field::Str("two-items".to_string()));
+    assert_eq!(field(1, "item_name"), Field::Str("Brush\nset".to_string()));
+    assert_eq!(field(1, "payment_payment_dt"), Field::Long(1637907727));
+    assert_eq!(field(2, "order_uid"), Field::Str("no-items".to_string()));
+    assert_eq!(field(2, "item_chrt_id"), Field::Null);
+    assert_eq!(field(3, "item_chrt_id"), Field::Long(1));
+}
+
+#[tokio::test]
+async fn parquet_is_written_in_row_groups() {
+    let orders = (0..12_500).map(|index| Ok(order(&format!("order-{index}"), "2021-11-26T06:22:19Z", vec![item(index, "Mascaras"); 2])));
+    let chunks: Vec<Vec<u8>> = encode(stream::iter(orders).boxed(), Box::new(ParquetEncoder::new())).try_collect().await.unwrap();
+    // A row group is sent as soon as it's full, before the rest of the file
+    assert!(chunks.len() > 1);
+    let reader = SerializedFileReader::new(Bytes::from(chunks.concat())).unwrap();
+    assert_eq!(reader.metadata().num_row_groups(), 3);
+    assert_eq!(reader.metadata().file_metadata().num_rows(), 25_000);
+}
+
+#[tokio::test]
+async fn ndjson_has_an_order_per_line() {
+    let app = sqlite_app(sample()).await;
+    let (status, content_type, body) = get(&app, "/orders/export?format=ndjson&from=2021-11-27").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(content_type.as_deref(), Some("application/x-ndjson"));
+    let orders: Vec<Order> = std::str::from_utf8(&body)
+        .unwrap()
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    assert_eq!(orders, [sample()[2].clone(), sample()[0].clone()]);
+}
+
+#[tokio::test]
+async fn bad_requests_are_rejected() {
+    let sqlite = sqlite_app(sample()).await;
+    assert_eq!(get(&sqlite, "/orders/export?format=xlsx").await.0, StatusCode::BAD_REQUEST);
+    assert_eq!(get(&sqlite, "/orders/export?from=26.11.2021").await.0, StatusCode::BAD_REQUEST);
+    assert_eq!(get(&app(None), "/orders/export").await.0, StatusCode::NOT_IMPLEMENTED);
+}
+
+#[tokio::test]
+async fn postgres_export_streams_past_a_batch() {
+    let Some(test) = TestDatabase::create().await else { return };
+    let count = 1234;
+    for index in 0..count {
+        let date = format!("2021-11-{:02}T06:22:19Z", 1 + index % 28);
+        test.database.insert(order(&format!("order-{index:04}"), &date, vec![item(index, "Mascaras")])).await.unwrap();
+    }
+    let app = app(Some(Box::new(test.database.clone())));
+    let (status, _, body) = get(&app, "/orders/export?format=ndjson").await;
+    assert_eq!(status, StatusCode::OK);
+    let orders: Vec<Order> = std::str::from_utf8(&body)
+        .unwrap()
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    assert_eq!(orders.len(), count as usize);
+    assert!(orders.windows(2).all(|pair| {
+        (&pair[0].date_created, &pair[0].order_uid) < (&pair[1].date_created, &pair[1].order_uid)
+    }));
+    assert_eq!(orders[0], order("order-0000", "2021-11-01T06:22:19Z", vec![item(0, "Mascaras")]));
+
+    let (_, _, body) = get(&app, "/orders/export?format=csv&from=2021-11-28").await;
+    assert_eq!(csv::Reader::from_reader(body.as_ref()).records().count(), count as usize / 28);
+    test.drop().await;
+}
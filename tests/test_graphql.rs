@@ -0,0 +1,133 @@
+mod common;
+
+use axum::body::{to_bytes, Body};
+use axum::http::{header, Request, StatusCode};
+use common::{item, order, CountingDatabase, Counters};
+use serde_json::{json, Value};
+use std::sync::Arc;
+use tower::ServiceExt;
+use wb_tech_l0::application::{router, AppState};
+use wb_tech_l0::infrastructure::{Cache, OrderService, Repository};
+use wb_tech_l0::interfaces::Database;
+use wb_tech_l0::models::{Delivery, Item};
+
+/// Router over five orders of one customer and what reached the database.
+async fn app() -> (axum::Router, Counters) {
+    let database = CountingDatabase::default();
+    let counters = database.counters.clone();
+    for index in 0..5 {
+        let uid = format!("order-{index}");
+        let order = order(&uid)
+            .delivery(Delivery { name: format!("Recipient of {uid}"), ..Default::default() })
+            .items(vec![item(1), Item { brand: "Maybelline".to_string(), ..item(2) }])
+            .customer_id("graphql")
+            .date_created(&format!("2021-11-2{index}T06:22:19Z"));
+        database.insert(order.build()).await.unwrap();
+    }
+    let state = AppState::new(Box::new(Repository::new(Cache::new(), database)), Box::new(OrderService::new()));
+    (router(Arc::new(state)), counters)
+}
+
+async fn query(app: &axum::Router, query: &str) -> Value {
+    let request = Request::post("/graphql")
+        .header(header::CONTENT_TYPE, "application/json")
+        .body(Body::from(json!({ "query": query }).to_string()))
+        .unwrap();
+    let response = app.clone().oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    serde_json::from_slice(&to_bytes(response.into_body(), usize::MAX).await.unwrap()).unwrap()
+}
+
+#[tokio::test]
+async fn only_selected_fields_are_returned() {
+    let (app, _) = app().await;
+    let response = query(&app, r#"{ order(orderUid: "order-1") { orderUid payment { amount currency } } }"#).await;
+    assert_eq!(
+        response,
+        json!({"data": {"order": {"orderUid": "order-1", "payment": {"amount": 1817, "currency": "USD"}}}})
+    );
+    let response = query(&app, r#"{ order(orderUid: "missing") { orderUid } }"#).await;
+    assert_eq!(response, json!({"data": {"order": null}}));
+}
+
+#[tokio::test]
+async fn a_page_of_orders_is_read_in_one_batch() {
+    let (app, counters) = app().await;
+    let response = query(
+        &app,
+        r#"{ orders(lookup: {customerId: "graphql"}, limit: 3) {
+            nodes { orderUid delivery { name } items(filter: {brand: "Maybelline"}) { chrtId } }
+            nextOffset
+        } }"#,
+    )
+    .await;
+    let page = &response["data"]["orders"];
+    assert_eq!(page["nextOffset"], 3);
+    let nodes = page["nodes"].as_array().unwrap();
+    assert_eq!(nodes.len(), 3);
+    assert_eq!(nodes[0], json!({"orderUid": "order-4", "delivery": {"name": "Recipient of order-4"}, "items": [{"chrtId": 2}]}));
+    assert_eq!(counters.batches(), [3]);
+
+    // Fields of the summary don't need the orders themselves
+    let response = query(&app, r#"{ orders(lookup: {customerId: "graphql"}) { nodes { orderUid dateCreated itemCount } } }"#).await;
+    assert_eq!(response["data"]["orders"]["nodes"][4], json!({"orderUid": "order-0", "dateCreated": "2021-11-20T06:22:19Z", "itemCount": 2}));
+    assert_eq!(counters.batches(), [3]);
+
+    let response = query(&app, r#"{ ordersByUid(orderUids: ["order-2", "missing", "order-0"]) { orderUid entry } }"#).await;
+    assert_eq!(
+        response["data"]["ordersByUid"],
+        json!([{"orderUid": "order-2", "entry": "WBIL"}, null, {"orderUid": "order-0", "entry": "WBIL"}])
+    );
+    // order-2 was cached by the first query
+    assert_eq!(counters.batches(), [3, 2]);
+
+    // Both are cached now, the missing uid is remembered as missing
+    query(&app, r#"{ ordersByUid(orderUids: ["missing", "order-0"]) { orderUid } }"#).await;
+    assert_eq!(counters.batches(), [3, 2]);
+    assert_eq!(counters.reads(), 0, "Orders are never read on their own");
+}
+
+#[tokio::test]
+async fn invalid_queries_are_reported_as_errors() {
+    let (app, _) = app().await;
+    let response = query(&app, r#"{ orders(lookup: {customerId: "graphql"}, limit: 1000) { limit } }"#).await;
+    assert_eq!(response["data"], Value::Null);
+    assert!(response["errors"][0]["message"].as_str().unwrap().contains("limit"), "{response}");
+
+    let response = query(&app, r#"{ orders(lookup: {customerId: "a", trackNumber: "b"}) { limit } }"#).await;
+    assert!(response["errors"].as_array().is_some_and(|errors| !errors.is_empty()), "{response}");
+
+    let response = query(&app, r#"{ order(orderUid: "order-1") { payment { amount { currency } } } }"#).await;
+    assert!(response["errors"].is_array(), "{response}");
+
+    let uids: Vec<String> = (0..101).map(|index| format!("order-{index}")).collect();
+    let response = query(&app, &format!("{{ ordersByUid(orderUids: {uids:?}) {{ orderUid }} }}")).await;
+    assert!(response["errors"][0]["message"].as_str().unwrap().contains("at most 100"), "{response}");
+}
+
+#[tokio::test]
+async fn expensive_queries_are_refused() {
+    let (app, counters) = app().await;
+    let uids: Vec<String> = (0..100).map(|index| format!("order-{index}")).collect();
+    let fields = "orderUid trackNumber entry locale customerId deliveryService shardkey smId dateCreated oofShard
+        delivery { name phone zip address region email }
+        payment { transaction requestId currency provider amount paymentDt bank deliveryCost goodsTotal customFee }
+        items { chrtId trackNumber price rid name sale size totalPrice nmId brand status }";
+    let aliases: String = (0..3).map(|index| format!("page{index}: ordersByUid(orderUids: {uids:?}) {{ {fields} }} ")).collect();
+    let response = query(&app, &format!("{{ {aliases} }}")).await;
+    assert!(response["errors"][0]["message"].as_str().unwrap().contains("complex"), "{response}");
+    assert!(counters.batches().is_empty());
+
+    // A single list of every field is fine
+    let response = query(&app, &format!("{{ ordersByUid(orderUids: {uids:?}) {{ {fields} }} }}")).await;
+    assert!(response.get("errors").is_none(), "{response}");
+}
+
+#[tokio::test]
+async fn graphiql_is_served_to_browsers() {
+    let (app, _) = app().await;
+    let response = app.oneshot(Request::get("/graphql").body(Body::empty()).unwrap()).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    assert!(std::str::from_utf8(&body).unwrap().contains("graphiql"));
+}
@@ -0,0 +1,163 @@
+mod common;
+
+use common::{order, MemoryRepository};
+use futures::StreamExt;
+use std::sync::Arc;
+use std::time::Duration;
+use tonic::transport::Channel;
+use tonic::{Code, Request};
+use tonic_reflection::pb::v1::server_reflection_client::ServerReflectionClient;
+use tonic_reflection::pb::v1::server_reflection_request::MessageRequest;
+use tonic_reflection::pb::v1::server_reflection_response::MessageResponse;
+use tonic_reflection::pb::v1::ServerReflectionRequest;
+use wb_tech_l0::application::grpc::serve_grpc;
+use wb_tech_l0::application::wire::orders::list_orders_request::Lookup;
+use wb_tech_l0::application::wire::orders::order_service_client::OrderServiceClient;
+use wb_tech_l0::application::wire::orders::{self, GetOrderRequest, ListOrdersRequest, StreamOrdersRequest};
+use wb_tech_l0::application::AppState;
+use wb_tech_l0::infrastructure::{
+    ApiKeyConfig, AuthConfig, Authenticator, OrderBroadcaster, OrderService, Quota, RateLimiter,
+};
+use wb_tech_l0::models::Order;
+
+/// Serves the state on a free port and returns a channel to it.
+async fn start(state: AppState) -> Channel {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move { serve_grpc(Arc::new(state), listener).await.unwrap() });
+    Channel::from_shared(format!("http://{addr}")).unwrap().connect().await.unwrap()
+}
+
+fn state() -> AppState {
+    let order_stream = Arc::new(OrderBroadcaster::new(100));
+    AppState::new(
+        Box::<MemoryRepository>::default(),
+        Box::new(OrderService::new().with_notifier(order_stream.clone())),
+    )
+    .with_order_stream(order_stream)
+}
+
+fn with_key<T>(message: T, key: &str) -> Request<T> {
+    let mut request = Request::new(message);
+    request.metadata_mut().insert("x-api-key", key.parse().unwrap());
+    request
+}
+
+#[tokio::test]
+async fn orders_are_added_and_read_back() {
+    let mut client = OrderServiceClient::new(start(state()).await);
+    client.add_order(orders::Order::from(order("grpc").build())).await.unwrap();
+
+    let found = client.get_order(GetOrderRequest { order_uid: "grpc".to_string() }).await.unwrap();
+    assert_eq!(Order::from(found.into_inner()), order("grpc").build());
+
+    let missing = client.get_order(GetOrderRequest { order_uid: "missing".to_string() }).await.unwrap_err();
+    assert_eq!(missing.code(), Code::NotFound);
+    let duplicate = client.add_order(orders::Order::from(order("grpc").build())).await.unwrap_err();
+    assert_eq!(duplicate.code(), Code::AlreadyExists);
+}
+
+#[tokio::test]
+async fn orders_are_listed_in_pages() {
+    let mut client = OrderServiceClient::new(start(state()).await);
+    for uid in ["a", "bb", "ccc"] {
+        let order = order(uid).customer_id("paged").date_created(&format!("2021-11-2{}T06:22:19Z", uid.len()));
+        client.add_order(orders::Order::from(order.build())).await.unwrap();
+    }
+    let list = |offset| ListOrdersRequest {
+        lookup: Some(Lookup::CustomerId("paged".to_string())),
+        limit: Some(2),
+        offset: Some(offset),
+    };
+    let first = client.list_orders(list(0)).await.unwrap().into_inner();
+    let uids: Vec<&str> = first.orders.iter().map(|summary| summary.order_uid.as_str()).collect();
+    assert_eq!(uids, ["ccc", "bb"]);
+    assert_eq!(first.next_offset, Some(2));
+    let last = client.list_orders(list(2)).await.unwrap().into_inner();
+    assert_eq!(last.orders.len(), 1);
+    assert_eq!(last.next_offset, None);
+
+    let no_lookup = ListOrdersRequest { lookup: None, limit: None, offset: None };
+    assert_eq!(client.list_orders(no_lookup).await.unwrap_err().code(), Code::InvalidArgument);
+    let too_big = ListOrdersRequest { limit: Some(1000), ..list(0) };
+    assert_eq!(client.list_orders(too_big).await.unwrap_err().code(), Code::InvalidArgument);
+}
+
+#[tokio::test]
+async fn streamed_orders_are_filtered_and_resumable() {
+    let mut client = OrderServiceClient::new(start(state()).await);
+    client.add_order(orders::Order::from(order("before").build())).await.unwrap();
+    let request = StreamOrdersRequest { entry: "WBIL".to_string(), after_id: Some(0), ..Default::default() };
+    let mut events = client.stream_orders(request).await.unwrap().into_inner();
+
+    let replayed = events.next().await.unwrap().unwrap();
+    assert_eq!(replayed.order.unwrap().order_uid, "before");
+    client.add_order(orders::Order::from(order("other").entry("L").build())).await.unwrap();
+    client.add_order(orders::Order::from(order("live").build())).await.unwrap();
+    let live = tokio::time::timeout(Duration::from_secs(5), events.next()).await.unwrap().unwrap().unwrap();
+    assert_eq!(live.order.unwrap().order_uid, "live");
+    assert!(live.id > replayed.id);
+
+    let disabled = AppState::new(Box::<MemoryRepository>::default(), Box::new(OrderService::new()));
+    let mut client = OrderServiceClient::new(start(disabled).await);
+    let status = client.stream_orders(StreamOrdersRequest::default()).await.unwrap_err();
+    assert_eq!(status.code(), Code::Unimplemented);
+}
+
+#[tokio::test]
+async fn calls_need_the_scope_of_their_http_route() {
+    let key = |name: &str, scopes: &[&str]| ApiKeyConfig {
+        name: name.to_string(),
+        sha256: Authenticator::hash_key(&format!("{name}-key")),
+        scopes: scopes.iter().map(|scope| scope.to_string()).collect(),
+    };
+    let authenticator = Authenticator::new(AuthConfig {
+        api_keys: vec![key("producer", &["orders:write"]), key("reader", &["orders:read"])],
+        jwt: None,
+    })
+    .unwrap();
+    let mut client = OrderServiceClient::new(start(state().with_authenticator(Box::new(authenticator))).await);
+    let get = |key: &str| with_key(GetOrderRequest { order_uid: "secured".to_string() }, key);
+
+    let anonymous = client.add_order(orders::Order::from(order("secured").build())).await.unwrap_err();
+    assert_eq!(anonymous.code(), Code::Unauthenticated);
+    let unknown = client.get_order(get("stolen-key")).await.unwrap_err();
+    assert_eq!(unknown.code(), Code::Unauthenticated);
+    let reader = with_key(orders::Order::from(order("secured").build()), "reader-key");
+    assert_eq!(client.add_order(reader).await.unwrap_err().code(), Code::PermissionDenied);
+
+    let producer = with_key(orders::Order::from(order("secured").build()), "producer-key");
+    client.add_order(producer).await.unwrap();
+    assert_eq!(client.get_order(get("producer-key")).await.unwrap_err().code(), Code::PermissionDenied);
+    assert_eq!(client.get_order(get("reader-key")).await.unwrap().into_inner().order_uid, "secured");
+}
+
+#[tokio::test]
+async fn calls_share_the_http_rate_limits() {
+    let limiter = RateLimiter::new(Quota { per_second: 0.01, burst: 1 });
+    let mut client = OrderServiceClient::new(start(state().with_rate_limits(None, Some(limiter))).await);
+    client.add_order(orders::Order::from(order("limited").build())).await.unwrap();
+
+    let refused = client.add_order(orders::Order::from(order("refused").build())).await.unwrap_err();
+    assert_eq!(refused.code(), Code::ResourceExhausted);
+    let retry_after: u64 = refused.metadata().get("retry-after").unwrap().to_str().unwrap().parse().unwrap();
+    assert!(retry_after >= 1);
+    // Reads have their own budget
+    client.get_order(GetOrderRequest { order_uid: "limited".to_string() }).await.unwrap();
+}
+
+#[tokio::test]
+async fn reflection_lists_the_order_service() {
+    let mut client = ServerReflectionClient::new(start(state()).await);
+    let request = ServerReflectionRequest {
+        host: String::new(),
+        message_request: Some(MessageRequest::ListServices(String::new())),
+    };
+    let mut responses = client.server_reflection_info(futures::stream::iter([request])).await.unwrap().into_inner();
+    let Some(MessageResponse::ListServicesResponse(list)) = responses.next().await.unwrap().unwrap().message_response
+    else {
+        panic!("Expected a list of services");
+    };
+    let services: Vec<&str> = list.service.iter().map(|service| service.name.as_str()).collect();
+    assert!(services.contains(&"wb_tech_l0.orders.OrderService"), "{services:?}");
+}
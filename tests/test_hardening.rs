@@ -0,0 +1,136 @@
+mod common;
+
+use axum::async_trait;
+use axum::body::Body;
+use axum::http::{header, Method, Request, StatusCode};
+use common::{item, order, MemoryRepository};
+use std::error::Error;
+use std::sync::Arc;
+use std::time::Duration;
+use tower::ServiceExt;
+use wb_tech_l0::application::{router, AppState, HttpSettings};
+use wb_tech_l0::infrastructure::OrderService;
+use wb_tech_l0::interfaces::{self, Repository};
+use wb_tech_l0::models::{CustomerOrders, Item, Order, OrderLookup, OrderSummary};
+
+struct SlowRepository(Duration);
+
+#[async_trait]
+impl interfaces::Repository for SlowRepository {
+    type Error = Box<dyn Error>;
+    async fn insert(&self, _order: Order) -> Result<(), Self::Error> {
+        tokio::time::sleep(self.0).await;
+        Ok(())
+    }
+
+    async fn remove(&self, _id: &str) -> Result<(), Self::Error> {
+        Ok(())
+    }
+
+    async fn get(&self, _id: &str) -> Result<Option<Order>, Self::Error> {
+        tokio::time::sleep(self.0).await;
+        Ok(None)
+    }
+
+    async fn get_and_cache(&self, id: &str) -> Result<Option<Order>, Self::Error> {
+        self.get(id).await
+    }
+
+    async fn find(&self, _lookup: &OrderLookup, _limit: i64, _offset: i64) -> Result<Vec<OrderSummary>, Self::Error> {
+        tokio::time::sleep(self.0).await;
+        Ok(Vec::new())
+    }
+
+    async fn customer_orders(&self, _customer_id: &str, _limit: i64, _offset: i64) -> Result<CustomerOrders, Self::Error> {
+        tokio::time::sleep(self.0).await;
+        Ok(CustomerOrders::default())
+    }
+}
+
+/// Items with names long enough to make an order compressible.
+fn items(count: usize) -> Vec<Item> {
+    vec![Item { name: "A rather long item name to make the order compressible".to_string(), ..item(9934930) }; count]
+}
+
+fn add_order(order: &Order) -> Request<Body> {
+    Request::post("/add_order")
+        .header(header::CONTENT_TYPE, "application/json")
+        .body(Body::from(serde_json::to_vec(order).unwrap()))
+        .unwrap()
+}
+
+#[tokio::test]
+async fn oversized_order_is_rejected_with_413() {
+    let state = AppState::new(Box::<MemoryRepository>::default(), Box::new(OrderService::new()))
+        .with_http_settings(HttpSettings {
+            max_order_body: 4096,
+            ..Default::default()
+        });
+    let app = router(Arc::new(state));
+    let response = app.clone().oneshot(add_order(&order("small").items(items(1)).build())).await.unwrap();
+    assert_eq!(response.status(), StatusCode::CREATED);
+    let response = app.oneshot(add_order(&order("large").items(items(100)).build())).await.unwrap();
+    assert_eq!(response.status(), StatusCode::PAYLOAD_TOO_LARGE);
+}
+
+#[tokio::test]
+async fn order_is_compressed_when_client_accepts_it() {
+    let repository = MemoryRepository::default();
+    repository.insert(order("order1").items(items(50)).build()).await.unwrap();
+    let state = AppState::new(Box::new(repository), Box::new(OrderService::new()));
+    let app = router(Arc::new(state));
+    let request = Request::get("/order/order1")
+        .header(header::ACCEPT_ENCODING, "gzip")
+        .body(Body::empty())
+        .unwrap();
+    let response = app.oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.headers()[header::CONTENT_ENCODING], "gzip");
+}
+
+#[tokio::test]
+async fn slow_requests_time_out_with_503() {
+    let state = AppState::new(
+        Box::new(SlowRepository(Duration::from_secs(5))),
+        Box::new(OrderService::new()),
+    )
+    .with_http_settings(HttpSettings {
+        read_timeout: Duration::from_millis(50),
+        write_timeout: Duration::from_millis(50),
+        ..Default::default()
+    });
+    let app = router(Arc::new(state));
+    let response = app
+        .clone()
+        .oneshot(Request::get("/order/order1").body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+    let response = app.oneshot(add_order(&order("order1").items(items(1)).build())).await.unwrap();
+    assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+}
+
+#[tokio::test]
+async fn cors_preflight_allows_configured_origin_only() {
+    let state = AppState::new(Box::<MemoryRepository>::default(), Box::new(OrderService::new()))
+        .with_http_settings(HttpSettings {
+            cors_origins: vec!["https://shop.example".to_string()],
+            ..Default::default()
+        });
+    let app = router(Arc::new(state));
+    let preflight = |origin: &str| {
+        Request::builder()
+            .method(Method::OPTIONS)
+            .uri("/add_order")
+            .header(header::ORIGIN, origin)
+            .header(header::ACCESS_CONTROL_REQUEST_METHOD, "POST")
+            .header(header::ACCESS_CONTROL_REQUEST_HEADERS, "content-type,x-api-key")
+            .body(Body::empty())
+            .unwrap()
+    };
+    let response = app.clone().oneshot(preflight("https://shop.example")).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.headers()[header::ACCESS_CONTROL_ALLOW_ORIGIN], "https://shop.example");
+    let response = app.oneshot(preflight("https://elsewhere.example")).await.unwrap();
+    assert!(!response.headers().contains_key(header::ACCESS_CONTROL_ALLOW_ORIGIN));
+}
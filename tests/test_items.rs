@@ -0,0 +1,82 @@
+mod common;
+mod pg;
+
+use common::order;
+use pg::TestDatabase;
+use wb_tech_l0::interfaces::Database;
+use wb_tech_l0::models::Item;
+
+fn item(chrt_id: i32, price: i32, rid: &str, track_number: &str) -> Item {
+    let track_number = track_number.to_string();
+    Item { track_number, price, rid: rid.to_string(), total_price: price * 7 / 10, ..common::item(chrt_id) }
+}
+
+#[tokio::test]
+async fn orders_sharing_chrt_id_keep_their_own_items() {
+    let Some(test) = TestDatabase::create().await else { return };
+    let first = order("first").items(vec![item(9934930, 453, "ab4219087a764ae0btest", "first-track")]).build();
+    let second = order("second").items(vec![item(9934930, 999, "cd4219087a764ae0btest", "second-track")]).build();
+    test.database.insert(first.clone()).await.unwrap();
+    test.database.insert(second.clone()).await.unwrap();
+    assert_eq!(test.database.get("first").await.unwrap(), Some(first));
+    assert_eq!(test.database.get("second").await.unwrap(), Some(second));
+    test.drop().await;
+}
+
+#[tokio::test]
+async fn items_come_back_in_the_order_they_were_sent() {
+    let Some(test) = TestDatabase::create().await else { return };
+    let mut items = vec![
+        item(3, 300, "c", "track"),
+        item(1, 100, "a", "track"),
+        item(2, 200, "b", "track"),
+    ];
+    // The same article twice, in different sizes
+    items.push(Item {
+        size: "XL".to_string(),
+        ..item(1, 100, "d", "track")
+    });
+    let order = order("unordered").items(items).build();
+    test.database.insert(order.clone()).await.unwrap();
+    assert_eq!(test.database.get("unordered").await.unwrap(), Some(order));
+    test.drop().await;
+}
+
+#[tokio::test]
+async fn order_without_items_round_trips() {
+    let Some(test) = TestDatabase::create().await else { return };
+    let order = order("empty").items(Vec::new()).build();
+    test.database.insert(order.clone()).await.unwrap();
+    assert_eq!(test.database.get("empty").await.unwrap(), Some(order));
+    test.drop().await;
+}
+
+#[tokio::test]
+async fn shared_items_are_copied_to_every_order() {
+    let Some(mut test) = TestDatabase::create_at(7).await else { return };
+    // Two orders referencing the same shared item row, the way the old schema stored them
+    test.client()
+        .await
+        .batch_execute(
+            "INSERT INTO Orders VALUES ('first', 'T', 'WBIL', 'en', '', 'test', 'meest', '9', 99, '2021-11-26T06:22:19Z', '1'),
+                                       ('second', 'T', 'WBIL', 'en', '', 'test', 'meest', '9', 99, '2021-11-27T06:22:19Z', '1');
+             INSERT INTO Deliveries VALUES (1, 'Test Testov', '+9720000000', '2639809', 'Ploshad Mira 15', 'Kraiot', 'test@gmail.com'),
+                                           (2, 'Test Testov', '+9720000000', '2639809', 'Ploshad Mira 15', 'Kraiot', 'test@gmail.com');
+             INSERT INTO OrderDeliveries VALUES ('first', 1), ('second', 2);
+             INSERT INTO Payments VALUES ('first', '', 'USD', 'wbpay', 1817, 1637907727, 'alpha', 1500, 317, 0),
+                                         ('second', '', 'USD', 'wbpay', 1817, 1637907727, 'alpha', 1500, 317, 0);
+             INSERT INTO OrderPayments VALUES ('first', 'first'), ('second', 'second');
+             INSERT INTO Items VALUES (9934930, 'WBILMTESTTRACK', 453, 'ab4219087a764ae0btest', 'Mascaras', 30, '0',
+                                       317, 2389212, 'Vivienne Sabo', 202);
+             INSERT INTO OrderItems VALUES ('first', 9934930), ('second', 9934930);",
+        )
+        .await
+        .unwrap();
+    test.migrate().await;
+    let shared = item(9934930, 453, "ab4219087a764ae0btest", "WBILMTESTTRACK");
+    for uid in ["first", "second"] {
+        let order = test.database.get(uid).await.unwrap().unwrap();
+        assert_eq!(order.items, vec![shared.clone()]);
+    }
+    test.drop().await;
+}
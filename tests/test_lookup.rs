@@ -0,0 +1,106 @@
+mod common;
+mod pg;
+
+use axum::body::{to_bytes, Body};
+use axum::http::{Request, StatusCode};
+use common::{item, order, MemoryRepository};
+use pg::TestDatabase;
+use serde_json::Value;
+use std::error::Error;
+use std::sync::Arc;
+use tower::ServiceExt;
+use wb_tech_l0::application::{router, AppState};
+use wb_tech_l0::infrastructure::{Cache, OrderService, Repository as OrderRepository};
+use wb_tech_l0::interfaces::Repository;
+use wb_tech_l0::models::Item;
+
+async fn seeded(repository: Box<dyn Repository<Error = Box<dyn Error>>>) -> axum::Router {
+    let orders: [(&str, &str, &str, &[i32]); 3] = [
+        ("first", "TRACK1", "2021-11-26T06:22:19Z", &[1, 2, 2]),
+        ("second", "TRACK1", "2021-11-27T06:22:19Z", &[2]),
+        ("third", "TRACK2", "2021-11-28T06:22:19Z", &[3]),
+    ];
+    for (uid, track_number, date_created, chrt_ids) in orders {
+        let items = chrt_ids.iter().map(|&chrt_id| Item { nm_id: chrt_id * 10, ..item(chrt_id) }).collect();
+        let order = order(uid).track_number(track_number).date_created(date_created).items(items);
+        let order = order.with(|order| order.payment.request_id = "request".to_string());
+        repository.insert(order.build()).await.unwrap();
+    }
+    let state = AppState::new(repository, Box::new(OrderService::new()));
+    router(Arc::new(state))
+}
+
+async fn app() -> axum::Router {
+    seeded(Box::<MemoryRepository>::default()).await
+}
+
+/// The same orders in a Postgres test database, to be dropped afterwards.
+async fn postgres_app() -> Option<(axum::Router, TestDatabase)> {
+    let test = TestDatabase::create().await?;
+    let app = seeded(Box::new(OrderRepository::new(Cache::new(), test.database.clone()))).await;
+    Some((app, test))
+}
+
+async fn find(app: &axum::Router, query: &str) -> (StatusCode, Value) {
+    let request = Request::get(format!("/orders?{query}")).body(Body::empty()).unwrap();
+    let response = app.clone().oneshot(request).await.unwrap();
+    let status = response.status();
+    let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    (status, serde_json::from_slice(&body).unwrap())
+}
+
+fn uids(body: &Value) -> Vec<&str> {
+    body["orders"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|order| order["order_uid"].as_str().unwrap())
+        .collect()
+}
+
+async fn assert_found_by_every_key(app: &axum::Router) {
+    assert_eq!(uids(&find(app, "track_number=TRACK1").await.1), ["second", "first"]);
+    assert_eq!(uids(&find(app, "transaction=third").await.1), ["third"]);
+    assert_eq!(uids(&find(app, "request_id=request").await.1), ["third", "second", "first"]);
+    // An item repeated within an order doesn't repeat the order
+    let (_, body) = find(app, "chrt_id=2").await;
+    assert_eq!(uids(&body), ["second", "first"]);
+    assert_eq!(body["orders"][1]["item_count"], 3);
+    assert_eq!(uids(&find(app, "nm_id=10").await.1), ["first"]);
+    assert!(uids(&find(app, "track_number=missing").await.1).is_empty());
+}
+
+async fn assert_paginated(app: &axum::Router) {
+    let (status, body) = find(app, "request_id=request&limit=2").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(uids(&body), ["third", "second"]);
+    assert_eq!(body["next_offset"], 2);
+    let (_, body) = find(app, "request_id=request&limit=2&offset=2").await;
+    assert_eq!(uids(&body), ["first"]);
+    assert!(body["next_offset"].is_null());
+}
+
+#[tokio::test]
+async fn orders_are_found_by_every_key_newest_first() {
+    assert_found_by_every_key(&app().await).await;
+}
+
+#[tokio::test]
+async fn lookups_are_paginated() {
+    assert_paginated(&app().await).await;
+}
+
+#[tokio::test]
+async fn postgres_finds_orders_by_every_key_newest_first() {
+    let Some((app, test)) = postgres_app().await else { return };
+    assert_found_by_every_key(&app).await;
+    assert_paginated(&app).await;
+    test.drop().await;
+}
+
+#[tokio::test]
+async fn exactly_one_key_is_required() {
+    let app = app().await;
+    assert_eq!(find(&app, "limit=5").await.0, StatusCode::BAD_REQUEST);
+    assert_eq!(find(&app, "track_number=TRACK1&chrt_id=1").await.0, StatusCode::BAD_REQUEST);
+}
@@ -0,0 +1,33 @@
+use std::path::PathBuf;
+use wb_tech_l0::application::openapi;
+
+fn committed_spec_path() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("API/openapi.json")
+}
+
+/// Run with `UPDATE_OPENAPI=1` to regenerate `API/openapi.json` after changing handlers or models.
+#[test]
+fn committed_spec_matches_code() {
+    let generated = openapi().to_pretty_json().unwrap() + "\n";
+    let path = committed_spec_path();
+    if std::env::var_os("UPDATE_OPENAPI").is_some() {
+        std::fs::write(&path, &generated).unwrap();
+    }
+    let committed = std::fs::read_to_string(&path).unwrap_or_default();
+    assert!(
+        committed == generated,
+        "API/openapi.json is out of date, rerun the tests with UPDATE_OPENAPI=1 and commit the result"
+    );
+}
+
+#[test]
+fn spec_documents_models_and_routes() {
+    let spec = serde_json::to_value(openapi()).unwrap();
+    assert!(spec["openapi"].as_str().unwrap().starts_with("3.1"));
+    for path in ["/order/{order_uid}", "/add_order", "/orders/stream", "/webhooks", "/webhooks/{id}"] {
+        assert!(spec["paths"].get(path).is_some(), "{path} is not documented");
+    }
+    for schema in ["Order", "Delivery", "Payment", "Item"] {
+        assert!(spec["components"]["schemas"].get(schema).is_some(), "{schema} schema is missing");
+    }
+}
@@ -0,0 +1,195 @@
+mod common;
+
+use axum::body::{to_bytes, Body};
+use axum::http::{header, Request, StatusCode};
+use axum::Router;
+use common::MemoryRepository;
+use proptest::prelude::*;
+use serde_json::Value;
+use std::sync::Arc;
+use tokio::runtime::Runtime;
+use tower::ServiceExt;
+use wb_tech_l0::application::{openapi, router, AppState};
+use wb_tech_l0::infrastructure::OrderService;
+use wb_tech_l0::models::{Delivery, Item, Order, Payment};
+
+/// Any text, including control characters, NUL, quotes, combining marks and the empty string.
+fn text() -> BoxedStrategy<String> {
+    prop_oneof![
+        "(?s).{0,24}",
+        Just(String::new()),
+        "[\"\\\\/\\x00\\n%?#.]{1,8}",
+        "[а-яё👩‍👧☃̀]{1,12}",
+    ]
+    .boxed()
+}
+
+/// Identifiers must not be empty, `/order/` doesn't match the route.
+fn id() -> BoxedStrategy<String> {
+    prop_oneof!["(?s).{1,24}", "[.]{1,3}", "[a-z0-9]{1,20}"].boxed()
+}
+
+fn number() -> BoxedStrategy<i32> {
+    prop_oneof![any::<i32>(), Just(0), Just(i32::MIN), Just(i32::MAX)].boxed()
+}
+
+prop_compose! {
+    fn delivery()(
+        name in text(), phone in text(), zip in text(), address in text(), region in text(), email in text(),
+    ) -> Delivery {
+        Delivery { name, phone, zip, address, region, email }
+    }
+}
+
+prop_compose! {
+    fn payment()(
+        transaction in text(), request_id in text(), currency in text(), provider in text(), amount in number(),
+        payment_dt in number(), bank in text(), delivery_cost in number(), goods_total in number(), custom_fee in number(),
+    ) -> Payment {
+        Payment {
+            transaction, request_id, currency, provider, amount, payment_dt, bank, delivery_cost, goods_total, custom_fee,
+        }
+    }
+}
+
+prop_compose! {
+    fn item()(
+        chrt_id in number(), track_number in text(), price in number(), rid in text(), name in text(), sale in number(),
+        size in text(), total_price in number(), nm_id in number(), brand in text(), status in number(),
+    ) -> Item {
+        Item { chrt_id, track_number, price, rid, name, sale, size, total_price, nm_id, brand, status }
+    }
+}
+
+prop_compose! {
+    fn order()(
+        order_uid in id(), track_number in text(), entry in text(), delivery in delivery().boxed(), payment in payment().boxed(),
+        items in prop::collection::vec(item().boxed(), 0..8), locale in text(), internal_signature in text(),
+        customer_id in text(), delivery_service in text(), shardkey in text(), sm_id in number(),
+        date_created in text(), oof_shard in text(),
+    ) -> Order {
+        Order {
+            order_uid, track_number, entry, delivery, payment, items, locale, internal_signature, customer_id,
+            delivery_service, shardkey, sm_id, date_created, oof_shard,
+        }
+    }
+}
+
+/// Any JSON value, nested a few levels deep.
+fn json() -> impl Strategy<Value = Value> {
+    let leaf = prop_oneof![
+        Just(Value::Null),
+        any::<bool>().prop_map(Value::from),
+        any::<i64>().prop_map(Value::from),
+        any::<f64>().prop_map(Value::from),
+        text().prop_map(Value::from),
+    ];
+    leaf.prop_recursive(3, 24, 4, |inner| {
+        prop_oneof![
+            prop::collection::vec(inner.clone(), 0..4).prop_map(Value::from),
+            prop::collection::hash_map(text(), inner, 0..4).prop_map(|map| Value::Object(map.into_iter().collect())),
+        ]
+    })
+}
+
+fn app() -> Router {
+    let state = AppState::new(Box::<MemoryRepository>::default(), Box::new(OrderService::new()));
+    router(Arc::new(state))
+}
+
+/// Statuses the OpenAPI document lists for the operation.
+fn documented(path: &str, method: &str) -> Vec<StatusCode> {
+    let spec = serde_json::to_value(openapi()).unwrap();
+    let responses = spec["paths"][path][method]["responses"].as_object().unwrap();
+    responses.keys().map(|status| status.parse().unwrap()).collect()
+}
+
+fn encode(segment: &str) -> String {
+    segment
+        .bytes()
+        .map(|byte| match byte {
+            b'a'..=b'z' | b'A'..=b'Z' | b'0'..=b'9' => (byte as char).to_string(),
+            _ => format!("%{byte:02X}"),
+        })
+        .collect()
+}
+
+fn add_order(body: impl Into<Body>) -> Request<Body> {
+    Request::post("/add_order")
+        .header(header::CONTENT_TYPE, "application/json")
+        .body(body.into())
+        .unwrap()
+}
+
+fn get_order(order_uid: &str) -> Request<Body> {
+    Request::get(format!("/order/{}", encode(order_uid))).body(Body::empty()).unwrap()
+}
+
+async fn send(app: &Router, request: Request<Body>) -> (StatusCode, Vec<u8>) {
+    let response = app.clone().oneshot(request).await.unwrap();
+    let status = response.status();
+    (status, to_bytes(response.into_body(), usize::MAX).await.unwrap().to_vec())
+}
+
+/// Paths of every leaf in the document, as JSON pointers.
+fn leaves(value: &Value, path: String, found: &mut Vec<String>) {
+    match value {
+        Value::Object(map) => map.iter().for_each(|(key, value)| leaves(value, format!("{path}/{key}"), found)),
+        Value::Array(values) => values.iter().enumerate().for_each(|(n, value)| leaves(value, format!("{path}/{n}"), found)),
+        _ => found.push(path),
+    }
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(128))]
+
+    #[test]
+    fn accepted_orders_come_back_unchanged(order in order()) {
+        let (added, fetched) = Runtime::new().unwrap().block_on(async {
+            let app = app();
+            let added = send(&app, add_order(serde_json::to_vec(&order).unwrap())).await;
+            (added, send(&app, get_order(&order.order_uid)).await)
+        });
+        prop_assert_eq!(added.0, StatusCode::CREATED);
+        prop_assert_eq!(fetched.0, StatusCode::OK);
+        prop_assert_eq!(serde_json::from_slice::<Order>(&fetched.1).unwrap(), order);
+    }
+
+    #[test]
+    fn arbitrary_bodies_get_a_documented_status(body in prop::collection::vec(any::<u8>(), 0..512)) {
+        let (status, _) = Runtime::new().unwrap().block_on(send(&app(), add_order(body)));
+        prop_assert!(documented("/add_order", "post").contains(&status), "undocumented {}", status);
+    }
+
+    #[test]
+    fn malformed_orders_get_a_documented_status(order in order(), leaf in any::<prop::sample::Index>(), value in json()) {
+        let mut document = serde_json::to_value(&order).unwrap();
+        let mut found = Vec::new();
+        leaves(&document, String::new(), &mut found);
+        let pointer: &String = leaf.get(&found);
+        *document.pointer_mut(pointer).unwrap() = value;
+        let (status, _) = Runtime::new().unwrap().block_on(send(&app(), add_order(document.to_string())));
+        prop_assert!(documented("/add_order", "post").contains(&status), "undocumented {}", status);
+    }
+
+    #[test]
+    fn unknown_orders_are_not_found(order_uid in id()) {
+        let (status, body) = Runtime::new().unwrap().block_on(send(&app(), get_order(&order_uid)));
+        prop_assert_eq!(status, StatusCode::NOT_FOUND);
+        let documented = documented("/order/{order_uid}", "get");
+        prop_assert!(documented.contains(&status));
+        prop_assert!(serde_json::from_slice::<Value>(&body).unwrap()["error"].is_string());
+    }
+
+    #[test]
+    fn duplicate_orders_are_rejected(order in order()) {
+        let (first, second) = Runtime::new().unwrap().block_on(async {
+            let app = app();
+            let body = serde_json::to_vec(&order).unwrap();
+            (send(&app, add_order(body.clone())).await.0, send(&app, add_order(body)).await.0)
+        });
+        prop_assert_eq!(first, StatusCode::CREATED);
+        prop_assert!(documented("/add_order", "post").contains(&second), "undocumented {}", second);
+        prop_assert_ne!(second, StatusCode::CREATED);
+    }
+}
@@ -1,50 +1,18 @@
-use wb_tech_l0::models::Order;
+mod common;
+
+use common::MemoryRepository;
+use wb_tech_l0::models::{Order, Principal};
 use wb_tech_l0::interfaces::{OrderService, self};
 use std::error::Error;
-use std::collections::HashMap;
 use std::ops::Deref;
-use std::sync::Arc;
-use tokio::sync::RwLock;
-use axum::async_trait;
 use wb_tech_l0::infrastructure;
 
-#[derive(Default)]
-pub struct MockRepository {
-    orders: Arc<RwLock<HashMap<String, Order>>>,
-}
-
 type Repository = dyn interfaces::Repository<Error = Box<dyn Error>>;
 
-#[async_trait]
-impl interfaces::Repository for MockRepository {
-    type Error = Box<dyn Error>;
-    async fn insert(&self, order: Order) -> Result<(), Self::Error> {
-        let mut wlock = self.orders.write().await;
-        if wlock.contains_key(&order.order_uid) {
-            return Err("already exists".into());
-        }
-        wlock.insert(order.order_uid.to_string(), order);
-        Ok(())
-    }
-
-    async fn remove(&self, id: &str) -> Result<(), Self::Error> {
-        self.orders.write().await.remove(id);
-        Ok(())
-    }
-
-    async fn get(&self, id: &str) -> Result<Option<Order>, Self::Error> {
-        Ok(self.orders.read().await.get(id).cloned())
-    }
-
-    async fn get_and_cache(&self, id: &str) -> Result<Option<Order>, Self::Error> {
-        self.get(id).await
-    }
-}
-
 #[tokio::test]
 async fn add_order() {
-    let mock_repo: Box<Repository> = Box::new(MockRepository::default());
-    let order_service = infrastructure::OrderService;
+    let mock_repo: Box<Repository> = Box::<MemoryRepository>::default();
+    let order_service = infrastructure::OrderService::new();
 
     let order = Order {
         order_uid: "order1".to_string(),
@@ -62,16 +30,16 @@ async fn add_order() {
         date_created: "2023-10-01T12:00:00Z".to_string(),
         oof_shard: "1".to_string(),
     };
-    let result = order_service.add_order(mock_repo.deref(), order.clone()).await;
+    let result = order_service.add_order(mock_repo.deref(), order.clone(), &Principal::anonymous()).await;
     assert!(result.is_ok());
-    let result = order_service.add_order(mock_repo.deref(), order).await;
+    let result = order_service.add_order(mock_repo.deref(), order, &Principal::anonymous()).await;
     assert!(result.is_err());
 }
 
 #[tokio::test]
 async fn test_get_order() {
-    let mut mock_repo: Box<Repository> = Box::new(MockRepository::default());
-    let order_service = infrastructure::OrderService;
+    let mock_repo: Box<Repository> = Box::<MemoryRepository>::default();
+    let order_service = infrastructure::OrderService::new();
 
     let order = Order {
         order_uid: "order1".to_string(),
@@ -90,10 +58,10 @@ async fn test_get_order() {
         oof_shard: "1".to_string(),
     };
     mock_repo.insert(order.clone()).await.unwrap();
-    let result = order_service.get_order("order1", mock_repo.deref()).await;
+    let result = order_service.get_order("order1", mock_repo.deref(), &Principal::anonymous()).await;
     assert!(result.is_ok());
     assert_eq!(result.unwrap().unwrap().order_uid, "order1");
-    let result = order_service.get_order("non_existent_order", mock_repo.deref()).await;
+    let result = order_service.get_order("non_existent_order", mock_repo.deref(), &Principal::anonymous()).await;
     assert!(result.is_ok());
     assert!(result.unwrap().is_none());
 }
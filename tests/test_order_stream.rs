@@ -0,0 +1,38 @@
+mod common;
+
+use common::order;
+use wb_tech_l0::infrastructure::OrderBroadcaster;
+
+#[tokio::test]
+async fn live_subscriber_receives_new_orders() {
+    let broadcaster = OrderBroadcaster::new(10);
+    broadcaster.publish(order("before").build());
+    let (missed, mut receiver) = broadcaster.subscribe(None);
+    assert!(missed.is_empty());
+    broadcaster.publish(order("after").build());
+    let received = receiver.recv().await.unwrap();
+    assert_eq!(received.id, 2);
+    assert_eq!(received.order.order_uid, "after");
+}
+
+#[tokio::test]
+async fn resume_replays_only_newer_orders() {
+    let broadcaster = OrderBroadcaster::new(10);
+    for uid in ["a", "b", "c"] {
+        broadcaster.publish(order(uid).build());
+    }
+    let (missed, _) = broadcaster.subscribe(Some(1));
+    let uids: Vec<_> = missed.iter().map(|streamed| streamed.order.order_uid.as_str()).collect();
+    assert_eq!(uids, ["b", "c"]);
+}
+
+#[tokio::test]
+async fn replay_buffer_is_bounded() {
+    let broadcaster = OrderBroadcaster::new(2);
+    for uid in ["a", "b", "c"] {
+        broadcaster.publish(order(uid).build());
+    }
+    let (missed, _) = broadcaster.subscribe(Some(0));
+    let ids: Vec<_> = missed.iter().map(|streamed| streamed.id).collect();
+    assert_eq!(ids, [2, 3]);
+}
@@ -0,0 +1,117 @@
+use axum::async_trait;
+use serde_json::json;
+use std::error::Error;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::Mutex;
+use wb_tech_l0::infrastructure::{HttpSink, OutboxRelay, RelayConfig};
+use wb_tech_l0::interfaces::{EventSink, Outbox};
+use wb_tech_l0::models::OrderEvent;
+
+type Failures = Arc<Mutex<Vec<(i64, Option<Duration>)>>>;
+
+#[derive(Default, Clone)]
+struct MockOutbox {
+    pending: Arc<Mutex<Vec<OrderEvent>>>,
+    delivered: Arc<Mutex<Vec<i64>>>,
+    failed: Failures,
+}
+
+#[async_trait]
+impl Outbox for MockOutbox {
+    type Error = Box<dyn Error>;
+
+    async fn claim_pending(&self, limit: i64, _lease: Duration) -> Result<Vec<OrderEvent>, Self::Error> {
+        let mut pending = self.pending.lock().await;
+        let count = pending.len().min(limit as usize);
+        Ok(pending.drain(..count).collect())
+    }
+
+    async fn mark_delivered(&self, id: i64) -> Result<(), Self::Error> {
+        self.delivered.lock().await.push(id);
+        Ok(())
+    }
+
+    async fn mark_failed(&self, id: i64, _error: &str, retry_in: Option<Duration>) -> Result<(), Self::Error> {
+        self.failed.lock().await.push((id, retry_in));
+        Ok(())
+    }
+}
+
+struct FlakySink {
+    failures_left: AtomicUsize,
+}
+
+#[async_trait]
+impl EventSink for FlakySink {
+    async fn publish(&self, _event: &OrderEvent) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let left = self.failures_left.load(Ordering::SeqCst);
+        if left > 0 {
+            self.failures_left.store(left - 1, Ordering::SeqCst);
+            return Err("sink unavailable".into());
+        }
+        Ok(())
+    }
+}
+
+fn event(id: i64, attempts: i32) -> OrderEvent {
+    OrderEvent {
+        id,
+        event_type: OrderEvent::ORDER_CREATED.to_string(),
+        order_uid: format!("order{id}"),
+        created_at: "2023-10-01 12:00:00+00".to_string(),
+        attempts,
+        payload: json!({}),
+    }
+}
+
+#[tokio::test]
+async fn relay_retries_with_backoff() {
+    let outbox = MockOutbox::default();
+    outbox.pending.lock().await.extend([event(1, 0), event(2, 2)]);
+    let sink = Box::new(FlakySink { failures_left: AtomicUsize::new(2) });
+    let relay = OutboxRelay::new(outbox.clone(), sink, RelayConfig::default());
+
+    assert_eq!(relay.run_once().await.unwrap(), 2);
+    assert!(outbox.delivered.lock().await.is_empty());
+    let failed = outbox.failed.lock().await.clone();
+    assert_eq!(failed, vec![(1, Some(Duration::from_secs(1))), (2, Some(Duration::from_secs(4)))]);
+
+    outbox.pending.lock().await.push(event(1, 1));
+    assert_eq!(relay.run_once().await.unwrap(), 1);
+    assert_eq!(*outbox.delivered.lock().await, vec![1]);
+}
+
+#[tokio::test]
+async fn relay_gives_up_after_max_attempts() {
+    let outbox = MockOutbox::default();
+    outbox.pending.lock().await.push(event(7, 2));
+    let sink = Box::new(FlakySink { failures_left: AtomicUsize::new(1) });
+    let config = RelayConfig {
+        max_attempts: 3,
+        ..Default::default()
+    };
+    let relay = OutboxRelay::new(outbox.clone(), sink, config);
+
+    relay.run_once().await.unwrap();
+    assert_eq!(*outbox.failed.lock().await, vec![(7, None)]);
+}
+
+#[tokio::test]
+async fn http_sink_gives_up_on_a_hanging_receiver() {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}/events", listener.local_addr().unwrap());
+    // Accepts connections and never answers
+    tokio::spawn(async move {
+        let mut connections = Vec::new();
+        while let Ok((connection, _)) = listener.accept().await {
+            connections.push(connection);
+        }
+    });
+    assert!(HttpSink::TIMEOUT < RelayConfig::default().lease);
+
+    let sink = HttpSink::with_timeout(url, Duration::from_millis(200));
+    let published = tokio::time::timeout(Duration::from_secs(5), sink.publish(&event(1, 0))).await;
+    assert!(published.expect("publish should time out on its own").is_err());
+}
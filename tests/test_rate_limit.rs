@@ -0,0 +1,97 @@
+mod common;
+
+use axum::body::Body;
+use axum::http::{header, Request, StatusCode};
+use common::{order, MemoryRepository};
+use std::sync::Arc;
+use std::time::Duration;
+use tower::ServiceExt;
+use wb_tech_l0::application::{router, AppState};
+use wb_tech_l0::infrastructure::{ApiKeyConfig, AuthConfig, Authenticator, OrderService, Quota, RateLimiter};
+
+fn state() -> AppState {
+    AppState::new(Box::<MemoryRepository>::default(), Box::new(OrderService::new()))
+}
+
+fn add_order(uid: &str, api_key: Option<&str>) -> Request<Body> {
+    let mut builder = Request::post("/add_order").header("Content-Type", "application/json");
+    if let Some(key) = api_key {
+        builder = builder.header("X-API-Key", key);
+    }
+    builder.body(Body::from(serde_json::to_vec(&order(uid).build()).unwrap())).unwrap()
+}
+
+#[test]
+fn bucket_allows_burst_then_asks_to_wait() {
+    let limiter = RateLimiter::new(Quota { per_second: 0.5, burst: 2 });
+    assert!(limiter.check("client").is_ok());
+    assert!(limiter.check("client").is_ok());
+    let wait = limiter.check("client").unwrap_err();
+    assert!(wait.as_secs_f64() > 1.0 && wait.as_secs_f64() <= 2.0);
+    assert!(limiter.check("another client").is_ok());
+}
+
+#[test]
+fn zero_rate_waits_without_panicking() {
+    let limiter = RateLimiter::new(Quota { per_second: 0.0, burst: 0 });
+    assert!(limiter.check("client").is_ok());
+    let wait = limiter.check("client").unwrap_err();
+    assert!(wait <= Duration::from_secs(86400));
+    // The limiter is still usable after refusing
+    assert!(limiter.check("client").is_err());
+    assert!(limiter.check("another client").is_ok());
+}
+
+#[tokio::test]
+async fn write_budget_returns_429_with_retry_after() {
+    let limiter = RateLimiter::new(Quota { per_second: 0.01, burst: 2 });
+    let app = router(Arc::new(state().with_rate_limits(None, Some(limiter))));
+    let response = app.clone().oneshot(add_order("order1", None)).await.unwrap();
+    assert_eq!(response.status(), StatusCode::CREATED);
+    let response = app.clone().oneshot(add_order("order2", None)).await.unwrap();
+    assert_eq!(response.status(), StatusCode::CREATED);
+    let response = app.clone().oneshot(add_order("order3", None)).await.unwrap();
+    assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+    let retry_after: u64 = response.headers()[header::RETRY_AFTER].to_str().unwrap().parse().unwrap();
+    assert!(retry_after >= 1);
+    // Reads have their own budget
+    let response = app.oneshot(Request::get("/order/order1").body(Body::empty()).unwrap()).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+}
+
+#[tokio::test]
+async fn authenticated_clients_have_separate_buckets() {
+    let key = |name: &str| ApiKeyConfig {
+        name: name.to_string(),
+        sha256: Authenticator::hash_key(name),
+        scopes: vec!["orders:write".to_string()],
+    };
+    let authenticator = Authenticator::new(AuthConfig {
+        api_keys: vec![key("first"), key("second")],
+        jwt: None,
+    })
+    .unwrap();
+    let limiter = RateLimiter::new(Quota { per_second: 0.01, burst: 1 });
+    let app = router(Arc::new(
+        state()
+            .with_authenticator(Box::new(authenticator))
+            .with_rate_limits(None, Some(limiter)),
+    ));
+    let status = |request: Request<Body>| {
+        let app = app.clone();
+        async move { app.oneshot(request).await.unwrap().status() }
+    };
+    assert_eq!(status(add_order("order1", Some("first"))).await, StatusCode::CREATED);
+    assert_eq!(status(add_order("order2", Some("first"))).await, StatusCode::TOO_MANY_REQUESTS);
+    assert_eq!(status(add_order("order3", Some("second"))).await, StatusCode::CREATED);
+}
+
+#[tokio::test]
+async fn concurrency_cap_applies_to_database_routes_only() {
+    let app = router(Arc::new(state().with_db_concurrency(0)));
+    let response = app.clone().oneshot(Request::get("/order/order1").body(Body::empty()).unwrap()).await.unwrap();
+    assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+    assert!(response.headers().contains_key(header::RETRY_AFTER));
+    let response = app.oneshot(Request::get("/orders/stream").body(Body::empty()).unwrap()).await.unwrap();
+    assert_eq!(response.status(), StatusCode::NOT_IMPLEMENTED);
+}
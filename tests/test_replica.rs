@@ -0,0 +1,138 @@
+//! Read routing between a primary and a replica. Both are separate test databases here, so a read
+//! tells where it went by what it finds.
+
+mod common;
+mod pg;
+
+use common::order;
+use pg::TestDatabase;
+use std::time::Duration;
+use wb_tech_l0::infrastructure::{Database, ReplicaConfig};
+use wb_tech_l0::interfaces::{Database as _, OrderExport};
+use futures::TryStreamExt;
+use wb_tech_l0::models::{Order, OrderFilter, OrderLookup};
+
+fn config(read_after_write: Duration) -> ReplicaConfig {
+    ReplicaConfig { read_after_write, max_lag: Duration::from_secs(10) }
+}
+
+/// Primary and replica test databases, each with an order only it has.
+async fn pair() -> Option<(TestDatabase, TestDatabase)> {
+    let primary = TestDatabase::create().await?;
+    let replica = TestDatabase::create().await?;
+    primary.database.insert(order("on-primary").customer_id("alice").build()).await.unwrap();
+    replica.database.insert(order("on-replica").customer_id("alice").build()).await.unwrap();
+    Some((primary, replica))
+}
+
+fn uids(orders: &[Order]) -> Vec<&str> {
+    orders.iter().map(|order| order.order_uid.as_str()).collect()
+}
+
+#[tokio::test]
+async fn reads_go_to_the_replica() {
+    let Some((primary, replica)) = pair().await else { return };
+    let database = primary.database.clone().with_replica(replica.config().clone(), config(Duration::ZERO)).unwrap();
+
+    assert!(database.get("on-replica").await.unwrap().is_some());
+    assert!(database.get("on-primary").await.unwrap().is_none());
+    let found = database.find(&OrderLookup::TrackNumber("WBILMTESTTRACK".to_string()), 10, 0).await.unwrap();
+    assert_eq!(found.len(), 1);
+    assert_eq!(found[0].order_uid, "on-replica");
+    let exported: Vec<Order> = database.export(&OrderFilter::default()).await.unwrap().try_collect().await.unwrap();
+    assert_eq!(uids(&exported), ["on-replica"]);
+
+    // Writes go to the primary
+    database.insert(order("written").customer_id("bob").build()).await.unwrap();
+    assert!(primary.database.get("written").await.unwrap().is_some());
+    assert!(replica.database.get("written").await.unwrap().is_none());
+    primary.drop().await;
+    replica.drop().await;
+}
+
+#[tokio::test]
+async fn recent_writes_are_read_from_the_primary() {
+    let Some((primary, replica)) = pair().await else { return };
+    let database = primary.database.clone().with_replica(replica.config().clone(), config(Duration::from_millis(300))).unwrap();
+
+    database.insert(order("written").customer_id("bob").build()).await.unwrap();
+    assert!(database.get("written").await.unwrap().is_some());
+    let orders = database.get_many(&["written".to_string(), "on-replica".to_string()]).await.unwrap();
+    assert_eq!(uids(&orders), ["written"]);
+    assert_eq!(database.customer_totals("bob").await.unwrap().order_count, 1);
+    // Other orders and customers are still read from the replica
+    assert!(database.get("on-replica").await.unwrap().is_some());
+    let lookup = OrderLookup::CustomerId("alice".to_string());
+    assert_eq!(database.find(&lookup, 10, 0).await.unwrap()[0].order_uid, "on-replica");
+
+    tokio::time::sleep(Duration::from_millis(400)).await;
+    assert!(database.get("written").await.unwrap().is_none());
+
+    database.remove("on-replica").await.unwrap();
+    assert_eq!(database.customer_totals("alice").await.unwrap().order_count, 1);
+    assert!(database.get("on-replica").await.unwrap().is_none());
+    primary.drop().await;
+    replica.drop().await;
+}
+
+#[tokio::test]
+async fn unreachable_replica_falls_back_to_the_primary() {
+    let Some((primary, replica)) = pair().await else { return };
+    let mut unreachable = tokio_postgres::Config::new();
+    unreachable.host("127.0.0.1").port(1).user("postgres").connect_timeout(Duration::from_secs(1));
+    let database = primary.database.clone().with_replica(unreachable, config(Duration::ZERO)).unwrap();
+
+    assert!(database.get("on-primary").await.unwrap().is_some());
+    assert!(database.check_replica().await.is_err());
+    let exported: Vec<Order> = database.export(&OrderFilter::default()).await.unwrap().try_collect().await.unwrap();
+    assert_eq!(uids(&exported), ["on-primary"]);
+
+    // A replica that comes back is used again once it's checked
+    let database = primary.database.clone().with_replica(replica.config().clone(), config(Duration::ZERO)).unwrap();
+    assert_eq!(database.check_replica().await.unwrap(), Some(Duration::ZERO));
+    assert!(database.get("on-replica").await.unwrap().is_some());
+    assert_eq!(Database::check_replica(&primary.database).await.unwrap(), None);
+    primary.drop().await;
+    replica.drop().await;
+}
+
+/// Makes the replica's connections see it as a standby in recovery streaming WAL from the primary,
+/// that has replayed all the WAL it received.
+async fn pretend_standby(replica: &TestDatabase) -> tokio_postgres::Config {
+    replica
+        .client()
+        .await
+        .batch_execute(
+            "CREATE SCHEMA standby;
+             CREATE FUNCTION standby.pg_is_in_recovery() RETURNS BOOLEAN AS 'SELECT TRUE' LANGUAGE SQL;
+             CREATE FUNCTION standby.pg_last_wal_receive_lsn() RETURNS pg_lsn AS 'SELECT ''0/3000000''::pg_lsn' LANGUAGE SQL;
+             CREATE FUNCTION standby.pg_last_wal_replay_lsn() RETURNS pg_lsn AS 'SELECT ''0/3000000''::pg_lsn' LANGUAGE SQL;
+             CREATE VIEW standby.pg_stat_wal_receiver AS SELECT 'streaming'::TEXT AS status;",
+        )
+        .await
+        .unwrap();
+    let mut config = replica.config().clone();
+    config.options("-c search_path=standby,pg_catalog,public");
+    config
+}
+
+#[tokio::test]
+async fn replica_without_wal_receiver_is_skipped() {
+    let Some((primary, replica)) = pair().await else { return };
+    let standby = pretend_standby(&replica).await;
+    let database = primary.database.clone().with_replica(standby, config(Duration::ZERO)).unwrap();
+    assert_eq!(database.check_replica().await.unwrap(), Some(Duration::ZERO));
+    assert!(database.get("on-replica").await.unwrap().is_some());
+
+    // Having replayed all it received says nothing once it receives nothing
+    replica
+        .client()
+        .await
+        .batch_execute("CREATE OR REPLACE VIEW standby.pg_stat_wal_receiver AS SELECT 'streaming'::TEXT AS status WHERE FALSE")
+        .await
+        .unwrap();
+    assert!(database.check_replica().await.is_err());
+    assert!(database.get("on-primary").await.unwrap().is_some());
+    primary.drop().await;
+    replica.drop().await;
+}
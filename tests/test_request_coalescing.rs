@@ -0,0 +1,78 @@
+//! Concurrent cache misses sharing database reads and uids that weren't found being remembered.
+
+mod common;
+
+use common::{order, CountingDatabase, Counters};
+use futures::future::join_all;
+use std::sync::Arc;
+use std::time::Duration;
+use wb_tech_l0::infrastructure::{Cache, Repository};
+use wb_tech_l0::interfaces::{Database, Repository as _};
+
+/// Reads of single orders take a while after reading, the first `failures` of them fail.
+async fn repository(failures: usize) -> (Repository<Cache, CountingDatabase>, Counters) {
+    let database = CountingDatabase { delay: Duration::from_millis(50), failures, ..Default::default() };
+    database.insert(order("popular").build()).await.unwrap();
+    let counters = database.counters.clone();
+    (Repository::new(Cache::new(), database), counters)
+}
+
+#[tokio::test]
+async fn concurrent_misses_share_one_read() {
+    let (repository, counters) = repository(0).await;
+    let found = join_all((0..10).map(|_| repository.get_and_cache("popular"))).await;
+    assert!(found.iter().all(|order| order.as_ref().unwrap().as_ref().unwrap().order_uid == "popular"));
+    assert_eq!(counters.reads(), 1);
+
+    // Served from the cache from now on
+    assert!(repository.get_and_cache("popular").await.unwrap().is_some());
+    assert_eq!(counters.reads(), 1);
+}
+
+#[tokio::test]
+async fn missing_uids_are_remembered_until_inserted() {
+    let (repository, counters) = repository(0).await;
+    let found = join_all((0..5).map(|_| repository.get_and_cache("missing"))).await;
+    assert!(found.iter().all(|order| order.as_ref().unwrap().is_none()));
+    assert!(repository.get_and_cache("missing").await.unwrap().is_none());
+    assert_eq!(counters.reads(), 1);
+
+    repository.insert(order("missing").build()).await.unwrap();
+    assert!(repository.get_and_cache("missing").await.unwrap().is_some());
+    assert_eq!(counters.reads(), 2);
+}
+
+#[tokio::test]
+async fn failed_reads_are_not_shared() {
+    let (repository, counters) = repository(1).await;
+    let found = join_all((0..3).map(|_| repository.get_and_cache("popular"))).await;
+    assert_eq!(found.iter().filter(|order| order.is_err()).count(), 1);
+    assert_eq!(found.iter().filter(|order| matches!(order, Ok(Some(_)))).count(), 2);
+    assert_eq!(counters.reads(), 2);
+}
+
+#[tokio::test]
+async fn reads_racing_writes_record_nothing() {
+    let (repository, _) = repository(0).await;
+    let repository = Arc::new(repository);
+
+    // The read finds nothing, the order is inserted before it finishes
+    let read = tokio::spawn({
+        let repository = repository.clone();
+        async move { repository.get_and_cache("late").await.unwrap() }
+    });
+    tokio::time::sleep(Duration::from_millis(10)).await;
+    repository.insert(order("late").build()).await.unwrap();
+    assert!(read.await.unwrap().is_none());
+    assert!(repository.get_and_cache("late").await.unwrap().is_some());
+
+    // The read finds the order, it's removed before the read finishes
+    let read = tokio::spawn({
+        let repository = repository.clone();
+        async move { repository.get_and_cache("popular").await.unwrap() }
+    });
+    tokio::time::sleep(Duration::from_millis(10)).await;
+    repository.remove("popular").await.unwrap();
+    assert!(read.await.unwrap().is_some());
+    assert!(repository.get_and_cache("popular").await.unwrap().is_none());
+}
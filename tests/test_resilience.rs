@@ -0,0 +1,152 @@
+//! Retries of transient Postgres failures and the circuit breaker in front of it.
+
+mod common;
+mod pg;
+
+use common::order;
+use pg::TestDatabase;
+use std::time::{Duration, Instant};
+use wb_tech_l0::infrastructure::{
+    is_conflict, BreakerConfig, CircuitBreaker, CircuitOpen, Database, DatabaseSettings, RetryPolicy,
+};
+use wb_tech_l0::interfaces::Database as _;
+
+fn settings(max_attempts: u32, failure_threshold: u32) -> DatabaseSettings {
+    DatabaseSettings {
+        retry: RetryPolicy {
+            max_attempts,
+            base_delay: Duration::from_millis(1),
+            max_delay: Duration::from_millis(5),
+        },
+        breaker: BreakerConfig { failure_threshold, open_for: Duration::from_secs(60) },
+        ..Default::default()
+    }
+}
+
+#[test]
+fn breaker_opens_and_lets_a_trial_through() {
+    let breaker = CircuitBreaker::new(BreakerConfig { failure_threshold: 2, open_for: Duration::from_millis(50) });
+    breaker.failure();
+    assert!(breaker.check().is_ok());
+    breaker.failure();
+    assert!(breaker.check().is_err());
+
+    std::thread::sleep(Duration::from_millis(60));
+    assert!(breaker.check().is_ok());
+    // Only one trial at a time
+    assert!(breaker.check().is_err());
+    breaker.failure();
+    assert!(breaker.check().is_err());
+
+    std::thread::sleep(Duration::from_millis(60));
+    assert!(breaker.check().is_ok());
+    breaker.success();
+    assert!(breaker.check().is_ok());
+    breaker.failure();
+    assert!(breaker.check().is_ok());
+}
+
+#[tokio::test]
+async fn unreachable_postgres_fails_fast() {
+    let mut config = tokio_postgres::Config::new();
+    config.host("127.0.0.1").port(1).user("postgres");
+    let database = Database::with_settings(config, settings(5, 2)).unwrap();
+
+    let err = database.get("anything").await.unwrap_err();
+    assert!(err.is::<CircuitOpen>(), "{err}");
+    let started = Instant::now();
+    let err = database.insert(order("anything").build()).await.unwrap_err();
+    assert!(err.is::<CircuitOpen>(), "{err}");
+    assert!(started.elapsed() < Duration::from_millis(50));
+}
+
+/// Makes the next `failures` order inserts fail with `code`, the sequence keeps counting across rollbacks.
+async fn fail_inserts(database: &TestDatabase, code: &str, failures: i64) {
+    fail(database, code, failures, "BEFORE INSERT ON Orders FOR EACH ROW").await;
+}
+
+/// Like `fail_inserts`, but the error is raised by the commit.
+async fn fail_commits(database: &TestDatabase, code: &str, failures: i64) {
+    fail(database, code, failures, "AFTER INSERT ON Orders DEFERRABLE INITIALLY DEFERRED FOR EACH ROW").await;
+}
+
+async fn fail(database: &TestDatabase, code: &str, failures: i64, timing: &str) {
+    let constraint = if timing.contains("DEFERRED") { "CONSTRAINT" } else { "" };
+    database
+        .client()
+        .await
+        .batch_execute(&format!(
+            "CREATE SEQUENCE insert_attempts;
+             CREATE FUNCTION flaky_insert() RETURNS trigger AS $$
+             BEGIN
+                 IF nextval('insert_attempts') <= {failures} THEN
+                     RAISE EXCEPTION 'transient' USING ERRCODE = '{code}';
+                 END IF;
+                 RETURN NEW;
+             END $$ LANGUAGE plpgsql;
+             CREATE {constraint} TRIGGER flaky_insert {timing} EXECUTE FUNCTION flaky_insert();"
+        ))
+        .await
+        .unwrap();
+}
+
+async fn insert_attempts(database: &TestDatabase) -> i64 {
+    let row = database.client().await.query_one("SELECT last_value FROM insert_attempts", &[]).await.unwrap();
+    row.get(0)
+}
+
+#[tokio::test]
+async fn transient_errors_are_retried() {
+    for code in ["40001", "40P01"] {
+        let Some(test) = TestDatabase::create().await else { return };
+        fail_inserts(&test, code, 2).await;
+        let database = Database::with_settings(test.config().clone(), settings(3, 5)).unwrap();
+        database.insert(order("retried").build()).await.unwrap();
+        assert_eq!(insert_attempts(&test).await, 3);
+        assert!(database.get("retried").await.unwrap().is_some());
+
+        // Conflicts are final
+        let err = database.insert(order("retried").build()).await.unwrap_err();
+        assert!(is_conflict(err.as_ref()), "{err}");
+        assert_eq!(insert_attempts(&test).await, 4);
+        drop(database);
+        test.drop().await;
+    }
+}
+
+#[tokio::test]
+async fn retries_give_up_after_the_last_attempt() {
+    let Some(test) = TestDatabase::create().await else { return };
+    fail_inserts(&test, "40001", 5).await;
+    let database = Database::with_settings(test.config().clone(), settings(2, 5)).unwrap();
+    let err = database.insert(order("failed").build()).await.unwrap_err();
+    let code = err.downcast_ref::<tokio_postgres::Error>().and_then(|err| err.code()).unwrap();
+    assert_eq!(code.code(), "40001");
+    assert_eq!(insert_attempts(&test).await, 2);
+    // Contention doesn't open the circuit
+    assert!(database.get("failed").await.unwrap().is_none());
+    drop(database);
+    test.drop().await;
+}
+
+#[tokio::test]
+async fn commits_are_retried_only_on_contention() {
+    let Some(test) = TestDatabase::create().await else { return };
+    fail_commits(&test, "40001", 1).await;
+    let database = Database::with_settings(test.config().clone(), settings(3, 5)).unwrap();
+    database.insert(order("contended").build()).await.unwrap();
+    assert_eq!(insert_attempts(&test).await, 2);
+    drop(database);
+    test.drop().await;
+
+    // A commit failing like a lost connection may have gone through, another attempt could only conflict with it
+    let Some(test) = TestDatabase::create().await else { return };
+    fail_commits(&test, "08006", 1).await;
+    let database = Database::with_settings(test.config().clone(), settings(3, 5)).unwrap();
+    let err = database.insert(order("uncertain").build()).await.unwrap_err();
+    let code = err.downcast_ref::<tokio_postgres::Error>().and_then(|err| err.code()).unwrap();
+    assert_eq!(code.code(), "08006");
+    assert_eq!(insert_attempts(&test).await, 1);
+    drop(database);
+    test.drop().await;
+}
@@ -0,0 +1,212 @@
+mod pg;
+
+use pg::TestDatabase;
+use std::error::Error;
+use tokio_postgres::error::SqlState;
+use wb_tech_l0::interfaces::Database;
+use wb_tech_l0::models::{Delivery, Item, Order, Payment};
+
+/// The order from `API/add_order.http`.
+fn sample() -> Order {
+    Order {
+        order_uid: "b563feb7b2b84b6test".to_string(),
+        track_number: "WBILMTESTTRACK".to_string(),
+        entry: "WBIL".to_string(),
+        delivery: Delivery {
+            name: "Test Testov".to_string(),
+            phone: "+9720000000".to_string(),
+            zip: "2639809".to_string(),
+            address: "Ploshad Mira 15".to_string(),
+            region: "Kraiot".to_string(),
+            email: "test@gmail.com".to_string(),
+        },
+        payment: Payment {
+            transaction: "b563feb7b2b84b6test".to_string(),
+            request_id: "".to_string(),
+            currency: "USD".to_string(),
+            provider: "wbpay".to_string(),
+            amount: 1817,
+            payment_dt: 1637907727,
+            bank: "alpha".to_string(),
+            delivery_cost: 1500,
+            goods_total: 317,
+            custom_fee: 0,
+        },
+        items: vec![Item {
+            chrt_id: 9934930,
+            track_number: "WBILMTESTTRACK".to_string(),
+            price: 453,
+            rid: "ab4219087a764ae0btest".to_string(),
+            name: "Mascaras".to_string(),
+            sale: 30,
+            size: "0".to_string(),
+            total_price: 317,
+            nm_id: 2389212,
+            brand: "Vivienne Sabo".to_string(),
+            status: 202,
+        }],
+        locale: "en".to_string(),
+        internal_signature: "".to_string(),
+        customer_id: "test".to_string(),
+        delivery_service: "meest".to_string(),
+        shardkey: "9".to_string(),
+        sm_id: 99,
+        date_created: "2021-11-26T06:22:19Z".to_string(),
+        oof_shard: "1".to_string(),
+    }
+}
+
+fn with_uid(mut order: Order, uid: &str) -> Order {
+    order.order_uid = uid.to_string();
+    order.payment.transaction = uid.to_string();
+    order
+}
+
+/// Inserts the order and checks it comes back equal and serializing to the same bytes.
+async fn assert_round_trips(database: &impl Database<Error = Box<dyn Error>>, order: Order) {
+    database.insert(order.clone()).await.unwrap();
+    let stored = database.get(&order.order_uid).await.unwrap().expect("Order is missing");
+    assert_eq!(serde_json::to_vec(&stored).unwrap(), serde_json::to_vec(&order).unwrap());
+    assert_eq!(stored, order);
+}
+
+fn sql_state<'a>(err: &'a (dyn Error + 'static)) -> Option<&'a SqlState> {
+    err.downcast_ref::<tokio_postgres::Error>()?.code()
+}
+
+async fn count(test: &TestDatabase, table: &str) -> i64 {
+    test.client()
+        .await
+        .query_one(&format!("SELECT COUNT(*) FROM {table}"), &[])
+        .await
+        .unwrap()
+        .get(0)
+}
+
+#[tokio::test]
+async fn sample_order_round_trips() {
+    let Some(test) = TestDatabase::create().await else { return };
+    assert_round_trips(&test.database, sample()).await;
+    test.drop().await;
+}
+
+#[tokio::test]
+async fn unicode_and_empty_strings_round_trip() {
+    let Some(test) = TestDatabase::create().await else { return };
+    let mut order = with_uid(sample(), "заказ-🚚-注文");
+    order.track_number = "ТРЕК\u{301}-ñ".to_string();
+    order.delivery.name = "Тест Тестов 测试 👩‍👩‍👧".to_string();
+    order.delivery.address = "line one\nline two\t'quoted' \"double\" \\".to_string();
+    order.delivery.zip = "".to_string();
+    order.delivery.email = "".to_string();
+    order.locale = "".to_string();
+    order.items[0].name = "Тушь для ресниц".to_string();
+    order.items[0].brand = "Ünïcödé ☃".to_string();
+    order.items[0].size = " ".to_string();
+    assert_round_trips(&test.database, order).await;
+    test.drop().await;
+}
+
+#[tokio::test]
+async fn integer_extremes_round_trip() {
+    let Some(test) = TestDatabase::create().await else { return };
+    let mut order = with_uid(sample(), "extremes");
+    order.sm_id = i32::MIN;
+    order.payment.amount = i32::MAX;
+    order.payment.payment_dt = i32::MIN;
+    order.payment.delivery_cost = 0;
+    order.payment.custom_fee = -1;
+    order.items[0].chrt_id = i32::MAX;
+    order.items[0].nm_id = i32::MIN;
+    order.items[0].price = i32::MAX;
+    order.items[0].status = -1;
+    assert_round_trips(&test.database, order).await;
+    test.drop().await;
+}
+
+#[tokio::test]
+async fn many_items_round_trip() {
+    let Some(test) = TestDatabase::create().await else { return };
+    let mut order = with_uid(sample(), "many-items");
+    let template = order.items[0].clone();
+    order.items = (0..100)
+        .map(|n| Item {
+            chrt_id: 100 - n,
+            rid: format!("rid-{n}"),
+            price: n * 10,
+            ..template.clone()
+        })
+        .collect();
+    // Repeated items are kept as they were sent
+    order.items.push(order.items[0].clone());
+    assert_round_trips(&test.database, order).await;
+    test.drop().await;
+}
+
+#[tokio::test]
+async fn empty_items_round_trip() {
+    let Some(test) = TestDatabase::create().await else { return };
+    let mut order = with_uid(sample(), "no-items");
+    order.items.clear();
+    assert_round_trips(&test.database, order).await;
+    test.drop().await;
+}
+
+#[tokio::test]
+async fn duplicate_order_uid_is_rejected_and_keeps_the_original() {
+    let Some(test) = TestDatabase::create().await else { return };
+    let original = sample();
+    test.database.insert(original.clone()).await.unwrap();
+    let mut duplicate = with_uid(sample(), &original.order_uid);
+    duplicate.payment.transaction = "another-transaction".to_string();
+    duplicate.delivery.name = "Someone Else".to_string();
+    let err = test.database.insert(duplicate).await.unwrap_err();
+    assert_eq!(sql_state(err.as_ref()), Some(&SqlState::UNIQUE_VIOLATION));
+    assert_eq!(test.database.get(&original.order_uid).await.unwrap(), Some(original));
+    assert_eq!(count(&test, "Deliveries").await, 1);
+    assert_eq!(count(&test, "Payments").await, 1);
+    test.drop().await;
+}
+
+#[tokio::test]
+async fn duplicate_transaction_leaves_no_partial_order() {
+    let Some(test) = TestDatabase::create().await else { return };
+    let original = sample();
+    test.database.insert(original.clone()).await.unwrap();
+    let mut duplicate = with_uid(sample(), "other-order");
+    duplicate.payment.transaction = original.payment.transaction.clone();
+    let err = test.database.insert(duplicate).await.unwrap_err();
+    assert_eq!(sql_state(err.as_ref()), Some(&SqlState::UNIQUE_VIOLATION));
+    assert_eq!(test.database.get("other-order").await.unwrap(), None);
+    assert_eq!(count(&test, "Orders").await, 1);
+    assert_eq!(count(&test, "Deliveries").await, 1);
+    assert_eq!(count(&test, "Items").await, 1);
+    assert_eq!(count(&test, "Outbox").await, 1);
+    test.drop().await;
+}
+
+#[tokio::test]
+async fn nul_characters_are_rejected() {
+    let Some(test) = TestDatabase::create().await else { return };
+    let mut order = with_uid(sample(), "nul");
+    order.delivery.address = "before\0after".to_string();
+    let err = test.database.insert(order).await.unwrap_err();
+    assert_eq!(sql_state(err.as_ref()), Some(&SqlState::CHARACTER_NOT_IN_REPERTOIRE));
+    assert_eq!(test.database.get("nul").await.unwrap(), None);
+    assert_eq!(count(&test, "Orders").await, 0);
+    test.drop().await;
+}
+
+#[tokio::test]
+async fn removed_order_can_be_inserted_again() {
+    let Some(test) = TestDatabase::create().await else { return };
+    let order = sample();
+    test.database.insert(order.clone()).await.unwrap();
+    test.database.remove(&order.order_uid).await.unwrap();
+    assert_eq!(test.database.get(&order.order_uid).await.unwrap(), None);
+    for table in ["Orders", "Deliveries", "Payments", "Items"] {
+        assert_eq!(count(&test, table).await, 0, "{table} wasn't cleaned up");
+    }
+    assert_round_trips(&test.database, order).await;
+    test.drop().await;
+}
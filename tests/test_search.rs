@@ -0,0 +1,210 @@
+mod common;
+mod pg;
+
+use axum::async_trait;
+use axum::body::{to_bytes, Body};
+use axum::http::{Request, StatusCode};
+use common::{order, MemoryRepository};
+use pg::TestDatabase;
+use serde_json::Value;
+use std::error::Error;
+use std::sync::{Arc, Mutex};
+use tower::ServiceExt;
+use wb_tech_l0::application::{router, AppState};
+use wb_tech_l0::infrastructure::OrderService;
+use wb_tech_l0::interfaces::{self, Database as _, OrderSearch as _};
+use wb_tech_l0::models::{Delivery, Item, OrderSummary};
+
+type Calls = Arc<Mutex<Vec<(String, i64, i64)>>>;
+
+/// Matches orders whose uid contains the query
+struct MockSearch {
+    orders: Vec<OrderSummary>,
+    calls: Calls,
+}
+
+#[async_trait]
+impl interfaces::OrderSearch for MockSearch {
+    type Error = Box<dyn Error>;
+
+    async fn search(&self, query: &str, limit: i64, offset: i64) -> Result<Vec<OrderSummary>, Self::Error> {
+        self.calls.lock().unwrap().push((query.to_string(), limit, offset));
+        Ok(self
+            .orders
+            .iter()
+            .filter(|order| order.order_uid.contains(query))
+            .skip(offset as usize)
+            .take(limit as usize)
+            .cloned()
+            .collect())
+    }
+}
+
+fn app(search: Option<MockSearch>) -> axum::Router {
+    let mut state = AppState::new(Box::<MemoryRepository>::default(), Box::new(OrderService::new()));
+    if let Some(search) = search {
+        state = state.with_search(Box::new(search));
+    }
+    router(Arc::new(state))
+}
+
+async fn get(app: &axum::Router, uri: &str) -> (StatusCode, Value) {
+    let response = app.clone().oneshot(Request::get(uri).body(Body::empty()).unwrap()).await.unwrap();
+    let status = response.status();
+    let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    (status, serde_json::from_slice(&body).unwrap())
+}
+
+#[tokio::test]
+async fn results_are_paginated() {
+    let orders = (0..5)
+        .map(|i| OrderSummary {
+            order_uid: format!("order{i}"),
+            ..Default::default()
+        })
+        .collect();
+    let calls = Calls::default();
+    let app = app(Some(MockSearch { orders, calls: calls.clone() }));
+
+    let (status, body) = get(&app, "/orders/search?q=order&limit=2").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["orders"].as_array().unwrap().len(), 2);
+    assert_eq!(body["next_offset"], 2);
+
+    let (status, body) = get(&app, "/orders/search?q=%20order%20&limit=2&offset=4").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["orders"][0]["order_uid"], "order4");
+    assert!(body["next_offset"].is_null());
+
+    // The store is asked for one extra row to detect the next page
+    assert_eq!(calls.lock().unwrap()[1], ("order".to_string(), 3, 4));
+}
+
+#[tokio::test]
+async fn invalid_queries_are_rejected() {
+    let app = app(Some(MockSearch { orders: Vec::new(), calls: Calls::default() }));
+    assert_eq!(get(&app, "/orders/search?q=%20%20").await.0, StatusCode::BAD_REQUEST);
+    assert_eq!(get(&app, "/orders/search?q=ivan&limit=0").await.0, StatusCode::BAD_REQUEST);
+    assert_eq!(get(&app, "/orders/search?q=ivan&limit=1000").await.0, StatusCode::BAD_REQUEST);
+    assert_eq!(get(&app, "/orders/search?q=ivan&offset=-1").await.0, StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn search_without_store_is_not_implemented() {
+    assert_eq!(get(&app(None), "/orders/search?q=ivan").await.0, StatusCode::NOT_IMPLEMENTED);
+}
+
+/// Items of the given names and brands.
+fn goods(items: &[(&str, &str)]) -> Vec<Item> {
+    let item = |(chrt_id, (name, brand)): (usize, &(&str, &str))| Item {
+        chrt_id: chrt_id as i32,
+        name: name.to_string(),
+        brand: brand.to_string(),
+        ..Default::default()
+    };
+    items.iter().enumerate().map(item).collect()
+}
+
+fn recipient(name: &str, phone: &str, email: &str) -> Delivery {
+    Delivery {
+        name: name.to_string(),
+        phone: phone.to_string(),
+        email: email.to_string(),
+        ..Default::default()
+    }
+}
+
+/// Postgres test database with three orders sharing some of their words.
+async fn searchable() -> Option<TestDatabase> {
+    let test = TestDatabase::create().await?;
+    let orders = [
+        (
+            "ivan",
+            "2021-11-26T06:22:19Z",
+            recipient("Ivan Petrov", "+7 900 123-45-67", "ivan.petrov@mail.ru"),
+            goods(&[("Mascaras", "Vivienne Sabo"), ("Lipstick", "Loreal")]),
+        ),
+        (
+            "maria",
+            "2021-11-27T06:22:19Z",
+            recipient("Maria Ivanova", "+7 911 000-00-00", "maria@yandex.ru"),
+            goods(&[("Lipstick", "Loreal")]),
+        ),
+        ("olga", "2021-11-28T06:22:19Z", recipient("Olga O'Brien", "", ""), goods(&[("Brush set", "Vivienne Sabo")])),
+    ];
+    for (uid, date_created, delivery, items) in orders {
+        let track_number = format!("WB{}TRACK", uid.to_uppercase());
+        let order = order(uid).track_number(&track_number).date_created(date_created).delivery(delivery).items(items);
+        test.database.insert(order.build()).await.unwrap();
+    }
+    Some(test)
+}
+
+async fn search(test: &TestDatabase, query: &str) -> Vec<String> {
+    let found = test.database.search(query, 10, 0).await.unwrap();
+    found.into_iter().map(|order| order.order_uid).collect()
+}
+
+#[tokio::test]
+async fn postgres_matches_every_word_as_a_prefix() {
+    let Some(test) = searchable().await else { return };
+    assert_eq!(search(&test, "WBIVANTRACK").await, ["ivan"]);
+    assert_eq!(search(&test, "petr").await, ["ivan"]);
+    assert_eq!(search(&test, "masc viv").await, ["ivan"]);
+    // Words may come from the delivery and the items alike, but all of them have to match
+    assert_eq!(search(&test, "ivan mascaras").await, ["ivan"]);
+    assert_eq!(search(&test, "maria mascaras").await, Vec::<String>::new());
+    // Ivan has it in the name and the email, so his order ranks first
+    assert_eq!(search(&test, "ivan").await, ["ivan", "maria"]);
+    assert_eq!(search(&test, "nobody").await, Vec::<String>::new());
+    test.drop().await;
+}
+
+#[tokio::test]
+async fn postgres_finds_phones_and_emails_by_their_parts() {
+    let Some(test) = searchable().await else { return };
+    assert_eq!(search(&test, "79001234567").await, ["ivan"]);
+    assert_eq!(search(&test, "+7900").await, ["ivan"]);
+    assert_eq!(search(&test, "7911").await, ["maria"]);
+    assert_eq!(search(&test, "ivan.petrov@mail.ru").await, ["ivan"]);
+    assert_eq!(search(&test, "yandex").await, ["maria"]);
+    assert_eq!(search(&test, "mail ru").await, ["ivan"]);
+    test.drop().await;
+}
+
+#[tokio::test]
+async fn postgres_search_is_safe_with_any_input() {
+    let Some(test) = searchable().await else { return };
+    assert_eq!(search(&test, "O'Brien").await, ["olga"]);
+    for query in ["'", "&", "!ivan", "a | b", "ivan:*", "(", "\\", "-- ;"] {
+        test.database.search(query, 10, 0).await.unwrap();
+    }
+    assert_eq!(search(&test, "!!!").await, Vec::<String>::new());
+    test.drop().await;
+}
+
+#[tokio::test]
+async fn postgres_ranks_and_pages_results() {
+    let Some(test) = searchable().await else { return };
+    // The track number weighs more than item brands, and equally ranked orders go newest first
+    let loreal = order("loreal")
+        .track_number("LOREAL")
+        .date_created("2021-11-25T06:22:19Z")
+        .delivery(recipient("Anna", "", ""))
+        .items(goods(&[("Cream", "Nivea")]));
+    test.database.insert(loreal.build()).await.unwrap();
+    assert_eq!(search(&test, "loreal").await, ["loreal", "maria", "ivan"]);
+    let page = test.database.search("loreal", 2, 1).await.unwrap();
+    let uids: Vec<&str> = page.iter().map(|order| order.order_uid.as_str()).collect();
+    assert_eq!(uids, ["maria", "ivan"]);
+    assert_eq!(page[1].customer_name, "Ivan Petrov");
+    assert_eq!(page[1].item_count, 2);
+
+    let state = AppState::new(Box::<MemoryRepository>::default(), Box::new(OrderService::new()))
+        .with_search(Box::new(test.database.clone()));
+    let (status, body) = get(&router(Arc::new(state)), "/orders/search?q=LOREAL&limit=2").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["orders"][0]["order_uid"], "loreal");
+    assert_eq!(body["next_offset"], 2);
+    test.drop().await;
+}
@@ -0,0 +1,340 @@
+mod common;
+mod pg;
+
+use axum::body::{to_bytes, Body, Bytes};
+use axum::http::{header, HeaderMap, Request, StatusCode};
+use axum::routing::post;
+use common::MemoryRepository;
+use pg::TestDatabase;
+use serde_json::{json, Value};
+use std::sync::atomic::{AtomicU16, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tower::ServiceExt;
+use wb_tech_l0::application::{router, AppState};
+use wb_tech_l0::infrastructure::{sign, Database, OrderService, WebhookConfig, WebhookWorker};
+use wb_tech_l0::interfaces::WebhookStore;
+use wb_tech_l0::models::{OrderEvent, WebhookDelivery, WebhookSubscriptionRequest};
+
+const SECRET: &str = "0123456789abcdef";
+
+#[test]
+fn signature_is_hmac_sha256_of_timestamp_and_body() {
+    let signature = sign("0123456789abcdef", 1700000000, br#"{"id":1}"#);
+    assert_eq!(
+        signature,
+        "sha256=4bcaced68dfea90a68df035b89cb7fb26692d899d32a1ccb1b0616cf48e4d1ed"
+    );
+}
+
+#[test]
+fn signature_depends_on_secret() {
+    let body = br#"{"id":1}"#;
+    assert_ne!(sign("0123456789abcdef", 1, body), sign("fedcba9876543210", 1, body));
+}
+
+/// Local endpoint that records what it receives and answers with a configurable status.
+#[derive(Clone)]
+struct Receiver {
+    url: String,
+    status: Arc<AtomicU16>,
+    received: Arc<Mutex<Vec<(HeaderMap, Bytes)>>>,
+}
+
+impl Receiver {
+    async fn start() -> Self {
+        let status = Arc::new(AtomicU16::new(200));
+        let received = Arc::new(Mutex::new(Vec::new()));
+        let app = axum::Router::new().route(
+            "/hook",
+            post({
+                let status = status.clone();
+                let received = received.clone();
+                move |headers: HeaderMap, body: Bytes| async move {
+                    received.lock().unwrap().push((headers, body));
+                    StatusCode::from_u16(status.load(Ordering::SeqCst)).unwrap()
+                }
+            }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        Self {
+            url: format!("http://{addr}/hook"),
+            status,
+            received,
+        }
+    }
+
+    fn respond_with(&self, status: u16) {
+        self.status.store(status, Ordering::SeqCst);
+    }
+
+    fn received(&self) -> Vec<(HeaderMap, Bytes)> {
+        self.received.lock().unwrap().clone()
+    }
+}
+
+fn subscription(url: &str) -> WebhookSubscriptionRequest {
+    WebhookSubscriptionRequest {
+        url: url.to_string(),
+        event_types: vec![OrderEvent::ORDER_CREATED.to_string()],
+        delivery_service: None,
+        customer_id: None,
+        secret: SECRET.to_string(),
+        enabled: true,
+    }
+}
+
+fn event(id: i64) -> OrderEvent {
+    OrderEvent {
+        id,
+        event_type: OrderEvent::ORDER_CREATED.to_string(),
+        order_uid: format!("order{id}"),
+        created_at: "2021-11-26T06:22:19Z".to_string(),
+        attempts: 0,
+        payload: json!({"order_uid": format!("order{id}"), "customer_id": "test", "delivery_service": "meest"}),
+    }
+}
+
+fn worker(database: &Database, max_attempts: i32, disable_after: i32) -> WebhookWorker<Database> {
+    WebhookWorker::new(
+        database.clone(),
+        WebhookConfig {
+            max_attempts,
+            disable_after,
+            base_backoff: Duration::from_secs(60),
+            max_backoff: Duration::from_secs(100),
+            request_timeout: Duration::from_secs(5),
+            ..Default::default()
+        },
+    )
+}
+
+/// Makes every pending delivery due right away, as if its backoff had passed.
+async fn make_due(test: &TestDatabase) {
+    test.client()
+        .await
+        .execute("UPDATE WebhookDeliveries SET next_attempt_at = now()", &[])
+        .await
+        .unwrap();
+}
+
+/// Seconds until the delivery is attempted again.
+async fn retry_in(test: &TestDatabase, delivery_id: i64) -> f64 {
+    test.client()
+        .await
+        .query_one(
+            "SELECT EXTRACT(EPOCH FROM next_attempt_at - now())::FLOAT8 FROM WebhookDeliveries WHERE id = $1",
+            &[&delivery_id],
+        )
+        .await
+        .unwrap()
+        .get(0)
+}
+
+async fn only_delivery(database: &Database, subscription_id: i64) -> WebhookDelivery {
+    let mut deliveries = database.deliveries(subscription_id, 10).await.unwrap();
+    assert_eq!(deliveries.len(), 1);
+    deliveries.remove(0)
+}
+
+fn app(database: &Database) -> axum::Router {
+    let state = AppState::new(Box::<MemoryRepository>::default(), Box::new(OrderService::new()))
+        .with_webhooks(Box::new(database.clone()));
+    router(Arc::new(state))
+}
+
+async fn call(app: &axum::Router, method: &str, uri: &str, body: Option<Value>) -> (StatusCode, Bytes) {
+    let request = Request::builder().method(method).uri(uri);
+    let request = match body {
+        Some(body) => request
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(Body::from(body.to_string())),
+        None => request.body(Body::empty()),
+    };
+    let response = app.clone().oneshot(request.unwrap()).await.unwrap();
+    let status = response.status();
+    (status, to_bytes(response.into_body(), usize::MAX).await.unwrap())
+}
+
+fn json_body(body: &Bytes) -> Value {
+    serde_json::from_slice(body).unwrap()
+}
+
+#[tokio::test]
+async fn invalid_subscriptions_are_rejected() {
+    let Some(test) = TestDatabase::create().await else { return };
+    let app = app(&test.database);
+    let valid = json!(subscription("https://shop.example/hook"));
+    let cases = [
+        ("url", json!("ftp://shop.example/hook"), "url must be an http(s) URL"),
+        ("event_types", json!([]), "event_types must not be empty"),
+        ("event_types", json!(["order.status_changed"]), "Unknown event type: order.status_changed"),
+        ("secret", json!("short"), "secret must be at least 16 characters long"),
+    ];
+    for (field, value, error) in cases {
+        let mut request = valid.clone();
+        request[field] = value;
+        let (status, body) = call(&app, "POST", "/webhooks", Some(request.clone())).await;
+        assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY, "{field}");
+        assert_eq!(json_body(&body)["error"], error);
+        let (status, _) = call(&app, "PUT", "/webhooks/1", Some(request)).await;
+        assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY, "{field}");
+    }
+    let (_, body) = call(&app, "GET", "/webhooks", None).await;
+    assert_eq!(json_body(&body), json!([]));
+    test.drop().await;
+}
+
+#[tokio::test]
+async fn subscriptions_can_be_managed() {
+    let Some(test) = TestDatabase::create().await else { return };
+    let app = app(&test.database);
+    let (status, body) = call(&app, "POST", "/webhooks", Some(json!(subscription("https://shop.example/hook")))).await;
+    assert_eq!(status, StatusCode::CREATED);
+    let created = json_body(&body);
+    assert_eq!(created["url"], "https://shop.example/hook");
+    assert_eq!(created["enabled"], true);
+    assert!(created.get("secret").is_none(), "The secret must not be returned");
+    let id = created["id"].as_i64().unwrap();
+
+    let (status, body) = call(&app, "GET", &format!("/webhooks/{id}"), None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(json_body(&body), created);
+    let (_, body) = call(&app, "GET", "/webhooks", None).await;
+    assert_eq!(json_body(&body), json!([created]));
+
+    let mut update = json!(subscription("https://shop.example/other"));
+    update["customer_id"] = json!("test");
+    let (status, body) = call(&app, "PUT", &format!("/webhooks/{id}"), Some(update.clone())).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(json_body(&body)["url"], "https://shop.example/other");
+    assert_eq!(json_body(&body)["customer_id"], "test");
+
+    let (status, body) = call(&app, "GET", &format!("/webhooks/{id}/deliveries"), None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(json_body(&body), json!([]));
+
+    let (status, body) = call(&app, "DELETE", &format!("/webhooks/{id}"), None).await;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+    assert!(body.is_empty(), "204 must not carry a body");
+    for (method, body) in [("GET", None), ("PUT", Some(update)), ("DELETE", None)] {
+        let (status, _) = call(&app, method, &format!("/webhooks/{id}"), body).await;
+        assert_eq!(status, StatusCode::NOT_FOUND, "{method}");
+    }
+    test.drop().await;
+}
+
+#[tokio::test]
+async fn webhook_responses_are_compressed() {
+    let Some(test) = TestDatabase::create().await else { return };
+    let app = app(&test.database);
+    call(&app, "POST", "/webhooks", Some(json!(subscription("https://shop.example/hook")))).await;
+    let request = Request::get("/webhooks").header(header::ACCEPT_ENCODING, "gzip").body(Body::empty()).unwrap();
+    let response = app.oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.headers()[header::CONTENT_ENCODING], "gzip");
+    test.drop().await;
+}
+
+#[tokio::test]
+async fn deliveries_are_signed_and_recorded() {
+    let Some(test) = TestDatabase::create().await else { return };
+    let receiver = Receiver::start().await;
+    let database = &test.database;
+    let matching = database.create(subscription(&receiver.url)).await.unwrap();
+    let other_customer = WebhookSubscriptionRequest {
+        customer_id: Some("someone else".to_string()),
+        ..subscription(&receiver.url)
+    };
+    let filtered = database.create(other_customer).await.unwrap();
+
+    assert_eq!(database.enqueue(&event(1)).await.unwrap(), 1);
+    assert_eq!(database.enqueue(&event(1)).await.unwrap(), 0, "The same event is enqueued once");
+    assert_eq!(worker(database, 3, 10).run_once().await.unwrap(), 1);
+
+    let received = receiver.received();
+    assert_eq!(received.len(), 1);
+    let (headers, body) = &received[0];
+    let timestamp: u64 = headers["X-Webhook-Timestamp"].to_str().unwrap().parse().unwrap();
+    assert_eq!(headers["X-Webhook-Signature"].to_str().unwrap(), sign(SECRET, timestamp, body));
+    assert_eq!(headers["X-Webhook-Event"], OrderEvent::ORDER_CREATED);
+    let body: Value = serde_json::from_slice(body).unwrap();
+    assert_eq!(body["id"], 1);
+    assert_eq!(body["data"]["order_uid"], "order1");
+
+    let delivery = only_delivery(database, matching.id).await;
+    assert_eq!(headers["X-Webhook-Id"].to_str().unwrap(), delivery.id.to_string());
+    assert_eq!(delivery.status, WebhookDelivery::DELIVERED);
+    assert_eq!((delivery.attempts, delivery.last_status_code), (1, Some(200)));
+    assert!(database.deliveries(filtered.id, 10).await.unwrap().is_empty());
+    assert_eq!(worker(database, 3, 10).run_once().await.unwrap(), 0);
+    test.drop().await;
+}
+
+#[tokio::test]
+async fn failed_deliveries_back_off_and_give_up() {
+    let Some(test) = TestDatabase::create().await else { return };
+    let receiver = Receiver::start().await;
+    receiver.respond_with(500);
+    let database = &test.database;
+    let worker = worker(database, 3, 10);
+    let subscription = database.create(subscription(&receiver.url)).await.unwrap();
+    database.enqueue(&event(1)).await.unwrap();
+
+    // Backoff doubles from 60 seconds and is capped at 100
+    for (attempt, backoff) in [(1, 60.0), (2, 100.0)] {
+        assert_eq!(worker.run_once().await.unwrap(), 1);
+        let delivery = only_delivery(database, subscription.id).await;
+        assert_eq!(delivery.status, WebhookDelivery::PENDING);
+        assert_eq!((delivery.attempts, delivery.last_status_code), (attempt, Some(500)));
+        let retry_in = retry_in(&test, delivery.id).await;
+        assert!((backoff - 5.0..=backoff).contains(&retry_in), "Attempt {attempt} retries in {retry_in}s");
+        assert_eq!(worker.run_once().await.unwrap(), 0, "The delivery isn't due yet");
+        make_due(&test).await;
+    }
+
+    assert_eq!(worker.run_once().await.unwrap(), 1);
+    let delivery = only_delivery(database, subscription.id).await;
+    assert_eq!((delivery.status.as_str(), delivery.attempts), (WebhookDelivery::FAILED, 3));
+    make_due(&test).await;
+    assert_eq!(worker.run_once().await.unwrap(), 0, "Failed deliveries are not retried");
+    assert_eq!(receiver.received().len(), 3);
+    test.drop().await;
+}
+
+#[tokio::test]
+async fn failing_subscriptions_are_disabled() {
+    let Some(test) = TestDatabase::create().await else { return };
+    let receiver = Receiver::start().await;
+    receiver.respond_with(503);
+    let database = &test.database;
+    let worker = worker(database, 8, 3);
+    let created = database.create(subscription(&receiver.url)).await.unwrap();
+    database.enqueue(&event(1)).await.unwrap();
+    database.enqueue(&event(2)).await.unwrap();
+
+    assert_eq!(worker.run_once().await.unwrap(), 2);
+    let stored = database.get(created.id).await.unwrap().unwrap();
+    assert_eq!((stored.enabled, stored.consecutive_failures), (true, 2));
+    make_due(&test).await;
+    assert_eq!(worker.run_once().await.unwrap(), 2);
+    let stored = database.get(created.id).await.unwrap().unwrap();
+    assert!(!stored.enabled);
+    assert!(stored.disabled_at.is_some());
+
+    make_due(&test).await;
+    assert_eq!(worker.run_once().await.unwrap(), 0, "Disabled subscriptions get no deliveries");
+    assert_eq!(database.enqueue(&event(3)).await.unwrap(), 0);
+
+    // Re-enabling starts over, and a success resets the failure counter
+    receiver.respond_with(204);
+    let stored = database.update(created.id, subscription(&receiver.url)).await.unwrap().unwrap();
+    assert_eq!((stored.enabled, stored.consecutive_failures, stored.disabled_at), (true, 0, None));
+    make_due(&test).await;
+    assert_eq!(worker.run_once().await.unwrap(), 2);
+    let stored = database.get(created.id).await.unwrap().unwrap();
+    assert_eq!((stored.enabled, stored.consecutive_failures), (true, 0));
+    test.drop().await;
+}
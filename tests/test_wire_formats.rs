@@ -0,0 +1,124 @@
+mod common;
+
+use axum::body::{to_bytes, Body, Bytes};
+use axum::http::{header, Request, StatusCode};
+use common::{item, MemoryRepository};
+use prost::Message;
+use std::sync::Arc;
+use tower::ServiceExt;
+use wb_tech_l0::application::wire::{orders, WireFormat};
+use wb_tech_l0::application::{router, AppState};
+use wb_tech_l0::infrastructure::OrderService;
+use wb_tech_l0::models::{Delivery, Item, Order};
+
+/// Has values at the edges of what each format has to carry.
+fn order(uid: &str) -> Order {
+    let email = "test@gmail.com".to_string();
+    let delivery = Delivery { name: "Тест Тестов".to_string(), email, ..Default::default() };
+    let extreme = Item { chrt_id: i32::MAX, nm_id: i32::MIN, ..Default::default() };
+    common::order(uid)
+        .delivery(delivery)
+        .items(vec![item(9934930), extreme])
+        .with(|order| {
+            order.payment.payment_dt = 1637907727;
+            order.payment.custom_fee = -1;
+        })
+        .build()
+}
+
+fn app() -> axum::Router {
+    let state = AppState::new(Box::<MemoryRepository>::default(), Box::new(OrderService::new()));
+    router(Arc::new(state))
+}
+
+async fn add(app: &axum::Router, content_type: &str, body: Vec<u8>) -> StatusCode {
+    let request = Request::post("/add_order")
+        .header(header::CONTENT_TYPE, content_type)
+        .body(Body::from(body))
+        .unwrap();
+    app.clone().oneshot(request).await.unwrap().status()
+}
+
+async fn get(app: &axum::Router, uid: &str, accept: Option<&str>) -> (StatusCode, String, Bytes) {
+    let mut request = Request::get(format!("/order/{uid}"));
+    if let Some(accept) = accept {
+        request = request.header(header::ACCEPT, accept);
+    }
+    let response = app.clone().oneshot(request.body(Body::empty()).unwrap()).await.unwrap();
+    let status = response.status();
+    let content_type = response.headers()[header::CONTENT_TYPE].to_str().unwrap().to_string();
+    (status, content_type, to_bytes(response.into_body(), usize::MAX).await.unwrap())
+}
+
+#[tokio::test]
+async fn orders_round_trip_in_every_format() {
+    let app = app();
+    let bodies = [
+        ("application/json", serde_json::to_vec(&order("json")).unwrap()),
+        ("application/msgpack", rmp_serde::to_vec_named(&order("msgpack")).unwrap()),
+        ("application/x-protobuf", orders::Order::from(order("protobuf")).encode_to_vec()),
+    ];
+    for (content_type, body) in bodies {
+        assert_eq!(add(&app, content_type, body).await, StatusCode::CREATED, "{content_type}");
+    }
+    for uid in ["json", "msgpack", "protobuf"] {
+        let (status, content_type, body) = get(&app, uid, None).await;
+        assert_eq!((status, content_type.as_str()), (StatusCode::OK, "application/json"));
+        assert_eq!(serde_json::from_slice::<Order>(&body).unwrap(), order(uid));
+
+        let (_, content_type, body) = get(&app, uid, Some("application/msgpack")).await;
+        assert_eq!(content_type, "application/msgpack");
+        assert_eq!(rmp_serde::from_slice::<Order>(&body).unwrap(), order(uid));
+
+        let (_, content_type, body) = get(&app, uid, Some("application/x-protobuf")).await;
+        assert_eq!(content_type, "application/x-protobuf");
+        assert_eq!(Order::from(orders::Order::decode(body).unwrap()), order(uid));
+    }
+}
+
+#[tokio::test]
+async fn accept_header_is_negotiated() {
+    let app = app();
+    assert_eq!(add(&app, "application/json", serde_json::to_vec(&order("order")).unwrap()).await, StatusCode::CREATED);
+    let negotiated = |accept: &'static str| {
+        let app = app.clone();
+        async move { get(&app, "order", Some(accept)).await.1 }
+    };
+    assert_eq!(negotiated("*/*").await, "application/json");
+    assert_eq!(negotiated("text/html").await, "application/json");
+    assert_eq!(negotiated("text/html, application/x-protobuf").await, "application/x-protobuf");
+    assert_eq!(negotiated("application/json;q=0.5, application/msgpack").await, "application/msgpack");
+    assert_eq!(negotiated("application/msgpack;q=0, application/json").await, "application/json");
+    assert_eq!(negotiated("application/x-protobuf, application/msgpack").await, "application/x-protobuf");
+
+    // Errors stay JSON whatever was asked for
+    let (status, content_type, _) = get(&app, "missing", Some("application/msgpack")).await;
+    assert_eq!((status, content_type.as_str()), (StatusCode::NOT_FOUND, "application/json"));
+}
+
+#[tokio::test]
+async fn undecodable_bodies_are_rejected() {
+    let app = app();
+    assert_eq!(add(&app, "application/msgpack", b"\xc1".to_vec()).await, StatusCode::BAD_REQUEST);
+    assert_eq!(add(&app, "application/x-protobuf", b"\xff\xff".to_vec()).await, StatusCode::BAD_REQUEST);
+    assert_eq!(add(&app, "text/plain", b"{}".to_vec()).await, StatusCode::UNSUPPORTED_MEDIA_TYPE);
+    // The same MessagePack order is fine once
+    let body = rmp_serde::to_vec_named(&order("twice")).unwrap();
+    assert_eq!(add(&app, "application/msgpack", body.clone()).await, StatusCode::CREATED);
+    assert_eq!(add(&app, "application/msgpack", body).await, StatusCode::CONFLICT);
+}
+
+#[test]
+fn request_body_format_follows_content_type() {
+    let headers = |content_type: &str| {
+        let mut headers = axum::http::HeaderMap::new();
+        headers.insert(header::CONTENT_TYPE, content_type.parse().unwrap());
+        headers
+    };
+    assert_eq!(WireFormat::of_body(&headers("application/json; charset=utf-8")), Some(WireFormat::Json));
+    assert_eq!(WireFormat::of_body(&headers("Application/MsgPack")), Some(WireFormat::MessagePack));
+    assert_eq!(WireFormat::of_body(&headers("application/x-msgpack")), Some(WireFormat::MessagePack));
+    assert_eq!(WireFormat::of_body(&headers("application/protobuf")), Some(WireFormat::Protobuf));
+    assert_eq!(WireFormat::of_body(&headers("text/csv")), None);
+    assert_eq!(WireFormat::of_body(&axum::http::HeaderMap::new()), None);
+}